@@ -0,0 +1,39 @@
+package idesyde.blueprints;
+
+import com.fasterxml.jackson.core.JsonProcessingException;
+import com.fasterxml.jackson.databind.annotation.JsonSerialize;
+
+import idesyde.core.DecisionModel;
+
+import java.util.List;
+import java.util.Optional;
+
+/**
+ * What a module answers at `/info/protocol`, so that the orchestrator can refuse or adapt to
+ * modules that speak another version of the protocol.
+ */
+@JsonSerialize
+public record ProtocolInfo(int version, List<String> encodings, List<String> features) {
+
+    /**
+     * The version of the messages exchanged with the orchestrator. It must match the one of the
+     * orchestrator for the module to be used.
+     */
+    public static final int PROTOCOL_VERSION = 1;
+
+    /**
+     * The protocol spoken by the JVM modules. Their caches only accept JSON and they do not
+     * have the batched cache endpoints.
+     */
+    public static ProtocolInfo current() {
+        return new ProtocolInfo(PROTOCOL_VERSION, List.of("json"), List.of());
+    }
+
+    public Optional<String> toJsonString() {
+        try {
+            return Optional.of(DecisionModel.objectMapper.writeValueAsString(this));
+        } catch (JsonProcessingException ignored) {
+            return Optional.empty();
+        }
+    }
+}
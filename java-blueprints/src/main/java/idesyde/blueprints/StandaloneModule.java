@@ -95,6 +95,8 @@ public interface StandaloneModule extends Module {
             server
                     .get("/info/unique_identifier", ctx -> ctx.result(uniqueIdentifier()))
                     .get("/info/is_caching", ctx -> ctx.result("true"))
+                    .get("/info/protocol",
+                            ctx -> ProtocolInfo.current().toJsonString().ifPresent(ctx::result))
                     .get("/decision/cache/exists",
                             ctx -> {
                                 if (ctx.isMultipartFormData()) {
@@ -78,6 +78,11 @@ public interface Explorer {
         public boolean strict = false;
         @JsonProperty("target_objectives")
         public Set<String> targetObjectives = new HashSet<>();
+        /**
+         * The seed for all randomness in the exploration, so that it can be reproduced.
+         * If it is null, the explorer seeds itself as it sees fit.
+         */
+        public Long seed = null;
 
         public Configuration() {
             // this.totalExplorationTimeOutInSecs = -1L;
@@ -92,7 +97,7 @@ public interface Explorer {
                     + ", improvementTimeOutInSecs=" + improvementTimeOutInSecs + ", maximumSolutions="
                     + maximumSolutions + ", improvementIterations=" + improvementIterations
                     + ", timeDiscretizationFactor=" + timeDiscretizationFactor + ", memoryDiscretizationFactor="
-                    + memoryDiscretizationFactor + ", strict=" + strict + "]";
+                    + memoryDiscretizationFactor + ", strict=" + strict + ", seed=" + seed + "]";
         }
 
         public static Optional<Configuration> fromJsonString(String s) {
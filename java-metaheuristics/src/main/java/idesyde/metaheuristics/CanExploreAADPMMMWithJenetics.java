@@ -23,6 +23,8 @@ import org.jgrapht.graph.SimpleDirectedGraph;
 
 import java.time.Duration;
 import java.util.*;
+import java.util.concurrent.Executor;
+import java.util.concurrent.ForkJoinPool;
 import java.util.function.Function;
 import java.util.stream.Collectors;
 import java.util.stream.IntStream;
@@ -229,6 +231,8 @@ public interface CanExploreAADPMMMWithJenetics extends AperiodicAsynchronousData
             jobIdxGraph.addVertex(dst);
             jobIdxGraph.addEdge(src, dst);
         });
+        // a fixed seed and a sequential evaluation make the exploration reproducible
+        final Executor executor = configuration.seed != null ? Runnable::run : ForkJoinPool.commonPool();
         var engine = Engine
                 .builder(g -> evaluateAADPMMM(g, jobs, jobIdxGraph, configuration),
                         allConstraints.constrain(codec))
@@ -240,6 +244,7 @@ public interface CanExploreAADPMMMWithJenetics extends AperiodicAsynchronousData
                 .alterers(
                         new UniformCrossover<>(0.2, 0.25),
                         new Mutator<>(0.25))
+                .executor(executor)
                 .minimizing()
                 .build();
         var solStream = engine
@@ -258,7 +263,11 @@ public interface CanExploreAADPMMMWithJenetics extends AperiodicAsynchronousData
         // var limitedSolStream = configuration.maximumSolutions > 0L
         // ? decodedStream.limit(configuration.maximumSolutions)
         // : decodedStream;
-        return limitedImprovementStream
+        // the seeded generator is only used while this exploration evolves
+        var seededStream = configuration.seed != null
+                ? ScopedRandom.pulledWith(new Random(configuration.seed), limitedImprovementStream)
+                : limitedImprovementStream;
+        return seededStream
                 .map(sol -> {
                     var decoded = codec.decode(sol.bestPhenotype().genotype());
                     var solMap = new HashMap<String, Double>(sol.bestFitness().length());
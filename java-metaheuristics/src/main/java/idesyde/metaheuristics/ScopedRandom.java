@@ -0,0 +1,39 @@
+package idesyde.metaheuristics;
+
+import io.jenetics.util.RandomRegistry;
+
+import java.util.Optional;
+import java.util.Spliterator;
+import java.util.Spliterators;
+import java.util.function.Consumer;
+import java.util.random.RandomGenerator;
+import java.util.stream.Stream;
+import java.util.stream.StreamSupport;
+
+/**
+ * Runs Jenetics streams with their own random generator, without replacing the generator
+ * of the whole JVM, which other explorations in the same embedded JVM also use.
+ */
+final class ScopedRandom {
+
+    private ScopedRandom() {
+    }
+
+    /**
+     * Pulls every element of {@code stream} with {@code random} as the generator of
+     * {@link RandomRegistry} for the current thread. The stream must be evaluated in the
+     * pulling thread, e.g. with a {@code Runnable::run} executor, for the generator to be used.
+     */
+    static <T> Stream<T> pulledWith(final RandomGenerator random, final Stream<T> stream) {
+        final var iterator = stream.iterator();
+        return StreamSupport.stream(new Spliterators.AbstractSpliterator<T>(Long.MAX_VALUE, Spliterator.ORDERED) {
+            @Override
+            public boolean tryAdvance(Consumer<? super T> action) {
+                Optional<T> next = RandomRegistry.with(random,
+                        r -> iterator.hasNext() ? Optional.of(iterator.next()) : Optional.empty());
+                next.ifPresent(action);
+                return next.isPresent();
+            }
+        }, false).onClose(stream::close);
+    }
+}
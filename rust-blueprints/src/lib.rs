@@ -1,4 +1,6 @@
 pub mod macros;
+#[cfg(feature = "server")]
+pub mod server;
 
 use std::{
     collections::{HashMap, HashSet},
@@ -11,6 +13,12 @@ use idesyde_core::{DecisionModel, ExplorationSolution, OpaqueDecisionModel};
 use serde::{Deserialize, Serialize};
 
 use base64::{engine::general_purpose, Engine as _};
+#[cfg(feature = "server")]
+use idesyde_core::{Module, RustEmbeddedModule};
+#[cfg(feature = "server")]
+use server::ModuleServer;
+#[cfg(feature = "server")]
+use std::sync::Arc;
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct ExplorationSolutionMessage {
@@ -32,7 +40,7 @@ impl ExplorationSolutionMessage {
         O: From<Vec<u8>>,
     {
         let mut buf: Vec<u8> = Vec::new();
-        ciborium::into_writer(self, buf.as_mut_slice())?;
+        ciborium::into_writer(self, &mut buf)?;
         Ok(buf.into())
     }
 
@@ -96,6 +104,79 @@ impl From<IdentificationResultMessage> for IdentificationResultCompactMessage {
     }
 }
 
+/// The version of the messages exchanged between the orchestrator and external modules,
+/// e.g. [ExplorationSolutionMessage] and [IdentificationResultCompactMessage].
+/// It is increased whenever one of them changes in a way that older peers cannot read.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// The feature of modules that have the batched `cache/missing`, `cache/add_all` and `cache/fetch_all` endpoints.
+pub const FEATURE_BATCHED_CACHE: &str = "batched_cache";
+
+/// What a module answers at `/info/protocol`, so that the orchestrator can refuse
+/// or adapt to modules that speak another version of the protocol.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolInfo {
+    pub version: u32,
+    /// The encodings accepted for models, e.g. `json` and `cbor`.
+    #[serde(default)]
+    pub encodings: Vec<String>,
+    /// Optional parts of the protocol that the module implements, e.g. [FEATURE_BATCHED_CACHE].
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+impl ProtocolInfo {
+    /// The protocol spoken by this version of the Rust modules and orchestrator.
+    pub fn current() -> ProtocolInfo {
+        ProtocolInfo {
+            version: PROTOCOL_VERSION,
+            encodings: vec!["json".to_string(), "cbor".to_string()],
+            features: vec![FEATURE_BATCHED_CACHE.to_string()],
+        }
+    }
+
+    /// The protocol assumed for modules that predate the `/info/protocol` handshake.
+    pub fn legacy() -> ProtocolInfo {
+        ProtocolInfo {
+            version: 1,
+            encodings: vec!["json".to_string()],
+            features: vec![],
+        }
+    }
+
+    pub fn supports_encoding(&self, encoding: &str) -> bool {
+        self.encodings
+            .iter()
+            .any(|e| e.eq_ignore_ascii_case(encoding))
+    }
+
+    pub fn supports_feature(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+    }
+
+    /// Two peers can talk if they speak the same protocol version and share at least one encoding.
+    /// The error explains why they cannot.
+    pub fn check_compatibility(&self, other: &ProtocolInfo) -> Result<(), String> {
+        if self.version != other.version {
+            return Err(format!(
+                "protocol version {} is not compatible with version {}",
+                other.version, self.version
+            ));
+        }
+        if !self.encodings.iter().any(|e| other.supports_encoding(e)) {
+            return Err(format!(
+                "none of the encodings {:?} is supported, only {:?}",
+                other.encodings, self.encodings
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn from_json_str(s: &str) -> Result<ProtocolInfo, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+}
+
 impl From<&ExplorationSolutionMessage> for OpaqueDecisionModel {
     fn from(value: &ExplorationSolutionMessage) -> Self {
         OpaqueDecisionModel {
@@ -184,6 +265,64 @@ pub struct ModuleArgs {
         default_value = "false"
     )]
     print_schema: bool,
+    #[arg(
+        long = "server",
+        help = "The type of server to start. Currently only 'http' is supported."
+    )]
+    server_type: Option<String>,
+    #[arg(
+        long = "server-address",
+        help = "The address the server binds to. Default is a random local port.",
+        default_value = "127.0.0.1:0"
+    )]
+    server_address: String,
+}
+
+/// Entry point for executables of Rust modules.
+///
+/// With `--server http` the module is served through the HTTP and websocket module protocol
+/// and `INITIALIZED <port> <unique identifier>` is printed once it is ready,
+/// which is what the orchestrator waits for when it launches local modules.
+///
+/// Only available with the `server` feature.
+#[cfg(feature = "server")]
+pub fn execute_standalone_module(module: RustEmbeddedModule) {
+    match ModuleArgs::try_parse() {
+        Ok(args) => {
+            if args.print_schema {
+                for schema in &module.decision_model_json_schemas {
+                    println!("{}", schema);
+                }
+            } else if let Some(server_type) = args.server_type {
+                if !server_type.eq_ignore_ascii_case("http") {
+                    eprintln!(
+                        "Unknown server type {}. Only 'http' is supported.",
+                        server_type
+                    );
+                    std::process::exit(64);
+                }
+                let unique_identifier = module.unique_identifier();
+                match ModuleServer::bind(Arc::new(module), &args.server_address) {
+                    Ok(server) => {
+                        println!(
+                            "INITIALIZED {} {}",
+                            server.port().unwrap_or(0),
+                            unique_identifier
+                        );
+                        server.run();
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to start the module server: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
+        Err(_) => {
+            eprintln!("Incorrect combination of parameters/options. Check usage with -h/--help.");
+            std::process::exit(64);
+        }
+    }
 }
 
 // pub fn execute_standalone_module(module: StandaloneModule) {
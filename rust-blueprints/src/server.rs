@@ -0,0 +1,874 @@
+//! A HTTP and websocket server that exposes any [Module] through the same protocol
+//! that the JVM modules implement, so that Rust modules can also run out-of-process
+//! (or in another machine) and be consumed by the orchestrator as external modules.
+//!
+//! The endpoints are:
+//!
+//! - `GET /info/unique_identifier` and `GET /info/is_caching`.
+//! - `GET /info/protocol` which returns the [ProtocolInfo] of the server.
+//! - `GET /{decision,design,solved}/cache/exists` with the SHA2 hash of the model as the body,
+//!   or a multipart form with `category` and `part` (or `elements`) fields.
+//! - `GET /{decision,design,solved,reversed}/cache/fetch` with the SHA2 hash of the model as the body.
+//! - `POST /{decision,design,solved,reversed}/cache/add` with a multipart form containing the
+//!   opaque model in the field `decisionModel`, `designModel`, `solvedModel` or `reversedModel`.
+//! - `POST /{decision,design,solved,reversed}/cache/clear`.
+//! - `POST /{decision,design,solved}/cache/missing` with a JSON list of base64 encoded hashes,
+//!   which answers the JSON list of those that are not in the cache.
+//! - `POST /{decision,design,solved,reversed}/cache/add_all` like `cache/add`, but with any
+//!   number of models in the form.
+//! - `POST /{decision,design,solved,reversed}/cache/fetch_all` with a JSON list of base64 encoded hashes,
+//!   which answers the JSON list of the opaque models found in the cache.
+//! - `POST /identify` which runs one identification step over the cached models and returns
+//!   a [IdentificationResultCompactMessage] with the hashes of the identified decision models.
+//! - `POST /reverse` which reverse identifies the cached solved decision models and returns
+//!   the list of hashes of the reverse identified design models.
+//! - `GET /explorers` which returns the list of explorer identifiers.
+//! - `GET /{explorer}/bid` with the SHA2 hash of a cached decision model as the body,
+//!   or a multipart form with a `decisionModel` field.
+//! - `ws://.../{explorer}/explore` which receives the decision model, the previous solutions and the
+//!   configuration, starts exploring at a `done` message and answers every solution found as a
+//!   [ExplorationSolutionMessage], followed by a final `done`.
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+};
+
+use base64::{engine::general_purpose, Engine as _};
+use idesyde_core::{
+    DecisionModel, DesignModel, ExplorationBid, ExplorationConfiguration,
+    ExplorationConfigurationBuilder, ExplorationSolution, Explorer, Module, OpaqueDecisionModel,
+    OpaqueDesignModel,
+};
+use log::debug;
+use tiny_http::{Header, Method, Request, Response};
+use tungstenite::{protocol::Role, Message, WebSocket};
+
+use crate::{ExplorationSolutionMessage, IdentificationResultCompactMessage, ProtocolInfo};
+
+/// One field of a `multipart/form-data` body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormField {
+    pub name: String,
+    pub content_type: Option<String>,
+    pub data: Vec<u8>,
+}
+
+impl FormField {
+    pub fn as_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.data).ok()
+    }
+}
+
+/// Parses a `multipart/form-data` body given the value of its `Content-Type` header.
+///
+/// Returns `None` if the content type is not multipart or has no boundary.
+pub fn parse_multipart_form(content_type: &str, body: &[u8]) -> Option<Vec<FormField>> {
+    if !content_type
+        .trim_start()
+        .to_lowercase()
+        .starts_with("multipart/form-data")
+    {
+        return None;
+    }
+    let boundary = content_type.split(';').find_map(|param| {
+        param
+            .trim()
+            .strip_prefix("boundary=")
+            .map(|b| b.trim_matches('"').to_string())
+    })?;
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut fields = Vec::new();
+    let mut rest = body;
+    // skip the preamble up to the first delimiter
+    let first = find_subslice(rest, &delimiter)?;
+    rest = &rest[first + delimiter.len()..];
+    loop {
+        // the final delimiter is followed by "--"
+        if rest.starts_with(b"--") {
+            break;
+        }
+        rest = rest.strip_prefix(b"\r\n").unwrap_or(rest);
+        let headers_end = find_subslice(rest, b"\r\n\r\n")?;
+        let headers = String::from_utf8_lossy(&rest[..headers_end]).to_string();
+        rest = &rest[headers_end + 4..];
+        let data_end = find_subslice(rest, &delimiter)?;
+        // the data is followed by a CRLF before the delimiter
+        let data = rest[..data_end]
+            .strip_suffix(b"\r\n")
+            .unwrap_or(&rest[..data_end]);
+        let mut name = None;
+        let mut part_content_type = None;
+        for header in headers.split("\r\n") {
+            if let Some((key, value)) = header.split_once(':') {
+                if key.trim().eq_ignore_ascii_case("content-disposition") {
+                    name = value.split(';').find_map(|param| {
+                        param
+                            .trim()
+                            .strip_prefix("name=")
+                            .map(|n| n.trim_matches('"').to_string())
+                    });
+                } else if key.trim().eq_ignore_ascii_case("content-type") {
+                    part_content_type = Some(value.trim().to_string());
+                }
+            }
+        }
+        if let Some(name) = name {
+            fields.push(FormField {
+                name,
+                content_type: part_content_type,
+                data: data.to_vec(),
+            });
+        }
+        rest = &rest[data_end + delimiter.len()..];
+    }
+    Some(fields)
+}
+
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// The state shared by all connections of a [ModuleServer].
+struct ModuleServerState {
+    module: Arc<dyn Module>,
+    explorers: Vec<Arc<dyn Explorer>>,
+    decision_cache: Mutex<HashMap<Vec<u8>, Arc<dyn DecisionModel>>>,
+    design_cache: Mutex<HashMap<Vec<u8>, Arc<dyn DesignModel>>>,
+    solved_cache: Mutex<HashMap<Vec<u8>, Arc<dyn DecisionModel>>>,
+    reversed_cache: Mutex<HashMap<Vec<u8>, Arc<dyn DesignModel>>>,
+}
+
+/// A server that exposes a [Module] through the HTTP and websocket module protocol.
+///
+/// The server can be run blocking with [ModuleServer::run], which is what a standalone module executable does,
+/// or in the background with [ModuleServer::spawn], which is useful to run the module in-process.
+pub struct ModuleServer {
+    server: Arc<tiny_http::Server>,
+    state: Arc<ModuleServerState>,
+}
+
+impl ModuleServer {
+    /// Binds the server to `address`, e.g. `127.0.0.1:0` for a random free local port.
+    pub fn bind(
+        module: Arc<dyn Module>,
+        address: &str,
+    ) -> Result<ModuleServer, Box<dyn std::error::Error + Send + Sync>> {
+        let server = tiny_http::Server::http(address)?;
+        Ok(ModuleServer {
+            server: Arc::new(server),
+            state: Arc::new(ModuleServerState {
+                explorers: module.explorers(),
+                module,
+                decision_cache: Mutex::new(HashMap::new()),
+                design_cache: Mutex::new(HashMap::new()),
+                solved_cache: Mutex::new(HashMap::new()),
+                reversed_cache: Mutex::new(HashMap::new()),
+            }),
+        })
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.server.server_addr().to_ip().map(|addr| addr.port())
+    }
+
+    pub fn url(&self) -> Option<String> {
+        self.server
+            .server_addr()
+            .to_ip()
+            .map(|addr| format!("http://{}", addr))
+    }
+
+    /// Serves requests until the server is stopped. Every request is served in its own thread
+    /// since websocket explorations can be long-lived.
+    pub fn run(&self) {
+        for request in self.server.incoming_requests() {
+            let state = self.state.clone();
+            std::thread::spawn(move || handle_request(state, request));
+        }
+    }
+
+    /// Serves requests in a background thread.
+    /// The returned [ModuleServerHandle] stops the server when it is dropped.
+    pub fn spawn(self) -> ModuleServerHandle {
+        let server = self.server.clone();
+        let url = self.url();
+        let thread = std::thread::spawn(move || self.run());
+        ModuleServerHandle {
+            server,
+            url,
+            thread: Some(thread),
+        }
+    }
+}
+
+/// A handle to a [ModuleServer] running in the background.
+pub struct ModuleServerHandle {
+    server: Arc<tiny_http::Server>,
+    url: Option<String>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ModuleServerHandle {
+    pub fn url(&self) -> Option<String> {
+        self.url.to_owned()
+    }
+
+    pub fn stop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for ModuleServerHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn text_response(status: u16, text: &str) -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_string(text).with_status_code(status)
+}
+
+fn json_response(text: String) -> Response<std::io::Cursor<Vec<u8>>> {
+    let response = Response::from_string(text);
+    match Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]) {
+        Ok(header) => response.with_header(header),
+        Err(_) => response,
+    }
+}
+
+fn header_value(request: &Request, name: &'static str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv(name))
+        .map(|h| h.value.to_string())
+}
+
+fn read_form(request: &Request, body: &[u8]) -> Option<Vec<FormField>> {
+    header_value(request, "Content-Type").and_then(|ct| parse_multipart_form(&ct, body))
+}
+
+fn encode_hash(hash: &[u8]) -> String {
+    general_purpose::STANDARD_NO_PAD.encode(hash)
+}
+
+fn decode_opaque_decision(field: &FormField) -> Option<OpaqueDecisionModel> {
+    let is_cbor = field
+        .content_type
+        .as_ref()
+        .map(|ct| ct.eq_ignore_ascii_case("application/cbor"))
+        .unwrap_or(false);
+    if is_cbor {
+        OpaqueDecisionModel::from_cbor(field.data.as_slice()).ok()
+    } else {
+        field
+            .as_str()
+            .and_then(|s| OpaqueDecisionModel::from_json_str(s).ok())
+    }
+}
+
+fn decode_opaque_design(field: &FormField) -> Option<OpaqueDesignModel> {
+    let is_cbor = field
+        .content_type
+        .as_ref()
+        .map(|ct| ct.eq_ignore_ascii_case("application/cbor"))
+        .unwrap_or(false);
+    if is_cbor {
+        OpaqueDesignModel::from_cbor(field.data.as_slice()).ok()
+    } else {
+        field
+            .as_str()
+            .and_then(|s| OpaqueDesignModel::from_json_str(s).ok())
+    }
+}
+
+/// Answers whether a model exists in the cache either by its hash or by a category and a set of elements.
+fn cache_exists<F>(
+    request: &Request,
+    body: &[u8],
+    keys_and_parts: Vec<(Vec<u8>, String, HashSet<String>)>,
+    part_field: F,
+) -> bool
+where
+    F: Fn(&str) -> bool,
+{
+    if let Some(fields) = read_form(request, body) {
+        let category = fields
+            .iter()
+            .find(|f| f.name == "category")
+            .and_then(|f| f.as_str())
+            .map(|s| s.to_string());
+        let elements: HashSet<String> = fields
+            .iter()
+            .filter(|f| part_field(&f.name))
+            .filter_map(|f| f.as_str().map(|s| s.to_string()))
+            .collect();
+        category
+            .map(|c| {
+                keys_and_parts
+                    .iter()
+                    .any(|(_, cat, part)| cat == &c && part.is_superset(&elements))
+            })
+            .unwrap_or(false)
+    } else {
+        keys_and_parts.iter().any(|(k, _, _)| k.as_slice() == body)
+    }
+}
+
+fn handle_request(state: Arc<ModuleServerState>, mut request: Request) {
+    let path = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or("/")
+        .trim_end_matches('/')
+        .to_string();
+    let is_websocket = header_value(&request, "Upgrade")
+        .map(|u| u.eq_ignore_ascii_case("websocket"))
+        .unwrap_or(false);
+    if is_websocket {
+        if let Some(explorer_name) = path
+            .strip_prefix('/')
+            .and_then(|p| p.strip_suffix("/explore"))
+        {
+            handle_explore(state, request, explorer_name.to_string());
+        } else {
+            let _ = request.respond(text_response(404, "Unknown websocket endpoint"));
+        }
+        return;
+    }
+    let mut body = Vec::new();
+    if let Err(e) = request.as_reader().read_to_end(&mut body) {
+        debug!("Failed to read request body for {}: {}", path, e);
+        let _ = request.respond(text_response(400, "Failed to read body"));
+        return;
+    }
+    let method = request.method().clone();
+    let response = match (method, path.as_str()) {
+        (Method::Get, "/info/unique_identifier") => {
+            text_response(200, &state.module.unique_identifier())
+        }
+        (Method::Get, "/info/is_caching") => text_response(200, "true"),
+        (Method::Get, "/info/protocol") => match serde_json::to_string(&ProtocolInfo::current()) {
+            Ok(s) => json_response(s),
+            Err(_) => text_response(500, "Failed to serialize protocol information"),
+        },
+        (Method::Get, "/decision/cache/exists") => {
+            let entries = decision_cache_entries(&state.decision_cache);
+            let exists = cache_exists(&request, &body, entries, |n| n == "part");
+            text_response(200, if exists { "true" } else { "false" })
+        }
+        (Method::Get, "/solved/cache/exists") => {
+            let entries = decision_cache_entries(&state.solved_cache);
+            let exists = cache_exists(&request, &body, entries, |n| n == "part");
+            text_response(200, if exists { "true" } else { "false" })
+        }
+        (Method::Get, "/design/cache/exists") => {
+            let entries = design_cache_entries(&state.design_cache);
+            let exists = cache_exists(&request, &body, entries, |n| n == "elements");
+            text_response(200, if exists { "true" } else { "false" })
+        }
+        (Method::Get, "/decision/cache/fetch") => fetch_decision(&state.decision_cache, &body),
+        (Method::Get, "/solved/cache/fetch") => fetch_decision(&state.solved_cache, &body),
+        (Method::Get, "/design/cache/fetch") => fetch_design(&state.design_cache, &body),
+        (Method::Get, "/reversed/cache/fetch") => fetch_design(&state.reversed_cache, &body),
+        (Method::Post, "/decision/cache/add") => match read_form(&request, &body) {
+            Some(fields) => add_decision(&state.decision_cache, &fields, "decisionModel"),
+            None => text_response(400, "Expected a multipart form"),
+        },
+        (Method::Post, "/solved/cache/add") => match read_form(&request, &body) {
+            Some(fields) => add_decision(&state.solved_cache, &fields, "solvedModel"),
+            None => text_response(400, "Expected a multipart form"),
+        },
+        (Method::Post, "/design/cache/add") => match read_form(&request, &body) {
+            Some(fields) => add_design(&state.design_cache, &fields, "designModel"),
+            None => text_response(400, "Expected a multipart form"),
+        },
+        (Method::Post, "/reversed/cache/add") => match read_form(&request, &body) {
+            Some(fields) => add_design(&state.reversed_cache, &fields, "reversedModel"),
+            None => text_response(400, "Expected a multipart form"),
+        },
+        (Method::Post, "/decision/cache/missing") => missing_in_cache(&state.decision_cache, &body),
+        (Method::Post, "/solved/cache/missing") => missing_in_cache(&state.solved_cache, &body),
+        (Method::Post, "/design/cache/missing") => missing_in_cache(&state.design_cache, &body),
+        (Method::Post, "/decision/cache/add_all") => match read_form(&request, &body) {
+            Some(fields) => add_decision(&state.decision_cache, &fields, "decisionModel"),
+            None => text_response(400, "Expected a multipart form"),
+        },
+        (Method::Post, "/solved/cache/add_all") => match read_form(&request, &body) {
+            Some(fields) => add_decision(&state.solved_cache, &fields, "solvedModel"),
+            None => text_response(400, "Expected a multipart form"),
+        },
+        (Method::Post, "/design/cache/add_all") => match read_form(&request, &body) {
+            Some(fields) => add_design(&state.design_cache, &fields, "designModel"),
+            None => text_response(400, "Expected a multipart form"),
+        },
+        (Method::Post, "/reversed/cache/add_all") => match read_form(&request, &body) {
+            Some(fields) => add_design(&state.reversed_cache, &fields, "reversedModel"),
+            None => text_response(400, "Expected a multipart form"),
+        },
+        (Method::Post, "/decision/cache/fetch_all") => {
+            fetch_all_decision(&state.decision_cache, &body)
+        }
+        (Method::Post, "/solved/cache/fetch_all") => fetch_all_decision(&state.solved_cache, &body),
+        (Method::Post, "/design/cache/fetch_all") => fetch_all_design(&state.design_cache, &body),
+        (Method::Post, "/reversed/cache/fetch_all") => {
+            fetch_all_design(&state.reversed_cache, &body)
+        }
+        (Method::Post, "/decision/cache/clear") => {
+            clear_cache(&state.decision_cache);
+            text_response(200, "OK")
+        }
+        (Method::Post, "/solved/cache/clear") => {
+            clear_cache(&state.solved_cache);
+            text_response(200, "OK")
+        }
+        (Method::Post, "/design/cache/clear") => {
+            clear_cache(&state.design_cache);
+            text_response(200, "OK")
+        }
+        (Method::Post, "/reversed/cache/clear") => {
+            clear_cache(&state.reversed_cache);
+            text_response(200, "OK")
+        }
+        (Method::Post, "/identify") => identify(&state),
+        (Method::Post, "/reverse") => reverse(&state),
+        (Method::Get, "/explorers") => {
+            let names: Vec<String> = state
+                .explorers
+                .iter()
+                .map(|e| e.unique_identifier())
+                .collect();
+            match serde_json::to_string(&names) {
+                Ok(s) => json_response(s),
+                Err(_) => text_response(500, "Failed to serialize explorers"),
+            }
+        }
+        (Method::Get, p) if p.ends_with("/bid") => {
+            let explorer_name = p.trim_start_matches('/').trim_end_matches("/bid");
+            bid(&state, &request, &body, explorer_name)
+        }
+        _ => text_response(404, "Unknown endpoint"),
+    };
+    if let Err(e) = request.respond(response) {
+        debug!("Failed to respond to request for {}: {}", path, e);
+    }
+}
+
+fn decision_cache_entries(
+    cache: &Mutex<HashMap<Vec<u8>, Arc<dyn DecisionModel>>>,
+) -> Vec<(Vec<u8>, String, HashSet<String>)> {
+    cache
+        .lock()
+        .map(|c| {
+            c.iter()
+                .map(|(k, m)| (k.to_owned(), m.category(), m.part()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn design_cache_entries(
+    cache: &Mutex<HashMap<Vec<u8>, Arc<dyn DesignModel>>>,
+) -> Vec<(Vec<u8>, String, HashSet<String>)> {
+    cache
+        .lock()
+        .map(|c| {
+            c.iter()
+                .map(|(k, m)| (k.to_owned(), m.category(), m.elements()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn clear_cache<K, V>(cache: &Mutex<HashMap<K, V>>) {
+    if let Ok(mut c) = cache.lock() {
+        c.clear();
+    }
+}
+
+/// Decodes a JSON list of base64 encoded hashes, as used by the batched cache endpoints.
+fn decode_hash_list(body: &[u8]) -> Option<Vec<Vec<u8>>> {
+    serde_json::from_slice::<Vec<String>>(body)
+        .ok()
+        .map(|hashes| {
+            hashes
+                .iter()
+                .filter_map(|h| general_purpose::STANDARD_NO_PAD.decode(h).ok())
+                .collect()
+        })
+}
+
+fn missing_in_cache<V>(
+    cache: &Mutex<HashMap<Vec<u8>, V>>,
+    body: &[u8],
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    match decode_hash_list(body) {
+        Some(hashes) => {
+            let missing: Vec<String> = cache
+                .lock()
+                .map(|c| {
+                    hashes
+                        .iter()
+                        .filter(|h| !c.contains_key(h.as_slice()))
+                        .map(|h| encode_hash(h))
+                        .collect()
+                })
+                .unwrap_or_default();
+            match serde_json::to_string(&missing) {
+                Ok(s) => json_response(s),
+                Err(_) => text_response(500, "Failed to serialize missing hashes"),
+            }
+        }
+        None => text_response(400, "Expected a JSON list of hashes"),
+    }
+}
+
+fn fetch_all_decision(
+    cache: &Mutex<HashMap<Vec<u8>, Arc<dyn DecisionModel>>>,
+    body: &[u8],
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    match decode_hash_list(body) {
+        Some(hashes) => {
+            let found: Vec<OpaqueDecisionModel> = cache
+                .lock()
+                .map(|c| {
+                    hashes
+                        .iter()
+                        .filter_map(|h| c.get(h.as_slice()))
+                        .map(|m| OpaqueDecisionModel::from(m.as_ref()))
+                        .collect()
+                })
+                .unwrap_or_default();
+            match serde_json::to_string(&found) {
+                Ok(s) => json_response(s),
+                Err(_) => text_response(500, "Failed to serialize decision models"),
+            }
+        }
+        None => text_response(400, "Expected a JSON list of hashes"),
+    }
+}
+
+fn fetch_all_design(
+    cache: &Mutex<HashMap<Vec<u8>, Arc<dyn DesignModel>>>,
+    body: &[u8],
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    match decode_hash_list(body) {
+        Some(hashes) => {
+            let found: Vec<OpaqueDesignModel> = cache
+                .lock()
+                .map(|c| {
+                    hashes
+                        .iter()
+                        .filter_map(|h| c.get(h.as_slice()))
+                        .map(|m| OpaqueDesignModel::from(m.as_ref()))
+                        .collect()
+                })
+                .unwrap_or_default();
+            match serde_json::to_string(&found) {
+                Ok(s) => json_response(s),
+                Err(_) => text_response(500, "Failed to serialize design models"),
+            }
+        }
+        None => text_response(400, "Expected a JSON list of hashes"),
+    }
+}
+
+fn fetch_decision(
+    cache: &Mutex<HashMap<Vec<u8>, Arc<dyn DecisionModel>>>,
+    hash: &[u8],
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    let found = cache.lock().ok().and_then(|c| c.get(hash).cloned());
+    match found.and_then(|m| OpaqueDecisionModel::from(m.as_ref()).to_json().ok()) {
+        Some(s) => json_response(s),
+        None => text_response(404, "Not in cache"),
+    }
+}
+
+fn fetch_design(
+    cache: &Mutex<HashMap<Vec<u8>, Arc<dyn DesignModel>>>,
+    hash: &[u8],
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    let found = cache.lock().ok().and_then(|c| c.get(hash).cloned());
+    match found.and_then(|m| OpaqueDesignModel::from(m.as_ref()).to_json().ok()) {
+        Some(s) => json_response(s),
+        None => text_response(404, "Not in cache"),
+    }
+}
+
+fn add_decision(
+    cache: &Mutex<HashMap<Vec<u8>, Arc<dyn DecisionModel>>>,
+    fields: &[FormField],
+    field_name: &str,
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    let opaques: Vec<OpaqueDecisionModel> = fields
+        .iter()
+        .filter(|f| f.name == field_name)
+        .filter_map(decode_opaque_decision)
+        .collect();
+    if opaques.is_empty() {
+        return text_response(400, "No decision model could be read");
+    }
+    let mut hashes = Vec::new();
+    if let Ok(mut c) = cache.lock() {
+        for opaque in opaques {
+            let hash = opaque.global_sha2_hash();
+            hashes.push(encode_hash(&hash));
+            c.insert(hash, Arc::new(opaque));
+        }
+    }
+    text_response(200, &hashes.join(","))
+}
+
+fn add_design(
+    cache: &Mutex<HashMap<Vec<u8>, Arc<dyn DesignModel>>>,
+    fields: &[FormField],
+    field_name: &str,
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    let opaques: Vec<OpaqueDesignModel> = fields
+        .iter()
+        .filter(|f| f.name == field_name)
+        .filter_map(decode_opaque_design)
+        .collect();
+    if opaques.is_empty() {
+        return text_response(400, "No design model could be read");
+    }
+    let mut hashes = Vec::new();
+    if let Ok(mut c) = cache.lock() {
+        for opaque in opaques {
+            let hash = opaque.global_sha2_hash();
+            hashes.push(encode_hash(&hash));
+            c.insert(hash, Arc::new(opaque));
+        }
+    }
+    text_response(200, &hashes.join(","))
+}
+
+fn identify(state: &ModuleServerState) -> Response<std::io::Cursor<Vec<u8>>> {
+    let decision_models: Vec<Arc<dyn DecisionModel>> = state
+        .decision_cache
+        .lock()
+        .map(|c| c.values().cloned().collect())
+        .unwrap_or_default();
+    let design_models: Vec<Arc<dyn DesignModel>> = state
+        .design_cache
+        .lock()
+        .map(|c| c.values().cloned().collect())
+        .unwrap_or_default();
+    debug!(
+        "Running a identification step with {} and {} decision and design models",
+        decision_models.len(),
+        design_models.len()
+    );
+    let (identified, messages) = state
+        .module
+        .identification_step(&decision_models, &design_models);
+    let mut identified_hashes = HashSet::new();
+    if let Ok(mut c) = state.decision_cache.lock() {
+        for m in identified {
+            let hash = m.global_sha2_hash();
+            identified_hashes.insert(encode_hash(&hash));
+            c.insert(hash, m);
+        }
+    }
+    let result = IdentificationResultCompactMessage {
+        identified: identified_hashes,
+        messages: messages.into_iter().collect(),
+    };
+    match serde_json::to_string(&result) {
+        Ok(s) => json_response(s),
+        Err(_) => text_response(500, "Failed to serialize identification result"),
+    }
+}
+
+fn reverse(state: &ModuleServerState) -> Response<std::io::Cursor<Vec<u8>>> {
+    let solved_models: Vec<Arc<dyn DecisionModel>> = state
+        .solved_cache
+        .lock()
+        .map(|c| c.values().cloned().collect())
+        .unwrap_or_default();
+    let design_models: Vec<Arc<dyn DesignModel>> = state
+        .design_cache
+        .lock()
+        .map(|c| c.values().cloned().collect())
+        .unwrap_or_default();
+    debug!(
+        "Running a reverse identification with {} and {} decision and design models",
+        solved_models.len(),
+        design_models.len()
+    );
+    // only the rules are run, as the orchestrator does for embedded modules, since
+    // a module's reverse_identification may itself be implemented with the same rules.
+    let mut reversed = Vec::new();
+    for rrule in state.module.reverse_identification_rules() {
+        let (models, messages) = rrule.reverse_identify(&solved_models, &design_models);
+        for msg in messages {
+            debug!("{}", msg);
+        }
+        reversed.extend(models);
+    }
+    let mut reversed_hashes = Vec::new();
+    if let Ok(mut c) = state.reversed_cache.lock() {
+        for m in reversed {
+            let hash = m.global_sha2_hash();
+            reversed_hashes.push(encode_hash(&hash));
+            c.insert(hash, m);
+        }
+    }
+    match serde_json::to_string(&reversed_hashes) {
+        Ok(s) => json_response(s),
+        Err(_) => text_response(500, "Failed to serialize reverse identification result"),
+    }
+}
+
+fn bid(
+    state: &ModuleServerState,
+    request: &Request,
+    body: &[u8],
+    explorer_name: &str,
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    let explorer = match state
+        .explorers
+        .iter()
+        .find(|e| e.unique_identifier().eq_ignore_ascii_case(explorer_name))
+    {
+        Some(e) => e.clone(),
+        None => return text_response(404, "Unknown explorer"),
+    };
+    let decision_model: Option<Arc<dyn DecisionModel>> = match read_form(request, body) {
+        Some(fields) => fields
+            .iter()
+            .filter(|f| f.name.starts_with("decisionModel"))
+            .find_map(decode_opaque_decision)
+            .map(|m| Arc::new(m) as Arc<dyn DecisionModel>),
+        None => state
+            .decision_cache
+            .lock()
+            .ok()
+            .and_then(|c| c.get(body).cloned()),
+    };
+    match decision_model {
+        Some(m) => {
+            let bid: ExplorationBid = explorer.bid(m);
+            match serde_json::to_string(&bid) {
+                Ok(s) => json_response(s),
+                Err(_) => text_response(500, "Failed to serialize bid"),
+            }
+        }
+        None => text_response(404, "Not in cache"),
+    }
+}
+
+/// The accept key of a websocket handshake, as defined in RFC 6455.
+fn websocket_accept_response(request: &Request) -> Option<Response<std::io::Empty>> {
+    let key = header_value(request, "Sec-WebSocket-Key")?;
+    let accept = tungstenite::handshake::derive_accept_key(key.trim().as_bytes());
+    let header = Header::from_bytes(&b"Sec-WebSocket-Accept"[..], accept.as_bytes()).ok()?;
+    Some(Response::empty(101).with_header(header))
+}
+
+fn handle_explore(state: Arc<ModuleServerState>, request: Request, explorer_name: String) {
+    let explorer = match state
+        .explorers
+        .iter()
+        .find(|e| e.unique_identifier().eq_ignore_ascii_case(&explorer_name))
+    {
+        Some(e) => e.clone(),
+        None => {
+            let _ = request.respond(text_response(404, "Unknown explorer"));
+            return;
+        }
+    };
+    let response = match websocket_accept_response(&request) {
+        Some(r) => r,
+        None => {
+            let _ = request.respond(text_response(400, "Invalid websocket handshake"));
+            return;
+        }
+    };
+    let stream = request.upgrade("websocket", response);
+    let mut ws = WebSocket::from_raw_socket(stream, Role::Server, None);
+    let mut decision_model: Option<Arc<dyn DecisionModel>> = None;
+    let mut previous_solutions: HashSet<ExplorationSolution> = HashSet::new();
+    // no limits until the client sends its own configuration
+    let mut configuration: ExplorationConfiguration = ExplorationConfigurationBuilder::default()
+        .total_timeout(0)
+        .improvement_timeout(0)
+        .time_resolution(0)
+        .memory_resolution(0)
+        .build()
+        .expect("Failed to build default exploration configuration. Should never fail.");
+    while let Ok(message) = ws.read() {
+        match message {
+            Message::Text(txt) => {
+                if txt.eq_ignore_ascii_case("done") {
+                    if let Some(m) = decision_model.clone() {
+                        debug!(
+                            "Starting exploration of a {} with {}",
+                            m.category(),
+                            explorer.unique_identifier()
+                        );
+                        let iter = explorer.explore(m, &previous_solutions, configuration.clone());
+                        if let Ok(mut iter) = iter.lock() {
+                            for solution in &mut *iter {
+                                if configuration.strict
+                                    && previous_solutions.iter().any(|other| {
+                                        other.partial_cmp(&solution)
+                                            == Some(std::cmp::Ordering::Less)
+                                    })
+                                {
+                                    continue;
+                                }
+                                previous_solutions.insert(solution.clone());
+                                if let Ok(s) =
+                                    ExplorationSolutionMessage::from(&solution).to_json_str()
+                                {
+                                    if ws.send(Message::text(s)).is_err() {
+                                        debug!("Exploration client disconnected. Stopping exploration.");
+                                        return;
+                                    }
+                                }
+                            }
+                        };
+                    }
+                    if ws.send(Message::text("done")).is_err() {
+                        return;
+                    }
+                } else if let Ok(sol) = ExplorationSolutionMessage::from_json_str(&txt) {
+                    previous_solutions.insert(ExplorationSolution {
+                        solved: Arc::new(OpaqueDecisionModel::from(&sol)),
+                        objectives: sol.objectives,
+                    });
+                } else if let Ok(conf) = serde_json::from_str::<ExplorationConfiguration>(&txt) {
+                    configuration = conf;
+                } else if let Ok(m) = OpaqueDecisionModel::from_json_str(&txt) {
+                    decision_model = Some(Arc::new(m));
+                } else {
+                    debug!("Ignoring unknown exploration message");
+                }
+            }
+            Message::Binary(payload) => {
+                if let Ok(sol) = ExplorationSolutionMessage::from_cbor(payload.as_slice()) {
+                    previous_solutions.insert(ExplorationSolution {
+                        solved: Arc::new(OpaqueDecisionModel::from(&sol)),
+                        objectives: sol.objectives,
+                    });
+                } else if let Ok(conf) =
+                    ciborium::from_reader::<ExplorationConfiguration, _>(payload.as_slice())
+                {
+                    configuration = conf;
+                } else if let Ok(m) = OpaqueDecisionModel::from_cbor(payload.as_slice()) {
+                    decision_model = Some(Arc::new(m));
+                }
+            }
+            Message::Close(_) => break,
+            _ => (),
+        }
+    }
+}
@@ -14,6 +14,7 @@ use jni::{
     objects::{GlobalRef, JObject, JObjectArray, JPrimitiveArray, JString, JValue},
     InitArgsBuilder, JNIEnv, JNIVersion, JavaVM,
 };
+use tracing::{debug, error};
 use zip::ZipArchive;
 
 trait FromJava<'a, T>: Sized
@@ -343,8 +344,15 @@ impl<'a> IntoJava<'a, JObject<'a>> for ExplorationConfiguration {
         // let improvement_iterations: JObject = self.improvement_iterations.into_java(env)?;
         // let strict: JObject = self.strict.into_java(env)?;
         let target_objectives = self.target_objectives.into_java(env)?;
+        let seed: Option<JObject> = match self.seed {
+            Some(s) => Some(s.into_java(env)?),
+            None => None,
+        };
         env.with_local_frame_returning_local(128, |inner| {
             let conf = inner.new_object(cls, "()V", &[])?;
+            if let Some(s) = &seed {
+                inner.set_field(&conf, "seed", "Ljava/lang/Long;", JValue::Object(s))?;
+            }
             inner.set_field(
                 &conf,
                 "totalExplorationTimeOutInSecs",
@@ -719,49 +727,26 @@ impl IdentificationRuleLike for JavaModuleIdentificationRule {
         design_models: &[Arc<dyn idesyde_core::DesignModel>],
         decision_models: &[Arc<dyn idesyde_core::DecisionModel>],
     ) -> idesyde_core::IdentificationResult {
-        let mut identified: Vec<Arc<dyn DecisionModel>> = vec![];
-        let mut messages: Vec<String> = vec![];
-        if let Ok(mut env_root) = self.java_vm.attach_current_thread_permanently() {
-            let jresult = env_root.with_local_frame(128, |env| {
-                let jdesigns = design_models.into_java(env)?;
-                let jdecisions = decision_models.into_java(env)?;
-                match env.call_method(
-                    &self.irule_jobject,
-                    "fromArrays",
-                    "([Lidesyde/core/DesignModel;[Lidesyde/core/DecisionModel;)Lidesyde/core/IdentificationResult;",
-                    &[
-                        JValue::Object(jdesigns.as_ref()),
-                        JValue::Object(jdecisions.as_ref()),
-                    ],
-                ) {
-                    Ok(irecord) => {
-                        return irecord
-                            .l()
-                            .and_then(|result| IdentificationResult::from_java(env, result))
-                    }
-                    Err(e) => {
-                        messages.push(format!("[<ERROR>] {}", e));
-                        if cfg!(debug_assertions) {
-                            let _ = env.exception_describe();
-                        }
-                    }
-                }
-                Err(jni::errors::Error::JavaException)
-            });
-            match jresult {
-                Ok((ms, msgs)) => {
-                    identified.extend(ms.into_iter());
-                    messages.extend(msgs.into_iter());
-                }
-                Err(e) => {
-                    messages.push(format!("[<ERROR>] {}", e));
-                    if cfg!(debug_assertions) {
-                        let _ = env_root.exception_describe();
-                    }
-                }
-            }
-        }
-        (identified, messages)
+        let mut env_root = attach_or_fail(&self.java_vm);
+        let jresult = env_root.with_local_frame(128, |env| {
+            let jdesigns = design_models.into_java(env)?;
+            let jdecisions = decision_models.into_java(env)?;
+            let irecord = env.call_method(
+                &self.irule_jobject,
+                "fromArrays",
+                "([Lidesyde/core/DesignModel;[Lidesyde/core/DecisionModel;)Lidesyde/core/IdentificationResult;",
+                &[
+                    JValue::Object(jdesigns.as_ref()),
+                    JValue::Object(jdecisions.as_ref()),
+                ],
+            )?;
+            irecord
+                .l()
+                .and_then(|result| IdentificationResult::from_java(env, result))
+        });
+        jresult.unwrap_or_else(|e| {
+            raise_java_failure(&mut env_root, "Java identification rule failed", e)
+        })
     }
 
     fn uses_design_models(&self) -> bool {
@@ -801,7 +786,8 @@ impl ReverseIdentificationRuleLike for JavaModuleReverseIdentificationRule {
     ) -> idesyde_core::ReverseIdentificationResult {
         let mut reversed: Vec<OpaqueDesignModel> = vec![];
         let messages: Vec<String> = vec![];
-        if let Ok(mut env_root) = self.java_vm.attach_current_thread_permanently() {
+        {
+            let mut env_root = attach_or_fail(&self.java_vm);
             let jresult =
                 env_root.with_local_frame(128, |env| {
                     let jdesigns = design_models.into_java(env)?;
@@ -821,7 +807,9 @@ impl ReverseIdentificationRuleLike for JavaModuleReverseIdentificationRule {
                 Ok(reversed_set) => {
                     reversed.extend(reversed_set.into_iter());
                 }
-                Err(e) => println!("[<ERROR>] {}", e),
+                Err(e) => {
+                    raise_java_failure(&mut env_root, "Java reverse identification rule failed", e)
+                }
             }
         }
         (
@@ -878,44 +866,32 @@ impl Iterator for JavaModuleExplorerationIter {
     type Item = idesyde_core::ExplorationSolution;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Ok(mut env) = self.java_vm.attach_current_thread_permanently() {
-            let has_next = env
-                .call_method(&self.iter, "hasNext", "()Z", &[])
-                .and_then(|x| x.z());
-            if has_next.map(|x| x == true).unwrap_or(false) {
-                let next_java_opt = env
-                    .call_method(&self.iter, "next", "()Ljava/lang/Object;", &[])
-                    .and_then(|x| x.l());
-                if let Ok(next_java) = next_java_opt {
-                    return ExplorationSolution::from_java(&mut env, next_java).ok();
+        let mut env = attach_or_fail(&self.java_vm);
+        let next_solution = env
+            .call_method(&self.iter, "hasNext", "()Z", &[])
+            .and_then(|x| x.z())
+            .and_then(|has_next| {
+                if has_next {
+                    let next_java = env
+                        .call_method(&self.iter, "next", "()Ljava/lang/Object;", &[])?
+                        .l()?;
+                    ExplorationSolution::from_java(&mut env, next_java).map(Some)
+                } else {
+                    Ok(None)
                 }
-            }
-        }
-        None
+            });
+        next_solution.unwrap_or_else(|e| raise_java_failure(&mut env, "Java exploration failed", e))
     }
 }
 
 impl Explorer for JavaModuleExplorer {
     fn unique_identifier(&self) -> String {
-        self.java_vm
-            .attach_current_thread_permanently()
-            .and_then(|mut env| {
-                env.call_method(
-                    &self.explorer_jobject,
-                    "uniqueIdentifier",
-                    "()Ljava/lang/String;",
-                    &[],
-                )
-                .and_then(|x| x.l())
-                .and_then(|x| {
-                    env.get_string(&JString::from(x)).map(|s| {
-                        s.to_str()
-                            .expect("[<ERROR>] Failed converting name to UTF8")
-                            .to_string()
-                    })
-                })
-            })
-            .expect("[<ERROR>] Could not load java module explorer's unique identifier.")
+        java_unique_identifier(&self.java_vm, &self.explorer_jobject).unwrap_or_else(|e| {
+            panic!(
+                "Could not load the unique identifier of a java explorer: {}",
+                e
+            )
+        })
     }
 
     fn bid(&self, m: Arc<dyn DecisionModel>) -> idesyde_core::ExplorationBid {
@@ -932,18 +908,15 @@ impl Explorer for JavaModuleExplorer {
                         )
                         .and_then(|x| x.l()),
                     Err(e) => {
-                        println!(
-                            "[<ERROR>] failed to convert {} to opaque with: {}",
-                            m.category(),
-                            e
-                        );
+                        error!("Failed to convert {} to opaque with: {}", m.category(), e);
                         Err(e)
                     }
                 });
-            if let Ok(java_bid) = java_bid_opt {
-                return ExplorationBid::from_java(&mut root_env, java_bid)
-                    .unwrap_or(ExplorationBid::impossible());
-            }
+            return java_bid_opt
+                .and_then(|java_bid| ExplorationBid::from_java(&mut root_env, java_bid))
+                .unwrap_or_else(|e| {
+                    raise_java_failure(&mut root_env, "Java explorer failed to bid", e)
+                });
         }
         idesyde_core::ExplorationBid::impossible()
     }
@@ -969,10 +942,13 @@ impl Explorer for JavaModuleExplorer {
             })?;
             top_env.new_global_ref(iter)
         });
-        if let Ok(iter) = exploration_iter {
-            Arc::new(Mutex::new(JavaModuleExplorerationIter { java_vm, iter }))
-        } else {
-            Arc::new(Mutex::new(std::iter::empty()))
+        match exploration_iter {
+            Ok(iter) => Arc::new(Mutex::new(JavaModuleExplorerationIter { java_vm, iter })),
+            Err(e) => raise_java_failure(
+                &mut attach_or_fail(&self.java_vm),
+                &format!("Failed to start the Java exploration of {}", m.category()),
+                e,
+            ),
         }
     }
 }
@@ -984,6 +960,64 @@ pub struct JavaModule {
     pub module_classes_canonical_name: String,
 }
 
+/// Attaches the current thread to the JVM, panicking if it cannot, like any other failure of a Java module.
+fn attach_or_fail(java_vm: &JavaVM) -> JNIEnv<'_> {
+    java_vm
+        .attach_current_thread_permanently()
+        .unwrap_or_else(|e| panic!("Failed to attach to the JVM: {}", e))
+}
+
+/// Clears the pending Java exception, if any, and panics with `e`, so that the failure reaches the
+/// module guard of the orchestrator, which quarantines the module, instead of becoming an empty result.
+fn raise_java_failure(env: &mut JNIEnv, context: &str, e: jni::errors::Error) -> ! {
+    if env.exception_check().unwrap_or(false) {
+        if cfg!(debug_assertions) {
+            let _ = env.exception_describe();
+        }
+        let _ = env.exception_clear();
+    }
+    panic!("{}: {}", context, e)
+}
+
+/// Calls `uniqueIdentifier` on a module or explorer object.
+fn java_unique_identifier(java_vm: &JavaVM, obj: &GlobalRef) -> Result<String, jni::errors::Error> {
+    let mut env = java_vm.attach_current_thread_permanently()?;
+    let name = env
+        .call_method(obj, "uniqueIdentifier", "()Ljava/lang/String;", &[])?
+        .l()?;
+    let name = JString::from(name);
+    let name = env.get_string(&name)?;
+    Ok(String::from(name))
+}
+
+/// Collects the elements of a `java.util.Set` as global references, so that they outlive the local frame.
+fn java_set_elements(
+    env: &mut JNIEnv,
+    set: &JObject,
+) -> Result<Vec<GlobalRef>, jni::errors::Error> {
+    let iter = env
+        .call_method(set, "iterator", "()Ljava/util/Iterator;", &[])?
+        .l()?;
+    let mut elements = vec![];
+    while env.call_method(&iter, "hasNext", "()Z", &[])?.z()? {
+        let element = env
+            .call_method(&iter, "next", "()Ljava/lang/Object;", &[])?
+            .l()?;
+        elements.push(env.new_global_ref(element)?);
+    }
+    Ok(elements)
+}
+
+/// Whether the JAR at `path` declares modules that can be loaded through JNI,
+/// i.e. whether it contains the `META-INF/idesyde/automodules` marker.
+pub fn is_jni_module_jar(path: &std::path::Path) -> bool {
+    std::fs::File::open(path)
+        .ok()
+        .and_then(|f| ZipArchive::new(f).ok())
+        .map(|mut jarfile| jarfile.by_name("META-INF/idesyde/automodules").is_ok())
+        .unwrap_or(false)
+}
+
 pub fn java_modules_from_jar_paths(
     paths: &[std::path::PathBuf],
     jvm_max_heap_in_mb: usize,
@@ -1045,25 +1079,12 @@ pub fn java_modules_from_jar_paths(
 
 impl Module for JavaModule {
     fn unique_identifier(&self) -> String {
-        self.java_vm
-            .attach_current_thread_permanently()
-            .and_then(|mut env| {
-                env.call_method(
-                    &self.module_jobject,
-                    "uniqueIdentifier",
-                    "()Ljava/lang/String;",
-                    &[],
-                )
-                .and_then(|x| x.l())
-                .and_then(|x| {
-                    env.get_string(&JString::from(x)).map(|s| {
-                        s.to_str()
-                            .expect("[<ERROR>] Failed converting name to UTF8")
-                            .to_string()
-                    })
-                })
-            })
-            .expect("[<ERROR>] Could not load java module explorer's unique identifier.")
+        java_unique_identifier(&self.java_vm, &self.module_jobject).unwrap_or_else(|e| {
+            panic!(
+                "Could not load the unique identifier of {}: {}",
+                self.module_classes_canonical_name, e
+            )
+        })
     }
 
     fn identification_rules(&self) -> Vec<Arc<dyn IdentificationRuleLike>> {
@@ -1078,34 +1099,31 @@ impl Module for JavaModule {
                 )
                 .and_then(|x| x.l())
             {
-                Ok(irules_objs) => {
-                    let iter = env
-                        .call_method(irules_objs, "iterator", "()Ljava/util/Iterator;", &[])
-                        .and_then(|x| x.l())
-                        .expect("Set to iterator should never fail");
-                    while env
-                        .call_method(&iter, "hasNext", "()Z", &[])
-                        .and_then(|x| x.z())
-                        .expect("Failed to get boolean from hasNext")
-                        == true
-                    {
-                        let irule_obj = env
-                            .call_method(&iter, "next", "()Ljava/lang/Object;", &[])
-                            .expect("Failed to call next")
-                            .l()
-                            .expect("Failed to get object from next");
-                        let irule = JavaModuleIdentificationRule {
-                            java_vm: self.java_vm.clone(),
-                            irule_jobject: env.new_global_ref(irule_obj).expect(
-                                "Failed to make an irule a global variable. Should not happen.",
-                            ),
-                        };
-                        irules.push(Arc::new(irule));
+                Ok(irules_objs) => match java_set_elements(&mut env, &irules_objs) {
+                    Ok(irule_objs) => {
+                        for irule_jobject in irule_objs {
+                            irules.push(Arc::new(JavaModuleIdentificationRule {
+                                java_vm: self.java_vm.clone(),
+                                irule_jobject,
+                            }));
+                        }
                     }
-                }
-                Err(e) => println!("Error: {}", e),
+                    Err(e) => error!(
+                        "Failed to iterate the identification rules of {}: {}",
+                        self.module_classes_canonical_name, e
+                    ),
+                },
+                Err(e) => error!(
+                    "Failed to get the identification rules of {}: {}",
+                    self.module_classes_canonical_name, e
+                ),
             }
         }
+        debug!(
+            "Loaded {} identification rules from {}",
+            irules.len(),
+            self.module_classes_canonical_name
+        );
         irules
     }
 
@@ -1116,32 +1134,24 @@ impl Module for JavaModule {
                 .call_method(&self.module_jobject, "explorers", "()Ljava/util/Set;", &[])
                 .and_then(|x| x.l())
             {
-                Ok(explorers_objs) => {
-                    let iter = env
-                        .call_method(explorers_objs, "iterator", "()Ljava/util/Iterator;", &[])
-                        .and_then(|x| x.l())
-                        .expect("Set to iterator should never fail");
-                    while env
-                        .call_method(&iter, "hasNext", "()Z", &[])
-                        .and_then(|x| x.z())
-                        .expect("Failed to get boolean from hasNext")
-                        == true
-                    {
-                        let explorer_obj = env
-                            .call_method(&iter, "next", "()Ljava/lang/Object;", &[])
-                            .expect("Failed to call next")
-                            .l()
-                            .expect("Failed to get object from next");
-                        let explorer = JavaModuleExplorer {
-                            java_vm: self.java_vm.clone(),
-                            explorer_jobject: env.new_global_ref(explorer_obj).expect(
-                                "Failed to make an irule a global variable. Should not happen.",
-                            ),
-                        };
-                        explorers.push(Arc::new(explorer));
+                Ok(explorers_objs) => match java_set_elements(&mut env, &explorers_objs) {
+                    Ok(explorer_objs) => {
+                        for explorer_jobject in explorer_objs {
+                            explorers.push(Arc::new(JavaModuleExplorer {
+                                java_vm: self.java_vm.clone(),
+                                explorer_jobject,
+                            }));
+                        }
                     }
-                }
-                Err(e) => println!("Error: {}", e),
+                    Err(e) => error!(
+                        "Failed to iterate the explorers of {}: {}",
+                        self.module_classes_canonical_name, e
+                    ),
+                },
+                Err(e) => error!(
+                    "Failed to get the explorers of {}: {}",
+                    self.module_classes_canonical_name, e
+                ),
             }
         }
         explorers
@@ -1159,28 +1169,19 @@ impl Module for JavaModule {
                 )
                 .and_then(|x| x.l())
             {
-                let iter = env
-                    .call_method(&irules_set_obj, "iterator", "()Ljava/util/Iterator;", &[])
-                    .and_then(|x| x.l())
-                    .expect("Set to iterator should never fail");
-                while env
-                    .call_method(&iter, "hasNext", "()Z", &[])
-                    .and_then(|x| x.z())
-                    .expect("Failed to get boolean from hasNext")
-                    == true
-                {
-                    let rrule_obj = env
-                        .call_method(&iter, "next", "()Ljava/lang/Object;", &[])
-                        .expect("Failed to call next")
-                        .l()
-                        .expect("Failed to get object from next");
-                    let rrule = JavaModuleReverseIdentificationRule {
-                        java_vm: self.java_vm.clone(),
-                        rrule_jobject: Arc::new(env.new_global_ref(rrule_obj).expect(
-                            "Failed to make an irule a global variable. Should not happen.",
-                        )),
-                    };
-                    rrules.push(Arc::new(rrule));
+                match java_set_elements(&mut env, &irules_set_obj) {
+                    Ok(rrule_objs) => {
+                        for rrule_jobject in rrule_objs {
+                            rrules.push(Arc::new(JavaModuleReverseIdentificationRule {
+                                java_vm: self.java_vm.clone(),
+                                rrule_jobject: Arc::new(rrule_jobject),
+                            }));
+                        }
+                    }
+                    Err(e) => error!(
+                        "Failed to iterate the reverse identification rules of {}: {}",
+                        self.module_classes_canonical_name, e
+                    ),
                 }
             }
         }
@@ -11,6 +11,7 @@ use std::{
 use idesyde_common::models::AperiodicAsynchronousDataflowToPartitionedMemoryMappableMulticoreAndPL;
 use idesyde_core::{ExplorationBid, ExplorationSolution, Explorer, RustEmbeddedModule};
 use serde::Deserialize;
+use tracing::{debug, warn};
 
 #[derive(Debug, Clone, PartialEq)]
 #[allow(dead_code)]
@@ -80,9 +81,12 @@ impl<T: Into<MiniZincData>> From<Vec<T>> for MiniZincData {
     }
 }
 
-impl<T: Into<MiniZincData>> From<HashSet<T>> for MiniZincData {
+impl<T: Into<MiniZincData> + Ord> From<HashSet<T>> for MiniZincData {
     fn from(value: HashSet<T>) -> Self {
-        MiniZincData::MznSet(value.into_iter().map(|x| x.into()).collect())
+        // sorted so that the solver input does not depend on the hashing order
+        let mut elems: Vec<T> = value.into_iter().collect();
+        elems.sort();
+        MiniZincData::MznSet(elems.into_iter().map(|x| x.into()).collect())
     }
 }
 
@@ -133,6 +137,12 @@ fn to_string(d: &MiniZincData) -> String {
     }
 }
 
+fn sorted<'a, T: Ord + 'a>(elems: impl Iterator<Item = &'a T>) -> Vec<&'a T> {
+    let mut v: Vec<&T> = elems.collect();
+    v.sort();
+    v
+}
+
 fn to_mzn_input(d: Vec<(&str, MiniZincData)>) -> String {
     format!(
         "{{{}}}",
@@ -183,14 +193,19 @@ impl Explorer for MiniZincGecodeExplorer {
         &self,
         m: std::sync::Arc<dyn idesyde_core::DecisionModel>,
         currrent_solutions: &std::collections::HashSet<idesyde_core::ExplorationSolution>,
-        _exploration_configuration: idesyde_core::ExplorationConfiguration,
+        exploration_configuration: idesyde_core::ExplorationConfiguration,
     ) -> Arc<Mutex<dyn Iterator<Item = ExplorationSolution> + Send + Sync>> {
         if let Ok(aad2pmmmap) =
             AperiodicAsynchronousDataflowToPartitionedMemoryMappableMulticoreAndPL::try_from(
                 m.as_ref(),
             )
         {
-            return solve_aad2pmmmap(&aad2pmmmap, currrent_solutions, "gecode");
+            return solve_aad2pmmmap(
+                &aad2pmmmap,
+                currrent_solutions,
+                "gecode",
+                exploration_configuration.seed,
+            );
         }
         Arc::new(Mutex::new(std::iter::empty()))
     }
@@ -229,12 +244,15 @@ fn solve_aad2pmmmap(
     m: &AperiodicAsynchronousDataflowToPartitionedMemoryMappableMulticoreAndPL,
     current_solutions: &HashSet<ExplorationSolution>,
     explorer_name: &str,
+    seed: Option<u64>,
 ) -> Arc<Mutex<dyn Iterator<Item = ExplorationSolution> + Send + Sync>> {
     let mut input_data = vec![];
+    // all elements coming from sets and maps are sorted so that the same decision model
+    // always produces the same solver input, which is necessary for seeded explorations
     let all_processes: Vec<String> = m
         .aperiodic_asynchronous_dataflows
         .iter()
-        .flat_map(|app| app.processes.iter())
+        .flat_map(|app| sorted(app.processes.iter()))
         .map(|x| x.to_string())
         .collect();
     let all_firings_actor: Vec<String> = m
@@ -247,7 +265,7 @@ fn solve_aad2pmmmap(
         .aperiodic_asynchronous_dataflows
         .iter()
         .flat_map(|app| {
-            app.buffers.iter().map(|b| {
+            sorted(app.buffers.iter()).into_iter().map(|b| {
                 (
                     b.to_string(),
                     app.buffer_max_size_in_bits.get(b).map(|x| *x).unwrap_or(0),
@@ -255,40 +273,48 @@ fn solve_aad2pmmmap(
             })
         })
         .unzip();
-    let memories: Vec<String> = m
-        .partitioned_mem_mappable_multicore_and_pl
-        .hardware
-        .storage_elems
-        .iter()
-        .map(|x| x.to_string())
-        .collect();
-    let communications: Vec<String> = m
-        .partitioned_mem_mappable_multicore_and_pl
-        .hardware
-        .communication_elems
-        .iter()
-        .map(|x| x.to_string())
-        .collect();
-    let list_schedulers: Vec<String> = m
-        .partitioned_mem_mappable_multicore_and_pl
-        .runtimes
-        .runtimes
-        .iter()
-        .filter(|x| {
-            m.partitioned_mem_mappable_multicore_and_pl
-                .runtimes
-                .is_super_loop
-                .contains(*x)
-        })
-        .map(|x| x.to_string())
-        .collect();
-    let logic_areas: Vec<String> = m
-        .partitioned_mem_mappable_multicore_and_pl
-        .hardware
-        .pl_module_available_areas
-        .keys()
-        .map(|x| x.to_string())
-        .collect();
+    let memories: Vec<String> = sorted(
+        m.partitioned_mem_mappable_multicore_and_pl
+            .hardware
+            .storage_elems
+            .iter(),
+    )
+    .into_iter()
+    .map(|x| x.to_string())
+    .collect();
+    let communications: Vec<String> = sorted(
+        m.partitioned_mem_mappable_multicore_and_pl
+            .hardware
+            .communication_elems
+            .iter(),
+    )
+    .into_iter()
+    .map(|x| x.to_string())
+    .collect();
+    let list_schedulers: Vec<String> = sorted(
+        m.partitioned_mem_mappable_multicore_and_pl
+            .runtimes
+            .runtimes
+            .iter(),
+    )
+    .into_iter()
+    .filter(|x| {
+        m.partitioned_mem_mappable_multicore_and_pl
+            .runtimes
+            .is_super_loop
+            .contains(*x)
+    })
+    .map(|x| x.to_string())
+    .collect();
+    let logic_areas: Vec<String> = sorted(
+        m.partitioned_mem_mappable_multicore_and_pl
+            .hardware
+            .pl_module_available_areas
+            .keys(),
+    )
+    .into_iter()
+    .map(|x| x.to_string())
+    .collect();
     let all_firings_instances: Vec<u64> = m
         .aperiodic_asynchronous_dataflows
         .iter()
@@ -335,22 +361,13 @@ fn solve_aad2pmmmap(
     let execution_times: Vec<Vec<i32>> = all_processes
         .iter()
         .map(|f| {
-            m.partitioned_mem_mappable_multicore_and_pl
-                .hardware
-                .processing_elems
+            list_schedulers
                 .iter()
-                .filter(|pe| {
+                .flat_map(|s| {
                     m.partitioned_mem_mappable_multicore_and_pl
                         .runtimes
-                        .processor_affinities
-                        .get(*pe)
-                        .map(|r| {
-                            m.partitioned_mem_mappable_multicore_and_pl
-                                .runtimes
-                                .is_super_loop
-                                .contains(r)
-                        })
-                        .unwrap_or(false)
+                        .runtime_host
+                        .get(s)
                 })
                 .map(|pe| {
                     m.instrumented_computation_times
@@ -365,40 +382,33 @@ fn solve_aad2pmmmap(
                         })
                         .unwrap_or(-1)
                 })
-                .chain(
-                    m.partitioned_mem_mappable_multicore_and_pl
-                        .hardware
-                        .programmable_logic_elems
-                        .iter()
-                        .map(|pla| {
-                            if m.hardware_implementation_area
-                                .required_resources
-                                .get(f)
-                                .and_then(|x| x.get(pla))
-                                .is_some()
-                            {
-                                m.hardware_implementation_area
-                                    .latencies_numerators
-                                    .get(f)
-                                    .and_then(|inner| inner.get(pla))
-                                    .map(|x| {
-                                        ((*x as f32)
-                                            / *m.hardware_implementation_area
-                                                .latencies_denominators
-                                                .get(f)
-                                                .and_then(|x| x.get(pla))
-                                                .unwrap_or(&1)
-                                                as f32
-                                            * discrete_max
-                                            / average_max)
-                                            .ceil() as i32
-                                    })
-                                    .unwrap_or(-1)
-                            } else {
-                                -1
-                            }
-                        }),
-                )
+                .chain(logic_areas.iter().map(|pla| {
+                    if m.hardware_implementation_area
+                        .required_resources
+                        .get(f)
+                        .and_then(|x| x.get(pla))
+                        .is_some()
+                    {
+                        m.hardware_implementation_area
+                            .latencies_numerators
+                            .get(f)
+                            .and_then(|inner| inner.get(pla))
+                            .map(|x| {
+                                ((*x as f32)
+                                    / *m.hardware_implementation_area
+                                        .latencies_denominators
+                                        .get(f)
+                                        .and_then(|x| x.get(pla))
+                                        .unwrap_or(&1) as f32
+                                    * discrete_max
+                                    / average_max)
+                                    .ceil() as i32
+                            })
+                            .unwrap_or(-1)
+                    } else {
+                        -1
+                    }
+                }))
                 .collect()
         })
         .collect();
@@ -481,8 +491,8 @@ fn solve_aad2pmmmap(
         })
         .collect();
     let programmable_scale_factors = m.get_requirements_scale_factors();
-    let programmable_resources_set: Vec<String> = programmable_scale_factors
-        .keys()
+    let programmable_resources_set: Vec<String> = sorted(programmable_scale_factors.keys())
+        .into_iter()
         .map(String::to_string)
         .collect();
     let processes_area_requirements: Vec<Vec<Vec<u64>>> = all_processes
@@ -728,26 +738,23 @@ fn solve_aad2pmmmap(
         "nPareto",
         MiniZincData::from(current_solutions.len() as u64),
     ));
-    input_data.push((
-        "previousSolutions",
-        MiniZincData::from(
-            current_solutions
-                .iter()
-                .map(|s| {
-                    let mut objs = vec![];
-                    objs.push((*s.objectives.get("nUsedPEs").unwrap_or(&0.0)) as u64);
-                    for p in &all_processes {
-                        objs.push(
-                            *s.objectives
-                                .get(&format!("invThroughput({})", p))
-                                .unwrap_or(&0.0) as u64,
-                        );
-                    }
-                    objs
-                })
-                .collect::<Vec<Vec<u64>>>(),
-        ),
-    ));
+    let mut previous_solutions: Vec<Vec<u64>> = current_solutions
+        .iter()
+        .map(|s| {
+            let mut objs = vec![];
+            objs.push((*s.objectives.get("nUsedPEs").unwrap_or(&0.0)) as u64);
+            for p in &all_processes {
+                objs.push(
+                    *s.objectives
+                        .get(&format!("invThroughput({})", p))
+                        .unwrap_or(&0.0) as u64,
+                );
+            }
+            objs
+        })
+        .collect();
+    previous_solutions.sort();
+    input_data.push(("previousSolutions", MiniZincData::from(previous_solutions)));
     input_data.push(("connected", MiniZincData::from(connected)));
 
     let temp_dir = std::env::temp_dir().join("idesyde").join("minizinc");
@@ -756,7 +763,11 @@ fn solve_aad2pmmmap(
     std::fs::create_dir_all(&temp_dir).expect("Could not create the temporary directory");
     std::fs::write(&model_file, AADPMMMPL_MZN).expect("Could not write the model file");
     std::fs::write(&data_file, to_mzn_input(input_data)).expect("Could not write the data file");
-    if let Ok(proc) = std::process::Command::new("minizinc")
+    let mut command = std::process::Command::new("minizinc");
+    if let Some(s) = seed {
+        command.arg("--random-seed").arg(s.to_string());
+    }
+    let spawned = command
         .arg("-n")
         .arg("10")
         .arg("--solver")
@@ -768,15 +779,29 @@ fn solve_aad2pmmmap(
         .arg(model_file.as_path())
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::null())
-        .spawn()
-    {
-        if let Some(stdout) = proc.stdout {
+        .spawn();
+    if let Err(e) = &spawned {
+        warn!("Failed to start the MiniZinc solver {}: {}", explorer_name, e);
+    }
+    if let Ok(mut proc) = spawned {
+        debug!(
+            solver = explorer_name,
+            pid = proc.id(),
+            "Started the MiniZinc solver"
+        );
+        if let Some(stdout) = proc.stdout.take() {
+            let solver = Arc::new(Mutex::new(proc));
+            idesyde_core::register_child_process(&solver);
             let bufreader = BufReader::new(stdout);
             let input = m.clone();
             return Arc::new(Mutex::new(
                 bufreader
                     .lines()
-                    .take_while(|l| l.is_ok())
+                    // the solver handle is kept, and so registered, while its output is read
+                    .take_while(move |l| {
+                        let _solver = &solver;
+                        l.is_ok()
+                    })
                     // .inspect(|l| {
                     //     if let Ok(line) = l {
                     //         println!("{}", line);
@@ -785,6 +810,7 @@ fn solve_aad2pmmmap(
                     .flat_map(move |line_r| {
                         if let Ok(line) = line_r {
                             if line.contains("UNSATISFIABLE") {
+                                debug!("MiniZinc reported the model as unsatisfiable");
                                 return None;
                             } else if line.contains("output") {
                                 let mzn_out: MiniZincSolutionOutput<AADPMMMPLMznOutput> =
@@ -1,14 +1,20 @@
-use idesyde_core::{decision_models_schemas_gen, RustEmbeddedModule};
+use idesyde_core::{
+    decision_models_schemas_gen, DecisionModel, OpaqueDecisionModel, RustEmbeddedModule,
+};
 use models::{
     AnalysedSDFApplication, AperiodicAsynchronousDataflow,
     AperiodicAsynchronousDataflowToPartitionedMemoryMappableMulticore,
     AperiodicAsynchronousDataflowToPartitionedMemoryMappableMulticoreAndPL,
-    AperiodicAsynchronousDataflowToPartitionedTiledMulticore, InstrumentedComputationTimes,
-    InstrumentedMemoryRequirements, MemoryMappableMulticoreWithPL, MemoryMappableMultiCore,
-    PartitionedMemoryMappableMulticore, PartitionedMemoryMappableMulticoreAndPL,
-    PartitionedTiledMulticore, RuntimesAndProcessors, SDFApplication, TiledMultiCore,
+    AperiodicAsynchronousDataflowToPartitionedTiledMulticore,
+    CommunicatingAndTriggeredReactiveWorkload, HardwareImplementationArea,
+    InstrumentedComputationTimes, InstrumentedMemoryRequirements, MemoryMappableMultiCore,
+    MemoryMappableMulticoreWithPL, PartitionedMemoryMappableMulticore,
+    PartitionedMemoryMappableMulticoreAndPL, PartitionedTiledMulticore,
+    PeriodicWorkloadToPartitionedSharedMultiCore, RuntimesAndProcessors, SDFApplication,
+    TiledMultiCore,
 };
 use schemars::schema_for;
+use serde::de::DeserializeOwned;
 use std::{collections::HashSet, sync::Arc};
 
 pub mod irules;
@@ -77,3 +83,53 @@ pub fn make_module() -> RustEmbeddedModule {
         .build()
         .expect("Failed to build common standalone identification module. Should never happen.")
 }
+
+type OpaqueConversion = fn(&OpaqueDecisionModel) -> Option<Arc<dyn DecisionModel>>;
+
+fn typed_from_opaque<T>(m: &OpaqueDecisionModel) -> Option<Arc<dyn DecisionModel>>
+where
+    T: DecisionModel + DeserializeOwned,
+{
+    m.body_cbor
+        .as_ref()
+        .and_then(|b| ciborium::from_reader::<T, &[u8]>(b.as_slice()).ok())
+        .or_else(|| {
+            m.body_json
+                .as_ref()
+                .and_then(|j| serde_json::from_str::<T>(j).ok())
+        })
+        .or_else(|| {
+            m.body_msgpack
+                .as_ref()
+                .and_then(|b| rmp_serde::from_slice::<T>(b).ok())
+        })
+        // the category is checked on the typed model, as it is not always the name of the type
+        .filter(|t| t.category() == m.category)
+        .map(|t| Arc::new(t) as Arc<dyn DecisionModel>)
+}
+
+/// The typed version of `m`, if its category is one of the decision models of this module
+/// and its body can be read as such.
+pub fn decision_model_from_opaque(m: &OpaqueDecisionModel) -> Option<Arc<dyn DecisionModel>> {
+    let conversions: [OpaqueConversion; 18] = [
+        typed_from_opaque::<SDFApplication>,
+        typed_from_opaque::<AnalysedSDFApplication>,
+        typed_from_opaque::<TiledMultiCore>,
+        typed_from_opaque::<RuntimesAndProcessors>,
+        typed_from_opaque::<PartitionedTiledMulticore>,
+        typed_from_opaque::<AperiodicAsynchronousDataflow>,
+        typed_from_opaque::<InstrumentedComputationTimes>,
+        typed_from_opaque::<InstrumentedMemoryRequirements>,
+        typed_from_opaque::<HardwareImplementationArea>,
+        typed_from_opaque::<CommunicatingAndTriggeredReactiveWorkload>,
+        typed_from_opaque::<PeriodicWorkloadToPartitionedSharedMultiCore>,
+        typed_from_opaque::<AperiodicAsynchronousDataflowToPartitionedTiledMulticore>,
+        typed_from_opaque::<MemoryMappableMultiCore>,
+        typed_from_opaque::<MemoryMappableMulticoreWithPL>,
+        typed_from_opaque::<PartitionedMemoryMappableMulticore>,
+        typed_from_opaque::<PartitionedMemoryMappableMulticoreAndPL>,
+        typed_from_opaque::<AperiodicAsynchronousDataflowToPartitionedMemoryMappableMulticore>,
+        typed_from_opaque::<AperiodicAsynchronousDataflowToPartitionedMemoryMappableMulticoreAndPL>,
+    ];
+    conversions.iter().find_map(|conversion| conversion(m))
+}
@@ -0,0 +1,3 @@
+fn main() {
+    idesyde_blueprints::execute_standalone_module(idesyde_common::make_module());
+}
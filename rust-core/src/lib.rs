@@ -3,10 +3,11 @@ pub mod macros;
 use std::{
     collections::{HashMap, HashSet},
     hash::Hash,
-    path::Path,
+    path::{Path, PathBuf},
+    process::Child,
     sync::{
         mpsc::{Receiver, Sender},
-        Arc, Mutex,
+        Arc, Mutex, OnceLock, Weak,
     },
     time::{Duration, Instant},
 };
@@ -16,6 +17,7 @@ use downcast_rs::{impl_downcast, Downcast, DowncastSync};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use sha2::{Digest, Sha512};
 use std::cmp::Ordering;
+use tracing::debug;
 use url::Url;
 
 /// A simple structure to contain a result and accumulate information regarding its computation
@@ -83,6 +85,17 @@ pub trait DesignModel: Send + DowncastSync {
     }
 
     fn write_to_dir(&self, base_path: &Path, prefix_str: &str, suffix_str: &str) {
+        self.write_files_to_dir(base_path, prefix_str, suffix_str);
+    }
+
+    /// Same as [DesignModel::write_to_dir], returning the paths of the files written.
+    fn write_files_to_dir(
+        &self,
+        base_path: &Path,
+        prefix_str: &str,
+        suffix_str: &str,
+    ) -> Vec<PathBuf> {
+        let mut written = Vec::new();
         if let Some(j) = self.body_as_string() {
             let p = base_path.join(format!(
                 "body_{}_{}_{}.{}",
@@ -92,12 +105,9 @@ pub trait DesignModel: Send + DowncastSync {
                 self.format()
             ));
             std::fs::write(&p, j).expect("Failed to write body of design model.");
-            // if let Some(s) = p.to_str().map(|x| x.to_string()) {
-            //     h.model_paths.push(s);
-            // }
+            written.push(p);
         }
-        // h.write_to_dir(base_path, prefix_str, suffix_str);
-        // h
+        written
     }
 
     fn global_md5_hash(&self) -> Vec<u8> {
@@ -207,7 +217,17 @@ pub trait DecisionModel: Send + DowncastSync {
     }
 
     fn write_to_dir(&self, base_path: &Path, prefix_str: &str, suffix_str: &str) {
-        // let mut h = self.header();
+        self.write_files_to_dir(base_path, prefix_str, suffix_str);
+    }
+
+    /// Same as [DecisionModel::write_to_dir], returning the paths of the files written.
+    fn write_files_to_dir(
+        &self,
+        base_path: &Path,
+        prefix_str: &str,
+        suffix_str: &str,
+    ) -> Vec<PathBuf> {
+        let mut written = Vec::new();
         if let Some(j) = self.body_as_json() {
             let p = base_path.join(format!(
                 "body_{}_{}_{}.json",
@@ -216,7 +236,7 @@ pub trait DecisionModel: Send + DowncastSync {
                 suffix_str
             ));
             std::fs::write(&p, j).expect("Failed to write JSON body of decision model.");
-            // h.body_path = p.to_str().map(|x| x.to_string());
+            written.push(p);
         }
         if let Some(b) = self.body_as_msgpack() {
             let p = base_path.join(format!(
@@ -226,7 +246,7 @@ pub trait DecisionModel: Send + DowncastSync {
                 suffix_str
             ));
             std::fs::write(&p, b).expect("Failed to write MsgPack body of decision model.");
-            // h.body_path = p.to_str().map(|x| x.to_string());
+            written.push(p);
         }
         if let Some(b) = self.body_as_cbor() {
             let p = base_path.join(format!(
@@ -236,10 +256,9 @@ pub trait DecisionModel: Send + DowncastSync {
                 suffix_str
             ));
             std::fs::write(&p, b).expect("Failed to write CBOR body of decision model.");
-            // h.body_path = p.to_str().map(|x| x.to_string());
+            written.push(p);
         }
-        // h.write_to_dir(base_path, prefix_str, suffix_str);
-        // h
+        written
     }
 
     fn global_md5_hash(&self) -> Vec<u8> {
@@ -486,6 +505,11 @@ pub struct ExplorationConfiguration {
     pub strict: bool,
     #[builder(default = "HashSet::new()")]
     pub target_objectives: HashSet<String>,
+    /// The seed for all randomness in the explorers, so that explorations can be reproduced.
+    /// If none is given, each explorer seeds itself as it sees fit.
+    #[builder(default = "None")]
+    #[serde(default)]
+    pub seed: Option<u64>,
 }
 
 impl ExplorationConfiguration {
@@ -498,7 +522,7 @@ impl ExplorationConfiguration {
         O: From<Vec<u8>>,
     {
         let mut buf: Vec<u8> = Vec::new();
-        ciborium::into_writer(self, buf.as_mut_slice())?;
+        ciborium::into_writer(self, &mut buf)?;
         Ok(buf.into())
     }
 }
@@ -808,7 +832,7 @@ impl OpaqueDecisionModel {
         O: From<Vec<u8>>,
     {
         let mut buf: Vec<u8> = Vec::new();
-        ciborium::into_writer(self, buf.as_mut_slice())?;
+        ciborium::into_writer(self, &mut buf)?;
         Ok(buf.into())
     }
 }
@@ -921,7 +945,7 @@ impl OpaqueDesignModel {
 
     pub fn to_cbor(&self) -> Result<Vec<u8>, ciborium::ser::Error<std::io::Error>> {
         let mut buf: Vec<u8> = Vec::new();
-        ciborium::into_writer(self, buf.as_mut_slice())?;
+        ciborium::into_writer(self, &mut buf)?;
         Ok(buf)
     }
 }
@@ -995,7 +1019,13 @@ impl DesignModel for OpaqueDesignModel {
         self.elements.iter().map(|x| x.to_owned()).collect()
     }
 
-    fn write_to_dir(&self, base_path: &Path, prefix_str: &str, suffix_str: &str) {
+    fn write_files_to_dir(
+        &self,
+        base_path: &Path,
+        prefix_str: &str,
+        suffix_str: &str,
+    ) -> Vec<PathBuf> {
+        let mut written = Vec::new();
         if let Some(j) = self.body_as_string() {
             let p = base_path.join(format!(
                 "body_{}_{}_{}.{}",
@@ -1005,12 +1035,9 @@ impl DesignModel for OpaqueDesignModel {
                 self.format()
             ));
             std::fs::write(&p, j).expect("Failed to write body of design model.");
-            // if let Some(s) = p.to_str().map(|x| x.to_string()) {
-            //     h.model_paths.push(s);
-            // }
+            written.push(p);
         }
-        // h.write_to_dir(base_path, prefix_str, suffix_str);
-        // h
+        written
     }
 }
 
@@ -1672,3 +1699,68 @@ pub fn merge_identification_results(
     }
     (models, msgs)
 }
+
+/// The child processes started on behalf of the orchestration, such as solvers and module servers.
+///
+/// They are kept as weak references, so that registering a process does not keep it alive
+/// after its owner is done with it.
+fn child_processes() -> &'static Mutex<Vec<Weak<Mutex<Child>>>> {
+    static CHILD_PROCESSES: OnceLock<Mutex<Vec<Weak<Mutex<Child>>>>> = OnceLock::new();
+    CHILD_PROCESSES.get_or_init(|| Mutex::new(vec![]))
+}
+
+/// Registers a child process so that it is stopped by [terminate_child_processes].
+pub fn register_child_process(child: &Arc<Mutex<Child>>) {
+    if let Ok(mut children) = child_processes().lock() {
+        children.retain(|c| c.strong_count() > 0);
+        children.push(Arc::downgrade(child));
+        if let Ok(process) = child.try_lock() {
+            debug!(pid = process.id(), "Registered child process");
+        }
+    }
+}
+
+/// How long [terminate_child_processes] keeps retrying the child processes used by other threads.
+const CHILD_TERMINATION_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Kills every registered child process that is still running and returns how many were killed.
+///
+/// Processes that are being used by another thread at the moment are retried until
+/// [CHILD_TERMINATION_TIMEOUT], so that this function never blocks for long, e.g. when called
+/// from a signal handler. The ones still in use by then stay registered for a later call.
+pub fn terminate_child_processes() -> usize {
+    let mut killed = 0;
+    let start = Instant::now();
+    loop {
+        let busy = if let Ok(mut children) = child_processes().lock() {
+            children.retain(|c| {
+                let child = match c.upgrade() {
+                    Some(child) => child,
+                    None => return false,
+                };
+                let mut process = match child.try_lock() {
+                    Ok(process) => process,
+                    Err(std::sync::TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+                    Err(std::sync::TryLockError::WouldBlock) => return true,
+                };
+                if process.try_wait().ok().flatten().is_none() && process.kill().is_ok() {
+                    // reaping it, so that it does not linger as a zombie
+                    let _ = process.wait();
+                    debug!(pid = process.id(), "Killed child process");
+                    killed += 1;
+                }
+                false
+            });
+            children.len()
+        } else {
+            0
+        };
+        if busy == 0 || start.elapsed() >= CHILD_TERMINATION_TIMEOUT {
+            if busy > 0 {
+                debug!("{} child process(es) in use could not be killed", busy);
+            }
+            return killed;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,639 @@
+//! Benchmarking of the orchestration over the instances in `examples_and_benchmarks`.
+//!
+//! A benchmark is described by a [BenchmarkManifest]: a list of instances, each made of one or more
+//! input files or directories, a list of named configurations and a number of repetitions. Every
+//! instance is run with every configuration as many times as the repetitions, in-process by an
+//! [crate::orchestrator::Orchestrator] with the same modules, and the identification time, the
+//! outcome of bidding, the number of solutions, the best objectives and why the exploration stopped
+//! are collected for each run.
+//!
+//! A manifest in JSON looks like:
+//!
+//! ```json
+//! {
+//!     "instances": [
+//!         { "name": "a_sobel", "inputs": ["examples_and_benchmarks/CODES_ISSS_2023/a_sobel"] }
+//!     ],
+//!     "configurations": [
+//!         { "name": "first", "max_solutions": 1 },
+//!         { "name": "minute", "total_timeout": 60, "restart_policy": "improvements:3" }
+//!     ],
+//!     "repetitions": 3
+//! }
+//! ```
+//!
+//! The runs are summarised per instance and configuration, and the summaries can be stored as a baseline
+//! and later compared against, producing a table in CSV or Markdown.
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use idesyde_core::{ExplorationConfigurationBuilder, Module};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::{
+    exploration::{
+        ComposedSolution, ExplorationStrategyBuilder, ExplorationTermination, RestartPolicy,
+        SolutionValidation,
+    },
+    orchestrator::OrchestratorBuilder,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchmarkInstance {
+    pub name: String,
+    /// Input files, or directories whose files are all inputs.
+    pub inputs: Vec<String>,
+}
+
+/// The exploration parameters of a benchmark configuration, with the same meaning
+/// as the corresponding orchestrator flags. Missing fields take the orchestrator defaults.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct BenchmarkConfiguration {
+    pub name: String,
+    #[serde(default)]
+    pub total_timeout: u64,
+    #[serde(default)]
+    pub improvement_timeout: u64,
+    #[serde(default)]
+    pub max_solutions: Option<u64>,
+    #[serde(default)]
+    pub time_resolution: u64,
+    #[serde(default)]
+    pub memory_resolution: u64,
+    #[serde(default)]
+    pub target_objectives: Vec<String>,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// As in `--x-restart-policy`, e.g. `every` or `improvements:3`.
+    #[serde(default)]
+    pub restart_policy: Option<String>,
+    #[serde(default)]
+    pub max_concurrent_levels: Option<usize>,
+    /// As in `--x-solution-validation`, e.g. `drop`.
+    #[serde(default)]
+    pub solution_validation: Option<SolutionValidation>,
+}
+
+fn default_repetitions() -> u64 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchmarkManifest {
+    pub instances: Vec<BenchmarkInstance>,
+    pub configurations: Vec<BenchmarkConfiguration>,
+    #[serde(default = "default_repetitions")]
+    pub repetitions: u64,
+}
+
+impl BenchmarkManifest {
+    pub fn from_file(path: &Path) -> Result<BenchmarkManifest, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let manifest: BenchmarkManifest = serde_json::from_str(&contents)
+            .map_err(|e| format!("failed to parse {}: {}", path.display(), e))?;
+        for conf in &manifest.configurations {
+            if let Some(policy) = &conf.restart_policy {
+                RestartPolicy::from_str(policy)
+                    .map_err(|e| format!("configuration {}: {}", conf.name, e))?;
+            }
+        }
+        Ok(manifest)
+    }
+}
+
+/// The outcome of one run of an instance with a configuration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkRun {
+    pub instance: String,
+    pub configuration: String,
+    pub repetition: u64,
+    pub identification_ms: u64,
+    pub identified: usize,
+    pub bids: usize,
+    pub explorable_bids: usize,
+    pub dominant_bids: usize,
+    pub exploration_ms: u64,
+    pub time_to_first_solution_ms: Option<u64>,
+    pub solutions: u64,
+    pub dominant_solutions: usize,
+    /// The best value of each objective among the dominant solutions.
+    pub best_objectives: BTreeMap<String, f64>,
+    /// Why the exploration stopped, or none if there was nothing to explore.
+    pub termination: Option<ExplorationTermination>,
+}
+
+/// The runs of an instance with a configuration, summarised over the repetitions.
+///
+/// Times and counts are averages, the best objectives are the best over all repetitions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkSummary {
+    pub instance: String,
+    pub configuration: String,
+    pub repetitions: u64,
+    pub identification_ms: f64,
+    pub identified: f64,
+    pub explorable_bids: f64,
+    pub dominant_bids: f64,
+    pub exploration_ms: f64,
+    pub time_to_first_solution_ms: Option<f64>,
+    pub solutions: f64,
+    pub timeouts: u64,
+    pub best_objectives: BTreeMap<String, f64>,
+}
+
+impl BenchmarkSummary {
+    /// The compared metrics, in table order. The best objectives are named `best <objective>`.
+    pub fn metrics(&self) -> Vec<(String, f64)> {
+        let mut metrics = vec![
+            ("identification ms".to_string(), self.identification_ms),
+            ("identified".to_string(), self.identified),
+            ("explorable bids".to_string(), self.explorable_bids),
+            ("dominant bids".to_string(), self.dominant_bids),
+            ("exploration ms".to_string(), self.exploration_ms),
+        ];
+        if let Some(t) = self.time_to_first_solution_ms {
+            metrics.push(("first solution ms".to_string(), t));
+        }
+        metrics.push(("solutions".to_string(), self.solutions));
+        metrics.push(("timeouts".to_string(), self.timeouts as f64));
+        for (k, v) in &self.best_objectives {
+            metrics.push((format!("best {}", k), *v));
+        }
+        metrics
+    }
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0usize), |(s, c), v| (s + v, c + 1));
+    if count > 0 {
+        Some(sum / count as f64)
+    } else {
+        None
+    }
+}
+
+pub fn summarize(runs: &[BenchmarkRun]) -> Vec<BenchmarkSummary> {
+    let mut keys: Vec<(String, String)> = vec![];
+    for run in runs {
+        let key = (run.instance.to_owned(), run.configuration.to_owned());
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys.into_iter()
+        .map(|(instance, configuration)| {
+            let group: Vec<&BenchmarkRun> = runs
+                .iter()
+                .filter(|r| r.instance == instance && r.configuration == configuration)
+                .collect();
+            let mut best_objectives: BTreeMap<String, f64> = BTreeMap::new();
+            for (k, v) in group.iter().flat_map(|r| r.best_objectives.iter()) {
+                let best = best_objectives.entry(k.to_owned()).or_insert(*v);
+                *best = best.min(*v);
+            }
+            BenchmarkSummary {
+                repetitions: group.len() as u64,
+                identification_ms: mean(group.iter().map(|r| r.identification_ms as f64))
+                    .unwrap_or(0.0),
+                identified: mean(group.iter().map(|r| r.identified as f64)).unwrap_or(0.0),
+                explorable_bids: mean(group.iter().map(|r| r.explorable_bids as f64))
+                    .unwrap_or(0.0),
+                dominant_bids: mean(group.iter().map(|r| r.dominant_bids as f64)).unwrap_or(0.0),
+                exploration_ms: mean(group.iter().map(|r| r.exploration_ms as f64)).unwrap_or(0.0),
+                time_to_first_solution_ms: mean(
+                    group
+                        .iter()
+                        .flat_map(|r| r.time_to_first_solution_ms)
+                        .map(|t| t as f64),
+                ),
+                solutions: mean(group.iter().map(|r| r.solutions as f64)).unwrap_or(0.0),
+                timeouts: group
+                    .iter()
+                    .filter(|r| r.termination == Some(ExplorationTermination::TimedOut))
+                    .count() as u64,
+                best_objectives,
+                instance,
+                configuration,
+            }
+        })
+        .collect()
+}
+
+/// Expands the inputs of an instance, replacing directories by the files in them, sorted.
+pub fn instance_input_files(instance: &BenchmarkInstance) -> Vec<PathBuf> {
+    let mut files = vec![];
+    for input in &instance.inputs {
+        let path = Path::new(input);
+        if path.is_dir() {
+            if let Ok(read_dir) = path.read_dir() {
+                let mut dir_files: Vec<PathBuf> = read_dir
+                    .flatten()
+                    .map(|e| e.path())
+                    .filter(|p| p.is_file())
+                    .collect();
+                dir_files.sort();
+                files.extend(dir_files);
+            }
+        } else {
+            files.push(path.to_path_buf());
+        }
+    }
+    files
+}
+
+/// Runs one instance with one configuration through the [crate::orchestrator::Orchestrator],
+/// in its own directory inside `run_path`.
+pub fn run_once(
+    modules: &[Arc<dyn Module>],
+    instance: &BenchmarkInstance,
+    configuration: &BenchmarkConfiguration,
+    repetition: u64,
+    run_path: &Path,
+) -> BenchmarkRun {
+    let input_files = instance_input_files(instance);
+    if input_files.is_empty() {
+        warn!("Instance {} has no input", instance.name);
+    }
+    let exploration_configuration = ExplorationConfigurationBuilder::default()
+        .max_sols(configuration.max_solutions.map(|n| n as i64).unwrap_or(-1))
+        .total_timeout(configuration.total_timeout)
+        .improvement_timeout(configuration.improvement_timeout)
+        .time_resolution(configuration.time_resolution)
+        .memory_resolution(configuration.memory_resolution)
+        .target_objectives(configuration.target_objectives.iter().cloned().collect())
+        .seed(configuration.seed)
+        .build()
+        .expect("Failed to build explorer configuration. Should never fail.");
+    let mut strategy_builder = ExplorationStrategyBuilder::default();
+    if let Some(policy) = configuration
+        .restart_policy
+        .as_ref()
+        .and_then(|p| RestartPolicy::from_str(p).ok())
+    {
+        strategy_builder.restart_policy(policy);
+    }
+    if let Some(levels) = configuration.max_concurrent_levels {
+        strategy_builder.max_concurrent_levels(levels);
+    }
+    if let Some(validation) = configuration.solution_validation {
+        strategy_builder.solution_validation(validation);
+    }
+    let strategy = strategy_builder
+        .build()
+        .expect("Failed to build exploration strategy. Should never fail.");
+    let found: Arc<Mutex<Vec<ComposedSolution>>> = Arc::new(Mutex::new(vec![]));
+    let found_by_hook = found.clone();
+    let report = OrchestratorBuilder::default()
+        .modules(modules.to_vec())
+        .inputs(input_files)
+        .run_path(
+            run_path
+                .join(&instance.name)
+                .join(&configuration.name)
+                .join(repetition.to_string()),
+        )
+        .exploration_configuration(exploration_configuration)
+        .strategy(strategy)
+        .on_solution(move |sol| {
+            if let Ok(mut found) = found_by_hook.lock() {
+                found.push(sol.clone());
+            }
+        })
+        .build()
+        .expect("Failed to build the orchestrator. Should never fail.")
+        .run();
+    // the orchestrator returns every solution not dominated by the ones before it, so the last ones dominate
+    let mut dominant_sols: Vec<ComposedSolution> = vec![];
+    for sol in found.lock().map(|f| f.clone()).unwrap_or_default() {
+        dominant_sols.retain(|cur_sol| sol.partial_cmp(cur_sol) != Some(Ordering::Less));
+        dominant_sols.push(sol);
+    }
+    let mut best_objectives: BTreeMap<String, f64> = BTreeMap::new();
+    for (k, v) in dominant_sols.iter().flat_map(|s| s.objectives.iter()) {
+        let best = best_objectives.entry(k.to_owned()).or_insert(*v);
+        *best = best.min(*v);
+    }
+    BenchmarkRun {
+        instance: instance.name.to_owned(),
+        configuration: configuration.name.to_owned(),
+        repetition,
+        identification_ms: report.identification_ms,
+        identified: report.identified,
+        bids: report.biddings,
+        explorable_bids: report.explorable_biddings,
+        dominant_bids: report.dominant_biddings,
+        exploration_ms: report.exploration_ms,
+        time_to_first_solution_ms: report.time_to_first_solution_ms,
+        solutions: report.solutions,
+        dominant_solutions: report.dominant_solutions,
+        best_objectives,
+        termination: report.termination,
+    }
+}
+
+/// Runs every instance with every configuration, as many times as the manifest repetitions.
+///
+/// Each run has its own directory in `run_path`, named after the instance, the configuration and the repetition.
+pub fn run_benchmark(
+    modules: &[Arc<dyn Module>],
+    manifest: &BenchmarkManifest,
+    run_path: &Path,
+) -> Vec<BenchmarkRun> {
+    let mut runs = vec![];
+    for instance in &manifest.instances {
+        for configuration in &manifest.configurations {
+            for repetition in 0..manifest.repetitions.max(1) {
+                info!(
+                    "Running {} with {} ({}/{})",
+                    instance.name,
+                    configuration.name,
+                    repetition + 1,
+                    manifest.repetitions.max(1)
+                );
+                let run = run_once(modules, instance, configuration, repetition, run_path);
+                debug!(
+                    "{} with {} found {} solution(s) in {} ms",
+                    instance.name, configuration.name, run.solutions, run.exploration_ms
+                );
+                runs.push(run);
+            }
+        }
+    }
+    runs
+}
+
+/// One line of the comparison table. A metric missing on one side has no value there.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComparisonRow {
+    pub instance: String,
+    pub configuration: String,
+    pub metric: String,
+    pub baseline: Option<f64>,
+    pub current: Option<f64>,
+}
+
+impl ComparisonRow {
+    /// The relative change from the baseline, in percent.
+    pub fn change(&self) -> Option<f64> {
+        match (self.baseline, self.current) {
+            (Some(b), Some(c)) if b != 0.0 => Some((c - b) / b.abs() * 100.0),
+            (Some(b), Some(c)) if b == c => Some(0.0),
+            _ => None,
+        }
+    }
+}
+
+/// Compares the `current` summaries with the `baseline` summaries, matching them by instance and configuration.
+pub fn compare(current: &[BenchmarkSummary], baseline: &[BenchmarkSummary]) -> Vec<ComparisonRow> {
+    let mut rows = vec![];
+    for summary in current {
+        let base = baseline
+            .iter()
+            .find(|b| b.instance == summary.instance && b.configuration == summary.configuration);
+        let base_metrics = base.map(|b| b.metrics()).unwrap_or_default();
+        let metrics = summary.metrics();
+        let mut names: Vec<&String> = metrics.iter().map(|(k, _)| k).collect();
+        for (k, _) in &base_metrics {
+            if !names.contains(&k) {
+                names.push(k);
+            }
+        }
+        for name in names {
+            rows.push(ComparisonRow {
+                instance: summary.instance.to_owned(),
+                configuration: summary.configuration.to_owned(),
+                metric: name.to_owned(),
+                baseline: base_metrics
+                    .iter()
+                    .find(|(k, _)| k == name)
+                    .map(|(_, v)| *v),
+                current: metrics.iter().find(|(k, _)| k == name).map(|(_, v)| *v),
+            });
+        }
+    }
+    rows
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableFormat {
+    Csv,
+    #[default]
+    Markdown,
+}
+
+impl FromStr for TableFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(TableFormat::Csv),
+            "md" | "markdown" => Ok(TableFormat::Markdown),
+            _ => Err(format!(
+                "unknown table format '{}', expected csv or markdown",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for TableFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableFormat::Csv => write!(f, "csv"),
+            TableFormat::Markdown => write!(f, "markdown"),
+        }
+    }
+}
+
+fn format_value(value: Option<f64>) -> String {
+    match value {
+        Some(v) if v.fract() == 0.0 => format!("{}", v),
+        Some(v) => format!("{:.2}", v),
+        None => String::new(),
+    }
+}
+
+pub fn comparison_table(rows: &[ComparisonRow], format: TableFormat) -> String {
+    let cells: Vec<[String; 6]> = rows
+        .iter()
+        .map(|r| {
+            [
+                r.instance.to_owned(),
+                r.configuration.to_owned(),
+                r.metric.to_owned(),
+                format_value(r.baseline),
+                format_value(r.current),
+                r.change()
+                    .map(|c| format!("{:+.1}%", c))
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+    let header = [
+        "instance",
+        "configuration",
+        "metric",
+        "baseline",
+        "current",
+        "change",
+    ];
+    let mut out = String::new();
+    match format {
+        TableFormat::Csv => {
+            out.push_str(&header.join(","));
+            out.push('\n');
+            for row in cells {
+                let escaped: Vec<String> = row
+                    .iter()
+                    .map(|c| {
+                        if c.contains([',', '"', '\n']) {
+                            format!("\"{}\"", c.replace('"', "\"\""))
+                        } else {
+                            c.to_owned()
+                        }
+                    })
+                    .collect();
+                out.push_str(&escaped.join(","));
+                out.push('\n');
+            }
+        }
+        TableFormat::Markdown => {
+            out.push_str(&format!("| {} |\n", header.join(" | ")));
+            out.push_str("|---|---|---|---:|---:|---:|\n");
+            for row in cells {
+                out.push_str(&format!("| {} |\n", row.join(" | ").replace('\n', " ")));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use idesyde_core::{
+        DecisionModel, DesignModel, ExplorationBid, ExplorationBidBuilder,
+        ExplorationConfiguration, ExplorationSolution, Explorer, MarkedIdentificationRule,
+        OpaqueDecisionModel, RustEmbeddedModule,
+    };
+
+    use super::*;
+
+    /// Solves every decision model once, exactly.
+    struct SolveExactly;
+
+    impl Explorer for SolveExactly {
+        fn unique_identifier(&self) -> String {
+            "SolveExactly".to_string()
+        }
+
+        fn bid(&self, _m: Arc<dyn DecisionModel>) -> ExplorationBid {
+            ExplorationBidBuilder::default()
+                .can_explore(true)
+                .is_exact(true)
+                .target_objectives(HashSet::from(["cost".to_string()]))
+                .build()
+                .expect("Failed to build the test bid. Should never fail.")
+        }
+
+        fn explore(
+            &self,
+            m: Arc<dyn DecisionModel>,
+            _currrent_solutions: &HashSet<ExplorationSolution>,
+            _exploration_configuration: ExplorationConfiguration,
+        ) -> Arc<Mutex<dyn Iterator<Item = ExplorationSolution> + Send + Sync>> {
+            let solution = ExplorationSolution {
+                solved: m,
+                objectives: HashMap::from([("cost".to_string(), 2.0)]),
+            };
+            Arc::new(Mutex::new(std::iter::once(solution)))
+        }
+    }
+
+    fn solving_module() -> Arc<dyn Module> {
+        Arc::new(
+            RustEmbeddedModule::builder()
+                .unique_identifier("SolvingModule".to_string())
+                .identification_rules(vec![Arc::new(
+                    MarkedIdentificationRule::DesignModelOnlyIdentificationRule(
+                        |design_models: &[Arc<dyn DesignModel>], _: &[Arc<dyn DecisionModel>]| {
+                            let identified = design_models
+                                .iter()
+                                .take(1)
+                                .map(|_| {
+                                    Arc::new(OpaqueDecisionModel {
+                                        category: "Solvable".to_string(),
+                                        part: HashSet::from(["a".to_string()]),
+                                        body_json: Some("{}".to_string()),
+                                        body_msgpack: None,
+                                        body_cbor: None,
+                                    }) as Arc<dyn DecisionModel>
+                                })
+                                .collect();
+                            (identified, vec![])
+                        },
+                    ),
+                )])
+                .explorers(vec![Arc::new(SolveExactly) as Arc<dyn Explorer>])
+                .build()
+                .expect("Failed to build the test module. Should never fail."),
+        )
+    }
+
+    #[test]
+    fn run_once_reports_the_orchestrator_run() {
+        let run_path =
+            std::env::temp_dir().join(format!("idesyde-benchmark-run-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&run_path);
+        std::fs::create_dir_all(&run_path).expect("Failed to create the test directory.");
+        let input = run_path.join("design.json");
+        std::fs::write(&input, "{}").expect("Failed to write the test input.");
+        let instance = BenchmarkInstance {
+            name: "instance".to_string(),
+            inputs: vec![input.display().to_string()],
+        };
+        let configuration = BenchmarkConfiguration {
+            name: "configuration".to_string(),
+            ..BenchmarkConfiguration::default()
+        };
+        let run = run_once(
+            &[solving_module()],
+            &instance,
+            &configuration,
+            0,
+            &run_path.join("runs"),
+        );
+        assert_eq!(run.identified, 1);
+        assert_eq!(run.dominant_bids, 1);
+        assert_eq!(run.solutions, 1);
+        assert_eq!(run.termination, Some(ExplorationTermination::Complete));
+        assert_eq!(run.best_objectives.get("cost"), Some(&2.0));
+        assert!(run_path
+            .join("runs")
+            .join("instance")
+            .join("configuration")
+            .join("0")
+            .join(crate::report::REPORT_FILE_NAME)
+            .is_file());
+        let summaries = summarize(&[
+            run.clone(),
+            BenchmarkRun {
+                repetition: 1,
+                termination: Some(ExplorationTermination::TimedOut),
+                ..run
+            },
+        ]);
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].timeouts, 1);
+        let _ = std::fs::remove_dir_all(&run_path);
+    }
+}
@@ -0,0 +1,169 @@
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use idesyde_orchestration::{
+    benchmark::{self, BenchmarkManifest, BenchmarkSummary, TableFormat},
+    deduplicate_modules, embedded_modules, find_modules_in_paths,
+    logging::{self, LogFormat},
+    module_paths, ModuleClientConfiguration,
+};
+use tracing::{error, info, warn};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "idesyde-benchmark",
+    author = "Rodolfo Jordao",
+    about = "Runs the orchestration over a benchmark manifest and compares the results against a baseline."
+)]
+struct Args {
+    #[arg(
+        help = "The benchmark manifest, in JSON, with the instances, configurations and repetitions."
+    )]
+    manifest: String,
+
+    #[arg(
+        long,
+        help = "A directory with the modules to be used, besides the embedded ones. Can be given multiple times. \nThe directories of IDESYDE_MODULES_PATH, of the user configuration file and 'modules' are also used, as in the orchestrator."
+    )]
+    modules_path: Vec<PathBuf>,
+
+    #[arg(
+        long,
+        default_value = "benchmark_run",
+        help = "The directory where the module logs, the run directories and the summaries are written."
+    )]
+    run_path: String,
+
+    #[arg(
+        long,
+        help = "A summary file of a previous benchmark to compare against."
+    )]
+    baseline: Option<String>,
+
+    #[arg(
+        long,
+        help = "Stores the summary of this benchmark as a baseline in the given file."
+    )]
+    save_baseline: Option<String>,
+
+    #[arg(
+        long,
+        default_value_t = TableFormat::Markdown,
+        help = "The format of the comparison table: csv or markdown."
+    )]
+    format: TableFormat,
+
+    #[arg(
+        long,
+        help = "Writes the comparison table to the given file instead of the standard output."
+    )]
+    output: Option<String>,
+
+    #[arg(short, long, help = "Sets the verbosity of this run.")]
+    verbosity: Option<String>,
+
+    #[arg(
+        long,
+        default_value_t = LogFormat::Text,
+        help = "The format of the log records: text or json."
+    )]
+    log_format: LogFormat,
+
+    #[arg(
+        long,
+        help = "Per-target or per-span log level, in the syntax of RUST_LOG, e.g. 'idesyde_bridge_minizinc=debug'."
+    )]
+    log_filter: Vec<String>,
+}
+
+fn main() {
+    let args = Args::parse();
+    let verbosity = args
+        .verbosity
+        .as_deref()
+        .and_then(logging::parse_level)
+        .unwrap_or(tracing::Level::INFO);
+    if let Err(e) = logging::init_logging(
+        verbosity,
+        &args.log_filter,
+        args.log_format,
+        None,
+        std::io::stderr,
+    ) {
+        eprintln!("Failed to set up logging: {}", e);
+        std::process::exit(1);
+    }
+    let manifest = match BenchmarkManifest::from_file(Path::new(&args.manifest)) {
+        Ok(m) => m,
+        Err(e) => {
+            error!("Invalid benchmark manifest: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let baseline: Vec<BenchmarkSummary> = match &args.baseline {
+        Some(path) => match std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_json::from_str(&s).map_err(|e| e.to_string()))
+        {
+            Ok(b) => b,
+            Err(e) => {
+                error!("Invalid baseline {}: {}", path, e);
+                std::process::exit(1);
+            }
+        },
+        None => vec![],
+    };
+    let run_path = Path::new(&args.run_path);
+    let logs_path = run_path.join("logs");
+    std::fs::create_dir_all(&logs_path).expect("Failed to create the benchmark run directory.");
+    let modules_paths: Vec<PathBuf> = module_paths::modules_paths(&args.modules_path)
+        .into_iter()
+        .map(|m| m.path)
+        .collect();
+    let mut modules = find_modules_in_paths(
+        &modules_paths,
+        0,
+        Some(&logs_path),
+        &ModuleClientConfiguration::default(),
+    );
+    modules.extend(embedded_modules());
+    let mut modules = deduplicate_modules(modules);
+    modules.sort_by_key(|m| m.unique_identifier());
+    info!(
+        "Benchmarking {} instance(s) with {} configuration(s) and {} repetition(s) using {} module(s)",
+        manifest.instances.len(),
+        manifest.configurations.len(),
+        manifest.repetitions,
+        modules.len()
+    );
+    let runs = benchmark::run_benchmark(&modules, &manifest, &run_path.join("runs"));
+    let summaries = benchmark::summarize(&runs);
+    for (file, json) in [
+        ("runs.json", serde_json::to_string_pretty(&runs)),
+        ("summary.json", serde_json::to_string_pretty(&summaries)),
+    ] {
+        let json = json.expect("Failed to serialize the benchmark results. Should never fail.");
+        if let Err(e) = std::fs::write(run_path.join(file), json) {
+            warn!("Failed to write {}: {}", file, e);
+        }
+    }
+    if let Some(path) = &args.save_baseline {
+        let json = serde_json::to_string_pretty(&summaries)
+            .expect("Failed to serialize the benchmark results. Should never fail.");
+        match std::fs::write(path, json) {
+            Ok(_) => info!("Baseline saved to {}", path),
+            Err(e) => warn!("Failed to save the baseline to {}: {}", path, e),
+        }
+    }
+    let table =
+        benchmark::comparison_table(&benchmark::compare(&summaries, &baseline), args.format);
+    match &args.output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, table) {
+                error!("Failed to write the comparison table to {}: {}", path, e);
+                std::process::exit(1);
+            }
+        }
+        None => print!("{}", table),
+    }
+}
@@ -0,0 +1,916 @@
+//! A conformance checker for external modules.
+//!
+//! It goes through the same HTTP and websocket protocol that [crate::ExternalServerModule] and
+//! [crate::exploration::ExternalExplorer] use, endpoint by endpoint, with small fixture models
+//! built from the [idesyde_common] decision models, and reports which endpoints behave as expected.
+//! It is meant for module authors, so that protocol mistakes show up before a full orchestration run.
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    net::TcpStream,
+    path::Path,
+    process::Child,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use base64::{engine::general_purpose, Engine as _};
+use idesyde_blueprints::{
+    ExplorationSolutionMessage, IdentificationResultCompactMessage, ProtocolInfo,
+    FEATURE_BATCHED_CACHE,
+};
+use idesyde_common::models::{MemoryMappableMultiCore, RuntimesAndProcessors};
+use idesyde_core::{
+    DecisionModel, DesignModel, ExplorationBid, ExplorationConfigurationBuilder,
+    OpaqueDecisionModel, OpaqueDesignModel,
+};
+use reqwest::blocking::{multipart::Form, Client};
+use tracing::debug;
+use url::Url;
+
+use crate::external_server::spawn_local_server;
+
+/// How long a request to the checked module can take before the endpoint is considered failed.
+const CHECK_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long an exploration can go without sending a message before it is considered hung.
+const CHECK_EXPLORATION_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckOutcome {
+    Pass(String),
+    Fail(String),
+    Skipped(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointCheck {
+    pub endpoint: String,
+    pub outcome: CheckOutcome,
+}
+
+/// The result of checking all endpoints of a module, in the order they were checked.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ConformanceReport {
+    pub module: Option<String>,
+    pub checks: Vec<EndpointCheck>,
+}
+
+impl ConformanceReport {
+    fn pass(&mut self, endpoint: &str, detail: impl Into<String>) {
+        self.push(endpoint, CheckOutcome::Pass(detail.into()));
+    }
+
+    fn fail(&mut self, endpoint: &str, detail: impl Into<String>) {
+        self.push(endpoint, CheckOutcome::Fail(detail.into()));
+    }
+
+    fn skip(&mut self, endpoint: &str, detail: impl Into<String>) {
+        self.push(endpoint, CheckOutcome::Skipped(detail.into()));
+    }
+
+    fn push(&mut self, endpoint: &str, outcome: CheckOutcome) {
+        debug!("{}: {:?}", endpoint, outcome);
+        self.checks.push(EndpointCheck {
+            endpoint: endpoint.to_string(),
+            outcome,
+        });
+    }
+
+    /// Whether no endpoint failed. Skipped endpoints do not count as failures.
+    pub fn passed(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|c| !matches!(c.outcome, CheckOutcome::Fail(_)))
+    }
+}
+
+impl Display for ConformanceReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Conformance report for {}",
+            self.module.as_deref().unwrap_or("an unidentified module")
+        )?;
+        for check in &self.checks {
+            let (label, detail) = match &check.outcome {
+                CheckOutcome::Pass(d) => ("PASS", d),
+                CheckOutcome::Fail(d) => ("FAIL", d),
+                CheckOutcome::Skipped(d) => ("SKIP", d),
+            };
+            if detail.is_empty() {
+                writeln!(f, "{}  {}", label, check.endpoint)?;
+            } else {
+                writeln!(f, "{}  {}: {}", label, check.endpoint, detail)?;
+            }
+        }
+        let failed = self
+            .checks
+            .iter()
+            .filter(|c| matches!(c.outcome, CheckOutcome::Fail(_)))
+            .count();
+        write!(
+            f,
+            "{} endpoint check(s), {} failed",
+            self.checks.len(),
+            failed
+        )
+    }
+}
+
+/// The fixture decision models sent to the checked module: a small memory mappable platform
+/// and its runtimes, which the common identification rules combine into a partitioned platform.
+pub fn fixture_decision_models() -> Vec<Arc<dyn DecisionModel>> {
+    let processors: Vec<String> = vec!["core_0".to_string(), "core_1".to_string()];
+    let platform = MemoryMappableMultiCore {
+        processing_elems: processors.iter().cloned().collect(),
+        storage_elems: HashSet::from(["memory".to_string()]),
+        communication_elems: HashSet::from(["bus".to_string()]),
+        topology_srcs: vec![
+            "core_0".to_string(),
+            "bus".to_string(),
+            "core_1".to_string(),
+            "bus".to_string(),
+        ],
+        topology_dsts: vec![
+            "bus".to_string(),
+            "core_0".to_string(),
+            "bus".to_string(),
+            "core_1".to_string(),
+        ],
+        processors_frequency: processors
+            .iter()
+            .map(|p| (p.to_owned(), 50_000_000))
+            .collect(),
+        processors_provisions: processors
+            .iter()
+            .map(|p| {
+                (
+                    p.to_owned(),
+                    HashMap::from([(
+                        "default".to_string(),
+                        HashMap::from([("FloatOp".to_string(), 1.0), ("IntOp".to_string(), 1.0)]),
+                    )]),
+                )
+            })
+            .collect(),
+        storage_sizes: HashMap::from([("memory".to_string(), 1_000_000)]),
+        communication_elements_max_channels: HashMap::from([("bus".to_string(), 1)]),
+        communication_elements_bit_per_sec_per_channel: HashMap::from([(
+            "bus".to_string(),
+            8_000_000.0,
+        )]),
+        pre_computed_paths: HashMap::new(),
+    };
+    let runtimes = RuntimesAndProcessors {
+        runtimes: processors.iter().map(|p| format!("os_{}", p)).collect(),
+        processors: processors.iter().cloned().collect(),
+        runtime_host: processors
+            .iter()
+            .map(|p| (format!("os_{}", p), p.to_owned()))
+            .collect(),
+        processor_affinities: processors
+            .iter()
+            .map(|p| (p.to_owned(), format!("os_{}", p)))
+            .collect(),
+        is_bare_metal: processors.iter().map(|p| format!("os_{}", p)).collect(),
+        is_fixed_priority: HashSet::new(),
+        is_preemptive: HashSet::new(),
+        is_earliest_deadline_first: HashSet::new(),
+        is_super_loop: HashSet::new(),
+    };
+    vec![Arc::new(platform), Arc::new(runtimes)]
+}
+
+/// The fixture design model sent to the checked module. No module is expected to understand it.
+pub fn fixture_design_model() -> OpaqueDesignModel {
+    OpaqueDesignModel {
+        category: "ConformanceFixture".to_string(),
+        elements: HashSet::from(["core_0".to_string(), "core_1".to_string()]),
+        format: "json".to_string(),
+        body: Some("{}".to_string()),
+    }
+}
+
+fn encode_hash(hash: &[u8]) -> String {
+    general_purpose::STANDARD_NO_PAD.encode(hash)
+}
+
+/// Checks the module at `target`, which is either an `http` URL of a running module
+/// or the path of a module executable or JAR that is launched for the check and stopped afterwards.
+pub fn check_module(target: &str) -> ConformanceReport {
+    let mut report = ConformanceReport::default();
+    let mut launched: Option<Arc<Mutex<Child>>> = None;
+    let url = match Url::parse(target) {
+        Ok(url) if url.scheme() == "http" => Some(url),
+        Ok(url) if url.scheme() != "file" && url.scheme().len() > 1 => {
+            report.fail(
+                "launch",
+                format!("the scheme {} is not supported", url.scheme()),
+            );
+            None
+        }
+        _ => match spawn_local_server(Path::new(target), None) {
+            Some((child, port, _)) => {
+                let child = Arc::new(Mutex::new(child));
+                idesyde_core::register_child_process(&child);
+                launched = Some(child);
+                let url = Url::parse(&format!("http://127.0.0.1:{}", port))
+                    .expect("Failed to build imodule url. Should always succeed.");
+                report.pass("launch", format!("listening at {}", url));
+                Some(url)
+            }
+            None => {
+                report.fail(
+                    "launch",
+                    format!(
+                        "{} did not print an 'INITIALIZED <port>' line when started with '--server http'",
+                        target
+                    ),
+                );
+                None
+            }
+        },
+    };
+    if let Some(url) = url {
+        match Client::builder().timeout(CHECK_REQUEST_TIMEOUT).build() {
+            Ok(client) => ModuleChecker {
+                client,
+                url,
+                report: &mut report,
+            }
+            .check_all(),
+            Err(e) => report.fail("client", format!("failed to build HTTP client: {}", e)),
+        }
+    }
+    if let Some(child) = launched {
+        if let Ok(mut child) = child.lock() {
+            if let Err(e) = child.kill().and_then(|_| child.wait()) {
+                debug!("Ignoring error whilst stopping the checked module: {}", e);
+            }
+        }
+    }
+    report
+}
+
+struct ModuleChecker<'a> {
+    client: Client,
+    url: Url,
+    report: &'a mut ConformanceReport,
+}
+
+impl<'a> ModuleChecker<'a> {
+    fn check_all(&mut self) {
+        let Some(name) = self.check_unique_identifier() else {
+            return;
+        };
+        self.report.module = Some(name);
+        let protocol = self.check_protocol();
+        // design models
+        let design = fixture_design_model();
+        let design_hash = design.global_sha2_hash();
+        if let Ok(json) = design.to_json() {
+            self.check_cache_add("design", "designModel", json);
+        }
+        self.check_cache_exists("design", &design_hash, true);
+        // decision models
+        let decision_models = fixture_decision_models();
+        for m in &decision_models {
+            if let Ok(json) = OpaqueDecisionModel::from(m.as_ref()).to_json() {
+                self.check_cache_add("decision", "decisionModel", json);
+            }
+        }
+        for m in &decision_models {
+            self.check_cache_exists("decision", &m.global_sha2_hash(), true);
+        }
+        self.check_cache_exists("decision", b"not a hash of any model", false);
+        for m in &decision_models {
+            self.check_cache_fetch_decision(m.as_ref());
+        }
+        if protocol
+            .as_ref()
+            .map(|p| p.supports_feature(FEATURE_BATCHED_CACHE))
+            .unwrap_or(false)
+        {
+            self.check_batched_cache(&decision_models);
+        } else {
+            self.report.skip(
+                "POST /{decision,design,solved}/cache/{missing,add_all,fetch_all}",
+                format!("the module does not announce '{}'", FEATURE_BATCHED_CACHE),
+            );
+        }
+        let identified = self.check_identify();
+        let mut all_decision_models = decision_models.clone();
+        all_decision_models.extend(identified);
+        self.check_reverse(&decision_models);
+        let explorers = self.check_explorers();
+        for explorer in &explorers {
+            self.check_bid_and_explore(explorer, &all_decision_models);
+        }
+    }
+
+    fn endpoint_url(&self, path: &str) -> Option<Url> {
+        self.url.join(path).ok()
+    }
+
+    fn check_unique_identifier(&mut self) -> Option<String> {
+        let endpoint = "GET /info/unique_identifier";
+        match self
+            .endpoint_url("/info/unique_identifier")
+            .map(|u| self.client.get(u).send())
+        {
+            Some(Ok(r)) if r.status().is_success() => match r.text() {
+                Ok(name) if !name.trim().is_empty() => {
+                    self.report.pass(endpoint, name.trim());
+                    Some(name.trim().to_string())
+                }
+                _ => {
+                    self.report.fail(endpoint, "the identifier is empty");
+                    None
+                }
+            },
+            Some(Ok(r)) => {
+                self.report
+                    .fail(endpoint, format!("answered {}", r.status()));
+                None
+            }
+            Some(Err(e)) => {
+                self.report
+                    .fail(endpoint, format!("could not connect: {}", e));
+                None
+            }
+            None => {
+                self.report.fail(endpoint, "invalid module URL");
+                None
+            }
+        }
+    }
+
+    fn check_protocol(&mut self) -> Option<ProtocolInfo> {
+        let endpoint = "GET /info/protocol";
+        match self
+            .endpoint_url("/info/protocol")
+            .map(|u| self.client.get(u).send())
+        {
+            Some(Ok(r)) if r.status() == reqwest::StatusCode::NOT_FOUND => {
+                self.report.skip(
+                    endpoint,
+                    "not implemented, the module is assumed to speak the legacy protocol",
+                );
+                Some(ProtocolInfo::legacy())
+            }
+            Some(Ok(r)) if r.status().is_success() => {
+                match r
+                    .text()
+                    .ok()
+                    .and_then(|t| ProtocolInfo::from_json_str(&t).ok())
+                {
+                    Some(protocol) => {
+                        match ProtocolInfo::current().check_compatibility(&protocol) {
+                            Ok(_) => self.report.pass(
+                                endpoint,
+                                format!(
+                                    "version {}, encodings {:?}, features {:?}",
+                                    protocol.version, protocol.encodings, protocol.features
+                                ),
+                            ),
+                            Err(e) => self.report.fail(endpoint, e),
+                        }
+                        Some(protocol)
+                    }
+                    None => {
+                        self.report
+                            .fail(endpoint, "the answer is not a protocol information message");
+                        None
+                    }
+                }
+            }
+            Some(Ok(r)) => {
+                self.report
+                    .fail(endpoint, format!("answered {}", r.status()));
+                None
+            }
+            Some(Err(e)) => {
+                self.report.fail(endpoint, format!("request failed: {}", e));
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn check_cache_add(&mut self, kind: &str, field: &'static str, json: String) {
+        let endpoint = format!("POST /{}/cache/add", kind);
+        let form = Form::new().text(field, json);
+        match self
+            .endpoint_url(&format!("/{}/cache/add", kind))
+            .map(|u| self.client.post(u).multipart(form).send())
+        {
+            Some(Ok(r)) if r.status().is_success() => self.report.pass(&endpoint, ""),
+            Some(Ok(r)) => self
+                .report
+                .fail(&endpoint, format!("answered {}", r.status())),
+            Some(Err(e)) => self
+                .report
+                .fail(&endpoint, format!("request failed: {}", e)),
+            None => {}
+        }
+    }
+
+    fn check_cache_exists(&mut self, kind: &str, hash: &[u8], expected: bool) {
+        let endpoint = format!("GET /{}/cache/exists", kind);
+        match self
+            .endpoint_url(&format!("/{}/cache/exists", kind))
+            .map(|u| self.client.get(u).body(hash.to_vec()).send())
+        {
+            Some(Ok(r)) if r.status().is_success() => {
+                let text = r.text().unwrap_or_default();
+                let answer = text.trim();
+                if answer.eq_ignore_ascii_case(if expected { "true" } else { "false" }) {
+                    self.report
+                        .pass(&endpoint, format!("'{}' for {}", answer, encode_hash(hash)));
+                } else {
+                    self.report.fail(
+                        &endpoint,
+                        format!(
+                            "expected '{}' for {} but got '{}'",
+                            expected,
+                            encode_hash(hash),
+                            answer
+                        ),
+                    );
+                }
+            }
+            Some(Ok(r)) => self
+                .report
+                .fail(&endpoint, format!("answered {}", r.status())),
+            Some(Err(e)) => self
+                .report
+                .fail(&endpoint, format!("request failed: {}", e)),
+            None => {}
+        }
+    }
+
+    fn fetch_decision(&self, hash: &[u8]) -> Result<OpaqueDecisionModel, String> {
+        let u = self
+            .endpoint_url("/decision/cache/fetch")
+            .ok_or("invalid module URL".to_string())?;
+        let r = self
+            .client
+            .get(u)
+            .body(hash.to_vec())
+            .send()
+            .map_err(|e| format!("request failed: {}", e))?;
+        if !r.status().is_success() {
+            return Err(format!("answered {} for {}", r.status(), encode_hash(hash)));
+        }
+        let text = r.text().map_err(|e| e.to_string())?;
+        OpaqueDecisionModel::from_json_str(&text)
+            .map_err(|e| format!("the answer is not an opaque decision model: {}", e))
+    }
+
+    fn check_cache_fetch_decision(&mut self, m: &dyn DecisionModel) {
+        let endpoint = "GET /decision/cache/fetch";
+        match self.fetch_decision(&m.global_sha2_hash()) {
+            Ok(fetched) if fetched.category() == m.category() && fetched.part() == m.part() => {
+                self.report.pass(endpoint, m.category())
+            }
+            Ok(fetched) => self.report.fail(
+                endpoint,
+                format!(
+                    "asked for a {} but got a {} or different parts",
+                    m.category(),
+                    fetched.category()
+                ),
+            ),
+            Err(e) => self.report.fail(endpoint, e),
+        }
+    }
+
+    fn check_batched_cache(&mut self, decision_models: &[Arc<dyn DecisionModel>]) {
+        let hashes: Vec<String> = decision_models
+            .iter()
+            .map(|m| encode_hash(&m.global_sha2_hash()))
+            .chain(std::iter::once(encode_hash(b"not a hash of any model")))
+            .collect();
+        let body = serde_json::to_string(&hashes).unwrap_or_default();
+        let endpoint = "POST /decision/cache/missing";
+        match self
+            .endpoint_url("/decision/cache/missing")
+            .map(|u| self.client.post(u).body(body.clone()).send())
+        {
+            Some(Ok(r)) if r.status().is_success() => {
+                match r
+                    .text()
+                    .ok()
+                    .and_then(|t| serde_json::from_str::<Vec<String>>(&t).ok())
+                {
+                    Some(missing) if missing == hashes[hashes.len() - 1..] => {
+                        self.report.pass(endpoint, "")
+                    }
+                    Some(missing) => self.report.fail(
+                        endpoint,
+                        format!("expected only the unknown hash but got {:?}", missing),
+                    ),
+                    None => self
+                        .report
+                        .fail(endpoint, "the answer is not a list of hashes"),
+                }
+            }
+            Some(Ok(r)) => self
+                .report
+                .fail(endpoint, format!("answered {}", r.status())),
+            Some(Err(e)) => self.report.fail(endpoint, format!("request failed: {}", e)),
+            None => {}
+        }
+        let endpoint = "POST /solved/cache/add_all";
+        let mut form = Form::new();
+        for m in decision_models {
+            if let Ok(json) = OpaqueDecisionModel::from(m.as_ref()).to_json() {
+                form = form.text("solvedModel", json);
+            }
+        }
+        match self
+            .endpoint_url("/solved/cache/add_all")
+            .map(|u| self.client.post(u).multipart(form).send())
+        {
+            Some(Ok(r)) if r.status().is_success() => self.report.pass(endpoint, ""),
+            Some(Ok(r)) => self
+                .report
+                .fail(endpoint, format!("answered {}", r.status())),
+            Some(Err(e)) => self.report.fail(endpoint, format!("request failed: {}", e)),
+            None => {}
+        }
+        let endpoint = "POST /decision/cache/fetch_all";
+        match self
+            .endpoint_url("/decision/cache/fetch_all")
+            .map(|u| self.client.post(u).body(body).send())
+        {
+            Some(Ok(r)) if r.status().is_success() => {
+                match r
+                    .text()
+                    .ok()
+                    .and_then(|t| serde_json::from_str::<Vec<OpaqueDecisionModel>>(&t).ok())
+                {
+                    Some(fetched) if fetched.len() == decision_models.len() => self
+                        .report
+                        .pass(endpoint, format!("{} model(s)", fetched.len())),
+                    Some(fetched) => self.report.fail(
+                        endpoint,
+                        format!(
+                            "expected {} model(s) but got {}",
+                            decision_models.len(),
+                            fetched.len()
+                        ),
+                    ),
+                    None => self.report.fail(
+                        endpoint,
+                        "the answer is not a list of opaque decision models",
+                    ),
+                }
+            }
+            Some(Ok(r)) => self
+                .report
+                .fail(endpoint, format!("answered {}", r.status())),
+            Some(Err(e)) => self.report.fail(endpoint, format!("request failed: {}", e)),
+            None => {}
+        }
+    }
+
+    fn check_identify(&mut self) -> Vec<Arc<dyn DecisionModel>> {
+        let endpoint = "POST /identify";
+        let message = match self
+            .endpoint_url("/identify")
+            .map(|u| self.client.post(u).send())
+        {
+            Some(Ok(r)) if r.status().is_success() => {
+                let text = r.text().unwrap_or_default();
+                match serde_json::from_str::<IdentificationResultCompactMessage>(&text) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        self.report.fail(
+                            endpoint,
+                            format!("the answer is not an identification result: {}", e),
+                        );
+                        return vec![];
+                    }
+                }
+            }
+            Some(Ok(r)) => {
+                self.report
+                    .fail(endpoint, format!("answered {}", r.status()));
+                return vec![];
+            }
+            Some(Err(e)) => {
+                self.report.fail(endpoint, format!("request failed: {}", e));
+                return vec![];
+            }
+            None => return vec![],
+        };
+        let mut identified: Vec<Arc<dyn DecisionModel>> = Vec::new();
+        let mut unfetchable = Vec::new();
+        for hash_str in &message.identified {
+            match general_purpose::STANDARD_NO_PAD
+                .decode(hash_str)
+                .map_err(|e| e.to_string())
+                .and_then(|hash| self.fetch_decision(&hash))
+            {
+                Ok(m) => identified.push(Arc::new(m)),
+                Err(e) => unfetchable.push(format!("{} ({})", hash_str, e)),
+            }
+        }
+        if unfetchable.is_empty() {
+            let categories: Vec<String> = identified.iter().map(|m| m.category()).collect();
+            self.report.pass(
+                endpoint,
+                format!(
+                    "{} identified {:?}, {} message(s)",
+                    identified.len(),
+                    categories,
+                    message.messages.len()
+                ),
+            );
+        } else {
+            self.report.fail(
+                endpoint,
+                format!(
+                    "identified models could not be fetched from the decision cache: {}",
+                    unfetchable.join(", ")
+                ),
+            );
+        }
+        identified
+    }
+
+    fn check_reverse(&mut self, solved: &[Arc<dyn DecisionModel>]) {
+        for m in solved {
+            if let Ok(json) = OpaqueDecisionModel::from(m.as_ref()).to_json() {
+                self.check_cache_add("solved", "solvedModel", json);
+            }
+        }
+        let endpoint = "POST /reverse";
+        match self
+            .endpoint_url("/reverse")
+            .map(|u| self.client.post(u).send())
+        {
+            Some(Ok(r)) if r.status().is_success() => {
+                let text = r.text().unwrap_or_default();
+                match serde_json::from_str::<Vec<String>>(&text) {
+                    Ok(hashes) => {
+                        let mut unfetchable = Vec::new();
+                        for hash_str in &hashes {
+                            let fetched = general_purpose::STANDARD_NO_PAD
+                                .decode(hash_str)
+                                .ok()
+                                .and_then(|hash| {
+                                    self.endpoint_url("/reversed/cache/fetch")
+                                        .and_then(|u| self.client.get(u).body(hash).send().ok())
+                                })
+                                .filter(|r| r.status().is_success())
+                                .and_then(|r| r.text().ok())
+                                .and_then(|t| OpaqueDesignModel::from_json_str(&t).ok());
+                            if fetched.is_none() {
+                                unfetchable.push(hash_str.to_owned());
+                            }
+                        }
+                        if unfetchable.is_empty() {
+                            self.report
+                                .pass(endpoint, format!("{} reversed", hashes.len()));
+                        } else {
+                            self.report.fail(
+                                endpoint,
+                                format!(
+                                    "reversed models could not be fetched from the reversed cache: {}",
+                                    unfetchable.join(", ")
+                                ),
+                            );
+                        }
+                    }
+                    Err(e) => self.report.fail(
+                        endpoint,
+                        format!("the answer is not a list of hashes: {}", e),
+                    ),
+                }
+            }
+            Some(Ok(r)) => self
+                .report
+                .fail(endpoint, format!("answered {}", r.status())),
+            Some(Err(e)) => self.report.fail(endpoint, format!("request failed: {}", e)),
+            None => {}
+        }
+    }
+
+    fn check_explorers(&mut self) -> Vec<String> {
+        let endpoint = "GET /explorers";
+        match self
+            .endpoint_url("/explorers")
+            .map(|u| self.client.get(u).send())
+        {
+            Some(Ok(r)) if r.status().is_success() => {
+                let text = r.text().unwrap_or_default();
+                match serde_json::from_str::<Vec<String>>(&text) {
+                    Ok(names) => {
+                        self.report.pass(endpoint, format!("{:?}", names));
+                        names
+                    }
+                    Err(e) => {
+                        self.report.fail(
+                            endpoint,
+                            format!("the answer is not a list of explorer names: {}", e),
+                        );
+                        vec![]
+                    }
+                }
+            }
+            Some(Ok(r)) => {
+                self.report
+                    .fail(endpoint, format!("answered {}", r.status()));
+                vec![]
+            }
+            Some(Err(e)) => {
+                self.report.fail(endpoint, format!("request failed: {}", e));
+                vec![]
+            }
+            None => vec![],
+        }
+    }
+
+    fn check_bid(&mut self, explorer: &str, m: &dyn DecisionModel) -> Option<ExplorationBid> {
+        let endpoint = format!("GET /{}/bid", explorer);
+        match self
+            .endpoint_url(&format!("/{}/bid", explorer))
+            .map(|u| self.client.get(u).body(m.global_sha2_hash()).send())
+        {
+            Some(Ok(r)) if r.status().is_success() => {
+                let text = r.text().unwrap_or_default();
+                match ExplorationBid::from_json_str(&text) {
+                    Some(bid) => {
+                        self.report.pass(
+                            &endpoint,
+                            format!("{} (can explore: {})", m.category(), bid.can_explore),
+                        );
+                        Some(bid)
+                    }
+                    None => {
+                        self.report.fail(
+                            &endpoint,
+                            format!("the answer for {} is not an exploration bid", m.category()),
+                        );
+                        None
+                    }
+                }
+            }
+            Some(Ok(r)) => {
+                self.report.fail(
+                    &endpoint,
+                    format!("answered {} for {}", r.status(), m.category()),
+                );
+                None
+            }
+            Some(Err(e)) => {
+                self.report
+                    .fail(&endpoint, format!("request failed: {}", e));
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn check_bid_and_explore(
+        &mut self,
+        explorer: &str,
+        decision_models: &[Arc<dyn DecisionModel>],
+    ) {
+        let mut explorable = None;
+        for m in decision_models {
+            if let Some(bid) = self.check_bid(explorer, m.as_ref()) {
+                if bid.can_explore && explorable.is_none() {
+                    explorable = Some(m.clone());
+                }
+            }
+        }
+        // explorers must terminate with "done" even for models they cannot explore
+        let (m, note) = match explorable {
+            Some(m) => (m, ""),
+            None => match decision_models.first() {
+                Some(m) => (m.clone(), ", which it cannot explore"),
+                None => return,
+            },
+        };
+        let endpoint = format!("WS /{}/explore", explorer);
+        match self.explore(explorer, m.as_ref()) {
+            Ok(n) => self.report.pass(
+                &endpoint,
+                format!("{} solution(s) for {}{}", n, m.category(), note),
+            ),
+            Err(e) => self.report.fail(&endpoint, e),
+        }
+    }
+
+    /// Explores `m` with `explorer` and returns the number of solutions received before `done`.
+    fn explore(&self, explorer: &str, m: &dyn DecisionModel) -> Result<usize, String> {
+        let mut ws_url = self.url.clone();
+        ws_url
+            .set_scheme("ws")
+            .map_err(|_| "failed to build the websocket URL".to_string())?;
+        let explore_url = ws_url
+            .join(&format!("/{}/explore", explorer))
+            .map_err(|e| e.to_string())?;
+        let address = explore_url
+            .socket_addrs(|| None)
+            .ok()
+            .and_then(|addrs| addrs.first().cloned())
+            .ok_or("failed to resolve the module address".to_string())?;
+        let stream =
+            TcpStream::connect(address).map_err(|e| format!("failed to connect: {}", e))?;
+        stream
+            .set_read_timeout(Some(CHECK_EXPLORATION_TIMEOUT))
+            .map_err(|e| e.to_string())?;
+        let (mut ws, _) = tungstenite::client(explore_url, stream)
+            .map_err(|e| format!("websocket handshake failed: {}", e))?;
+        let configuration = ExplorationConfigurationBuilder::default()
+            .total_timeout(10)
+            .improvement_timeout(10)
+            .time_resolution(100)
+            .memory_resolution(100)
+            .max_sols(1)
+            .build()
+            .expect("Failed to build exploration configuration. Should never fail.");
+        let model_json = OpaqueDecisionModel::from(m)
+            .to_json()
+            .map_err(|e| e.to_string())?;
+        let configuration_json = configuration.to_json_string().map_err(|e| e.to_string())?;
+        for message in [model_json, configuration_json, "done".to_string()] {
+            ws.send(tungstenite::Message::text(message))
+                .map_err(|e| format!("failed to send the exploration request: {}", e))?;
+        }
+        let mut solutions = 0;
+        loop {
+            match ws.read() {
+                Ok(tungstenite::Message::Text(txt)) => {
+                    if txt.eq_ignore_ascii_case("done") {
+                        let _ = ws.close(None);
+                        return Ok(solutions);
+                    }
+                    ExplorationSolutionMessage::from_json_str(&txt)
+                        .map_err(|e| format!("received a message that is not a solution: {}", e))?;
+                    solutions += 1;
+                }
+                Ok(tungstenite::Message::Binary(bytes)) => {
+                    ExplorationSolutionMessage::from_cbor(bytes.as_slice()).map_err(|e| {
+                        format!(
+                            "received a binary message that is not a CBOR solution: {}",
+                            e
+                        )
+                    })?;
+                    solutions += 1;
+                }
+                Ok(tungstenite::Message::Close(_)) => {
+                    return Err(format!(
+                        "the connection was closed after {} solution(s) without a 'done' message",
+                        solutions
+                    ))
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    return Err(format!(
+                        "no 'done' message after {} solution(s): {}",
+                        solutions, e
+                    ))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use idesyde_blueprints::server::ModuleServer;
+    use idesyde_core::Module;
+
+    use super::*;
+
+    #[test]
+    fn common_module_served_in_process_conforms() {
+        let server = ModuleServer::bind(Arc::new(idesyde_common::make_module()), "127.0.0.1:0")
+            .expect("Failed to bind the test module server.")
+            .spawn();
+        let report = check_module(&server.url().expect("The test module server has no URL."));
+        assert!(report.passed(), "{}", report);
+        assert_eq!(
+            report.module,
+            Some(idesyde_common::make_module().unique_identifier())
+        );
+        assert!(report
+            .checks
+            .iter()
+            .any(|c| c.endpoint == "POST /identify" && matches!(c.outcome, CheckOutcome::Pass(_))));
+    }
+
+    #[test]
+    fn unsupported_scheme_fails_to_launch() {
+        let report = check_module("ftp://127.0.0.1/module");
+        assert!(!report.passed());
+        assert_eq!(report.checks.len(), 1);
+        assert_eq!(report.checks[0].endpoint, "launch");
+    }
+}
@@ -0,0 +1,183 @@
+//! Structured progress events for IDEs, CI pipelines and other external observers.
+//!
+//! The orchestrator can emit its progress as a stream of JSON lines, one event per line,
+//! to an already open file descriptor or to a local Unix socket. Each line is a JSON
+//! object with the following fields in common:
+//!
+//! - `event`: the kind of the event, as listed below.
+//! - `elapsed_ms`: milliseconds since the event stream was opened.
+//!
+//! The remaining fields depend on the `event` kind:
+//!
+//! | `event`                   | fields                                                                                   |
+//! |---------------------------|------------------------------------------------------------------------------------------|
+//! | `identification_step`     | `step`, `identified` (total so far), `changed` (added or replaced in this step)          |
+//! | `identification_finished` | `identified`, `categories`                                                               |
+//! | `bid`                     | `explorer`, `decision_model`, `can_explore`, `is_exact`, `competitiveness`, `target_objectives`, `dominant` |
+//! | `exploration_started`     | `explorers`, `decision_models`                                                           |
+//! | `new_solution`            | `index`, `decision_model`, `objectives` (a map from objective name to value)              |
+//! | `level_restart`           | `level`, `active_levels`, `pareto_size`                                                  |
+//! | `exploration_finished`    | `total_solutions`, `dominant_solutions`                                                  |
+//! | `reverse_identified`      | `module`, `design_model`, `path`                                                         |
+//! | `finished`                | no extra field                                                                           |
+//! | `watch_cycle_started`     | `cycle`, `changed` (the inputs that changed since the last cycle, empty for the first)   |
+//! | `module_quarantined`      | `module`, `stage` (where it failed), `reason`                                            |
+//! | `invalid_solution`        | `explorer`, `decision_model`, `violations` (why the solution is invalid)                 |
+//!
+//! Consumers should ignore unknown event kinds and unknown fields so that the schema can
+//! grow without breaking them.
+//!
+//! The stream is process-wide, like the logger: it is opened once with [open_event_stream]
+//! and events are sent with [emit] from anywhere in the orchestration. If no stream
+//! was opened, [emit] does nothing.
+use std::{
+    collections::HashMap,
+    io::Write,
+    path::Path,
+    sync::{Mutex, OnceLock},
+    time::Instant,
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    IdentificationStep {
+        step: i32,
+        identified: usize,
+        changed: usize,
+    },
+    IdentificationFinished {
+        identified: usize,
+        categories: Vec<String>,
+    },
+    Bid {
+        explorer: String,
+        decision_model: String,
+        can_explore: bool,
+        is_exact: bool,
+        competitiveness: f32,
+        target_objectives: Vec<String>,
+        dominant: bool,
+    },
+    ExplorationStarted {
+        explorers: Vec<String>,
+        decision_models: Vec<String>,
+    },
+    NewSolution {
+        index: u64,
+        decision_model: String,
+        objectives: HashMap<String, f64>,
+    },
+    LevelRestart {
+        level: u64,
+        active_levels: usize,
+        pareto_size: usize,
+    },
+    ExplorationFinished {
+        total_solutions: u64,
+        dominant_solutions: usize,
+    },
+    ReverseIdentified {
+        module: String,
+        design_model: String,
+        path: String,
+    },
+    Finished,
+    WatchCycleStarted {
+        cycle: u64,
+        changed: Vec<String>,
+    },
+    ModuleQuarantined {
+        module: String,
+        stage: String,
+        reason: String,
+    },
+    InvalidSolution {
+        explorer: String,
+        decision_model: String,
+        violations: Vec<String>,
+    },
+}
+
+#[derive(Serialize)]
+struct EventLine<'a> {
+    elapsed_ms: u128,
+    #[serde(flatten)]
+    event: &'a ProgressEvent,
+}
+
+struct EventStream {
+    writer: Mutex<Box<dyn Write + Send>>,
+    start: Instant,
+}
+
+static EVENT_STREAM: OnceLock<EventStream> = OnceLock::new();
+
+/// Opens the process-wide event stream over any writer.
+///
+/// Returns `false` if a stream was already open, in which case the new writer is dropped.
+pub fn open_event_stream(writer: Box<dyn Write + Send>) -> bool {
+    EVENT_STREAM
+        .set(EventStream {
+            writer: Mutex::new(writer),
+            start: Instant::now(),
+        })
+        .is_ok()
+}
+
+/// Opens the event stream over a file descriptor that was already opened by the parent process,
+/// e.g. `3` when the orchestrator is started with `3>events.jsonl`.
+#[cfg(unix)]
+pub fn open_event_stream_fd(fd: i32) -> std::io::Result<bool> {
+    use std::os::fd::FromRawFd;
+    if fd < 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Invalid file descriptor {}", fd),
+        ));
+    }
+    // the descriptor is owned by the stream from now on and closed at process exit
+    let file = unsafe { std::fs::File::from_raw_fd(fd) };
+    Ok(open_event_stream(Box::new(file)))
+}
+
+/// Opens the event stream by connecting to a Unix socket that is listening at `path`.
+#[cfg(unix)]
+pub fn open_event_stream_socket(path: &Path) -> std::io::Result<bool> {
+    let stream = std::os::unix::net::UnixStream::connect(path)?;
+    Ok(open_event_stream(Box::new(stream)))
+}
+
+/// Returns whether an event stream is open, so that callers can skip
+/// building expensive events when nobody is listening.
+pub fn is_event_stream_open() -> bool {
+    EVENT_STREAM.get().is_some()
+}
+
+/// Writes `event` as one JSON line in the event stream, if one is open.
+///
+/// Failures to write are not fatal for the orchestration and are only logged.
+pub fn emit(event: ProgressEvent) {
+    if let Some(stream) = EVENT_STREAM.get() {
+        let line = EventLine {
+            elapsed_ms: stream.start.elapsed().as_millis(),
+            event: &event,
+        };
+        match serde_json::to_string(&line) {
+            Ok(s) => {
+                if let Ok(mut writer) = stream.writer.lock() {
+                    if writeln!(writer, "{}", s)
+                        .and_then(|_| writer.flush())
+                        .is_err()
+                    {
+                        debug!("Failed to write progress event to the event stream");
+                    }
+                }
+            }
+            Err(e) => debug!("Failed to serialize progress event: {}", e),
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! A dry-run explanation of what an exploration would do.
+//!
+//! The orchestrator can stop right after identification and bidding and print, instead of
+//! exploring, which decision models were identified and how they dominate each other,
+//! every bid that the explorers made, which bids [idesyde_core::compute_dominant_biddings]
+//! kept and why the others were discarded, and which design elements no kept bid covers.
+use std::{cmp::Ordering, collections::HashSet, fmt::Display, sync::Arc};
+
+use idesyde_core::{DecisionModel, ExplorationBid, Explorer};
+
+use crate::filters::NameFilter;
+
+/// An identified decision model and how it relates to the other identified decision models.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdentifiedEntry {
+    pub category: String,
+    pub part_size: usize,
+    /// Whether the model passed the decision model filters.
+    pub selected: bool,
+    /// Whether the model was kept by [idesyde_core::compute_dominant_identification] and went to bidding.
+    pub dominant: bool,
+    /// The categories of the identified models that this one dominates.
+    pub dominates: Vec<String>,
+}
+
+/// Why a bid was or was not kept for exploration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BidVerdict {
+    Kept,
+    CannotExplore,
+    /// Another explorable bid is for a decision model that covers strictly more design elements.
+    Covered {
+        explorer: String,
+        decision_model: String,
+    },
+    /// Another explorable bid is for a decision model with the same design elements and is a better bid.
+    Outbid {
+        explorer: String,
+        decision_model: String,
+    },
+}
+
+impl Display for BidVerdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BidVerdict::Kept => write!(f, "kept"),
+            BidVerdict::CannotExplore => write!(f, "discarded: the explorer cannot explore it"),
+            BidVerdict::Covered {
+                explorer,
+                decision_model,
+            } => write!(
+                f,
+                "discarded: {} with {} covers strictly more design elements",
+                decision_model, explorer
+            ),
+            BidVerdict::Outbid {
+                explorer,
+                decision_model,
+            } => write!(
+                f,
+                "discarded: {} with {} covers the same design elements with a better bid",
+                decision_model, explorer
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BidEntry {
+    pub explorer: String,
+    pub decision_model: String,
+    pub bid: ExplorationBid,
+    pub verdict: BidVerdict,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ExplainReport {
+    pub identified: Vec<IdentifiedEntry>,
+    pub bids: Vec<BidEntry>,
+    /// Design elements that are not part of any kept bid, sorted.
+    pub uncovered: Vec<String>,
+}
+
+/// An explorer, the decision model it bid for and its bid.
+pub type Bidding = (Arc<dyn Explorer>, Arc<dyn DecisionModel>, ExplorationBid);
+
+/// Builds the explanation of an exploration.
+///
+/// `identified` are all identified decision models, before the `decision_model_filter`
+/// is applied, and `biddings` must contain every bid made, including the ones that cannot explore.
+/// The kept bids are computed with [idesyde_core::compute_dominant_biddings]
+/// exactly as the orchestrator does before exploring.
+pub fn explain(
+    identified: &[Arc<dyn DecisionModel>],
+    dominant_identified: &[Arc<dyn DecisionModel>],
+    biddings: &[Bidding],
+    decision_model_filter: &NameFilter,
+    design_elements: &HashSet<String>,
+) -> ExplainReport {
+    let identified_entries = identified
+        .iter()
+        .map(|m| IdentifiedEntry {
+            category: m.category(),
+            part_size: m.part().len(),
+            selected: decision_model_filter.accepts(&m.category()),
+            dominant: dominant_identified.contains(m),
+            dominates: identified
+                .iter()
+                .filter(|o| *o != m && m.partial_cmp(o) == Some(Ordering::Greater))
+                .map(|o| o.category())
+                .collect(),
+        })
+        .collect();
+    let considered_idx: Vec<usize> = biddings
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, _, b))| b.can_explore)
+        .map(|(i, _)| i)
+        .collect();
+    let considered: Vec<Bidding> = considered_idx
+        .iter()
+        .map(|i| biddings[*i].clone())
+        .collect();
+    let kept: Vec<usize> = idesyde_core::compute_dominant_biddings(&considered)
+        .into_iter()
+        .map(|i| considered_idx[i])
+        .collect();
+    let bids = biddings
+        .iter()
+        .enumerate()
+        .map(|(i, (explorer, m, b))| {
+            let verdict = if kept.contains(&i) {
+                BidVerdict::Kept
+            } else if !b.can_explore {
+                BidVerdict::CannotExplore
+            } else {
+                discard_reason(m, b, &considered)
+            };
+            BidEntry {
+                explorer: explorer.unique_identifier(),
+                decision_model: m.category(),
+                bid: b.to_owned(),
+                verdict,
+            }
+        })
+        .collect();
+    let covered: HashSet<String> = kept.iter().flat_map(|i| biddings[*i].1.part()).collect();
+    let mut uncovered: Vec<String> = design_elements.difference(&covered).cloned().collect();
+    uncovered.sort();
+    ExplainReport {
+        identified: identified_entries,
+        bids,
+        uncovered,
+    }
+}
+
+/// Finds the bid that made [idesyde_core::compute_dominant_biddings] discard the bid `b` on `m`,
+/// following the same conditions.
+fn discard_reason(
+    m: &Arc<dyn DecisionModel>,
+    b: &ExplorationBid,
+    considered: &[Bidding],
+) -> BidVerdict {
+    let left_part = m.part();
+    considered
+        .iter()
+        .filter(|(_, mm, bb)| b != bb && m != mm)
+        .find_map(|(explorer, mm, bb)| {
+            let right_part = mm.part();
+            if left_part != right_part && left_part.is_subset(&right_part) {
+                Some(BidVerdict::Covered {
+                    explorer: explorer.unique_identifier(),
+                    decision_model: mm.category(),
+                })
+            } else if left_part == right_part && b.partial_cmp(bb) == Some(Ordering::Greater) {
+                Some(BidVerdict::Outbid {
+                    explorer: explorer.unique_identifier(),
+                    decision_model: mm.category(),
+                })
+            } else {
+                None
+            }
+        })
+        // only reachable if the dominance computation changes without this function following it
+        .unwrap_or(BidVerdict::Kept)
+}
+
+impl Display for ExplainReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Identified decision models ({}):", self.identified.len())?;
+        for entry in &self.identified {
+            writeln!(
+                f,
+                "  {} {} ({} design elements){}",
+                if !entry.selected {
+                    "x"
+                } else if entry.dominant {
+                    "*"
+                } else {
+                    " "
+                },
+                entry.category,
+                entry.part_size,
+                if entry.dominates.is_empty() {
+                    String::new()
+                } else {
+                    format!(", dominates {}", entry.dominates.join(", "))
+                }
+            )?;
+        }
+        writeln!(
+            f,
+            "  (* marks the dominant models, which are the ones sent to bidding, and x the ones excluded by the filters)"
+        )?;
+        writeln!(f)?;
+        writeln!(f, "Bids ({}):", self.bids.len())?;
+        let explorer_width = self
+            .bids
+            .iter()
+            .map(|b| b.explorer.len())
+            .chain(std::iter::once("explorer".len()))
+            .max()
+            .unwrap_or(0);
+        let model_width = self
+            .bids
+            .iter()
+            .map(|b| b.decision_model.len())
+            .chain(std::iter::once("decision model".len()))
+            .max()
+            .unwrap_or(0);
+        writeln!(
+            f,
+            "  {:ew$}  {:mw$}  {:7}  {:5}  {:15}  {:20}  verdict",
+            "explorer",
+            "decision model",
+            "explore",
+            "exact",
+            "competitiveness",
+            "objectives",
+            ew = explorer_width,
+            mw = model_width
+        )?;
+        for entry in &self.bids {
+            let mut objectives: Vec<&String> = entry.bid.target_objectives.iter().collect();
+            objectives.sort();
+            writeln!(
+                f,
+                "  {:ew$}  {:mw$}  {:7}  {:5}  {:15}  {:20}  {}",
+                entry.explorer,
+                entry.decision_model,
+                entry.bid.can_explore,
+                entry.bid.is_exact,
+                entry.bid.competitiveness,
+                objectives
+                    .iter()
+                    .map(|s| s.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(","),
+                entry.verdict,
+                ew = explorer_width,
+                mw = model_width
+            )?;
+        }
+        writeln!(f)?;
+        if self.uncovered.is_empty() {
+            write!(f, "All design elements are covered by the kept bids.")
+        } else {
+            write!(
+                f,
+                "Design elements not covered by any kept bid ({}):\n  {}",
+                self.uncovered.len(),
+                self.uncovered.join(", ")
+            )
+        }
+    }
+}
@@ -2,21 +2,29 @@ use std::{
     cmp::Ordering,
     collections::{HashMap, HashSet, VecDeque},
     sync::{
+        atomic::AtomicBool,
         mpsc::{Receiver, Sender},
         Arc, Mutex,
     },
     time::{Duration, Instant},
 };
 
+use crate::events::{self, ProgressEvent};
+use crate::interrupt;
+use crate::trace::ExplorationTrace;
+#[cfg(feature = "http-modules")]
+use crate::{send_with_retries, ExternalServerModule, ModuleClientConfiguration};
 use derive_builder::Builder;
 use idesyde_blueprints::ExplorationSolutionMessage;
 use idesyde_core::{
     DecisionModel, ExplorationBid, ExplorationConfiguration, ExplorationConfigurationBuilder,
     ExplorationSolution, Explorer, OpaqueDecisionModel,
 };
-use log::{debug, warn};
+#[cfg(feature = "http-modules")]
 use reqwest::blocking::multipart::Form;
 use serde::{Deserialize, Serialize};
+use tracing::{debug, error, info_span, warn};
+#[cfg(feature = "http-modules")]
 use url::Url;
 
 // use rayon::prelude::*;
@@ -66,10 +74,12 @@ impl TryFrom<&str> for ExplorationRequestMessage {
     }
 }
 
+#[cfg(feature = "http-modules")]
 pub struct ExternalExplorerSolutionIter {
     pub websocket: tungstenite::WebSocket<std::net::TcpStream>,
 }
 
+#[cfg(feature = "http-modules")]
 impl ExternalExplorerSolutionIter {
     /// This creation function assumes that the channel is already sending back solutions
     pub fn new<I>(c: I) -> ExternalExplorerSolutionIter
@@ -82,6 +92,7 @@ impl ExternalExplorerSolutionIter {
     }
 }
 
+#[cfg(feature = "http-modules")]
 impl Iterator for ExternalExplorerSolutionIter {
     type Item = ExplorationSolution;
 
@@ -137,6 +148,7 @@ impl Iterator for ExternalExplorerSolutionIter {
     }
 }
 
+#[cfg(feature = "http-modules")]
 impl Drop for ExternalExplorerSolutionIter {
     fn drop(&mut self) {
         if self.websocket.can_write() {
@@ -148,49 +160,99 @@ impl Drop for ExternalExplorerSolutionIter {
     }
 }
 
+#[cfg(feature = "http-modules")]
 #[derive(Builder, Clone)]
 pub struct ExternalExplorer {
     name: String,
     url: Url,
     client: Arc<reqwest::blocking::Client>,
+    /// The module serving this explorer, if it is supervised by the orchestrator.
+    #[builder(default)]
+    module: Option<ExternalServerModule>,
+    #[builder(default)]
+    configuration: ModuleClientConfiguration,
 }
 
+#[cfg(feature = "http-modules")]
+impl ExternalExplorer {
+    /// The current address of the explorer. If the explorer comes from a supervised module,
+    /// the module is checked first and restarted if needed, which might change its address.
+    fn current_url(&self) -> Url {
+        match &self.module {
+            Some(module) => {
+                module.ensure_alive();
+                module.url()
+            }
+            None => self.url.to_owned(),
+        }
+    }
+}
+
+#[cfg(feature = "http-modules")]
 impl Explorer for ExternalExplorer {
     fn unique_identifier(&self) -> String {
         self.name.to_owned()
     }
 
     fn location_url(&self) -> std::option::Option<url::Url> {
-        Some(self.url.to_owned())
+        Some(
+            self.module
+                .as_ref()
+                .map(|module| module.url())
+                .unwrap_or(self.url.to_owned()),
+        )
     }
 
     fn bid(&self, m: Arc<dyn DecisionModel>) -> ExplorationBid {
         let model_hash = m.global_sha2_hash();
-        let exists = self
-            .url
-            .join("/decision/cache/exists")
-            .ok()
-            .and_then(|u| self.client.get(u).body(model_hash.clone()).send().ok())
-            .and_then(|r| r.text().ok())
-            .map(|t| t.eq_ignore_ascii_case("true"))
-            .unwrap_or(false);
-        if !exists {
-            // debug!("{} is not in cache for {}. Adding it with {:?}.", m.category(), self.unique_identifier(), m.global_sha2_hash());
-            if let Ok(json_str) = OpaqueDecisionModel::from(m).to_json() {
-                if let Ok(r) = self
-                    .client
-                    .post(self.url.join("/decision/cache/add").unwrap())
-                    .multipart(Form::new().text("decisionModel", json_str))
-                    .send()
-                {
-                    // debug!("Added decision model to cache: {:?}", r.bytes().unwrap());
-                    debug!("{}", r.text().unwrap());
-                };
+        let url = self.current_url();
+        match &self.module {
+            Some(module) => module.sync_decision_models("decision", std::slice::from_ref(&m)),
+            None => {
+                let exists = url
+                    .join("/decision/cache/exists")
+                    .ok()
+                    .and_then(|u| {
+                        send_with_retries(
+                            &self.configuration,
+                            &format!("Checking the cache of {}", self.unique_identifier()),
+                            || self.client.get(u.clone()).body(model_hash.clone()),
+                        )
+                    })
+                    .and_then(|r| r.text().ok())
+                    .map(|t| t.eq_ignore_ascii_case("true"))
+                    .unwrap_or(false);
+                if !exists {
+                    if let Ok(json_str) = OpaqueDecisionModel::from(m).to_json() {
+                        if let Ok(add_url) = url.join("/decision/cache/add") {
+                            if send_with_retries(
+                                &self.configuration,
+                                &format!(
+                                    "Adding a model to the cache of {}",
+                                    self.unique_identifier()
+                                ),
+                                || {
+                                    self.client.post(add_url.clone()).multipart(
+                                        Form::new().text("decisionModel", json_str.clone()),
+                                    )
+                                },
+                            )
+                            .is_none()
+                            {
+                                debug!("Failed to add decision model to cache");
+                            };
+                        }
+                    }
+                }
             }
         }
-        if let Ok(bid_url) = self.url.join(format!("/{}/bid", self.name).as_str()) {
-            match self.client.get(bid_url).body(model_hash).send() {
-                Ok(result) => match result.text() {
+        if let Ok(bid_url) = url.join(format!("/{}/bid", self.name).as_str()) {
+            match send_with_retries(
+                &self.configuration,
+                &format!("Bidding of {}", self.unique_identifier()),
+                || self.client.get(bid_url.clone()).body(model_hash.clone()),
+            ) {
+                Some(result) => match result.text() {
                     Ok(text) => {
                         if !text.is_empty() {
                             match ExplorationBid::from_json_str(&text) {
@@ -212,11 +274,10 @@ impl Explorer for ExternalExplorer {
                         );
                     }
                 },
-                Err(err) => {
+                None => {
                     debug!(
-                        "Explorer {} failed to request with: {}",
-                        self.unique_identifier(),
-                        err.to_string()
+                        "Explorer {} failed to answer the bidding request",
+                        self.unique_identifier()
                     );
                 }
             }
@@ -230,7 +291,7 @@ impl Explorer for ExternalExplorer {
         currrent_solutions: &HashSet<ExplorationSolution>,
         exploration_configuration: ExplorationConfiguration,
     ) -> Arc<Mutex<dyn Iterator<Item = ExplorationSolution> + Send + Sync>> {
-        let mut mut_url = self.url.clone();
+        let mut mut_url = self.current_url();
         if let Err(_) = mut_url.set_scheme("ws") {
             warn!(
                 "Failed to set up exploration websocket. Module {} is likely to fail exploration.",
@@ -241,9 +302,21 @@ impl Explorer for ExternalExplorer {
             mut_url.join(format!("/{}/explore", self.unique_identifier()).as_str())
         {
             if let Ok(explore_sckt) = explore_url.socket_addrs(|| None) {
-                if let Some((mut ws, _)) = std::net::TcpStream::connect(explore_sckt[0])
-                    .ok()
-                    .and_then(|stream| tungstenite::client(explore_url, stream).ok())
+                if let Some((mut ws, _)) = explore_sckt
+                    .first()
+                    .and_then(|addr| {
+                        std::net::TcpStream::connect_timeout(
+                            addr,
+                            self.configuration.connect_timeout,
+                        )
+                        .ok()
+                    })
+                    .and_then(|stream| {
+                        // the handshake and the request are bounded like any other request
+                        let _ = stream.set_read_timeout(Some(self.configuration.read_timeout));
+                        let _ = stream.set_write_timeout(Some(self.configuration.read_timeout));
+                        tungstenite::client(explore_url, stream).ok()
+                    })
                 {
                     if let Ok(design_cbor) = OpaqueDecisionModel::from(m).to_json() {
                         if let Err(e) = ws.send(tungstenite::Message::text(design_cbor)) {
@@ -271,6 +344,18 @@ impl Explorer for ExternalExplorer {
                         warn!("Failed to send exploration request to {} for exploration. Exploration is likely to fail.", self.unique_identifier());
                         debug!("Message was: {}", e.to_string());
                     };
+                    // finding a solution can take long, so a solution is waited for only
+                    // as long as the exploration itself would wait for one.
+                    let solution_timeout = [
+                        exploration_configuration.improvement_timeout,
+                        exploration_configuration.total_timeout,
+                    ]
+                    .into_iter()
+                    .find(|t| *t > 0)
+                    .map(Duration::from_secs);
+                    if let Err(e) = ws.get_ref().set_read_timeout(solution_timeout) {
+                        debug!("Failed to set the exploration read timeout: {}", e);
+                    }
                     return Arc::new(Mutex::new(ExternalExplorerSolutionIter::new(ws)));
                 }
             } else {
@@ -281,6 +366,147 @@ impl Explorer for ExternalExplorer {
     }
 }
 
+/// When the cooperative exploration restarts the explorers with the updated Pareto set,
+/// which opens a new exploration level.
+///
+/// Restarting often gives the explorers the latest solutions to improve upon, but throws away
+/// their search state, which is costly for exact explorers and causes churn for heuristics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RestartPolicy {
+    /// Restart on every new non-dominated solution.
+    #[default]
+    EveryImprovement,
+    /// Restart once this many new non-dominated solutions were found since the last restart.
+    AfterImprovements(u64),
+    /// Restart once this many seconds passed since the last restart and there is a new solution.
+    AfterSeconds(u64),
+    /// Never restart. The explorers run isolated on their first level, since an explorer only
+    /// receives solutions when it starts, and their solutions are only combined in the
+    /// Pareto set approximation of the orchestrator.
+    Never,
+}
+
+impl std::str::FromStr for RestartPolicy {
+    type Err = String;
+
+    /// Parses `every`, `never`, `improvements:<N>` or `seconds:<T>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+        match lower.split_once(':') {
+            None if lower == "every" => Ok(RestartPolicy::EveryImprovement),
+            None if lower == "never" => Ok(RestartPolicy::Never),
+            Some(("improvements", n)) => n
+                .parse()
+                .map(RestartPolicy::AfterImprovements)
+                .map_err(|e| format!("invalid number of improvements '{}': {}", n, e)),
+            Some(("seconds", t)) => t
+                .parse()
+                .map(RestartPolicy::AfterSeconds)
+                .map_err(|e| format!("invalid number of seconds '{}': {}", t, e)),
+            _ => Err(format!(
+                "unknown restart policy '{}'. Expected 'every', 'never', 'improvements:<N>' or 'seconds:<T>'",
+                s
+            )),
+        }
+    }
+}
+
+/// What the cooperative exploration does with a solution that fails [solution_violations].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SolutionValidation {
+    /// Log the violations and keep the solution.
+    #[default]
+    Warn,
+    /// Log the violations and discard the solution.
+    Drop,
+    /// Log the violations and stop the exploration as [ExplorationTermination::Aborted].
+    Abort,
+}
+
+impl std::str::FromStr for SolutionValidation {
+    type Err = String;
+
+    /// Parses `warn`, `drop` or `abort`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "warn" => Ok(SolutionValidation::Warn),
+            "drop" => Ok(SolutionValidation::Drop),
+            "abort" => Ok(SolutionValidation::Abort),
+            _ => Err(format!(
+                "unknown solution validation '{}'. Expected 'warn', 'drop' or 'abort'",
+                s
+            )),
+        }
+    }
+}
+
+/// Why `solution` is not a valid solution of `explored` for the explorer that made `bid`, or nothing if it is.
+///
+/// The solved decision model must have the same category and the same part as the explored one,
+/// and the solution must have a value for every target objective of the bid.
+pub fn solution_violations(
+    explored: &dyn DecisionModel,
+    bid: &ExplorationBid,
+    solution: &ExplorationSolution,
+) -> Vec<String> {
+    let mut violations = vec![];
+    if solution.solved.category() != explored.category() {
+        violations.push(format!(
+            "the solved decision model is a {} instead of a {}",
+            solution.solved.category(),
+            explored.category()
+        ));
+    }
+    let (explored_part, solved_part) = (explored.part(), solution.solved.part());
+    if solved_part != explored_part {
+        violations.push(format!(
+            "the solved decision model misses {} and adds {} of the explored design elements",
+            explored_part.difference(&solved_part).count(),
+            solved_part.difference(&explored_part).count()
+        ));
+    }
+    let mut missing: Vec<&String> = bid
+        .target_objectives
+        .iter()
+        .filter(|o| !solution.objectives.contains_key(*o))
+        .collect();
+    if !missing.is_empty() {
+        missing.sort();
+        violations.push(format!(
+            "the objectives {} are missing",
+            missing
+                .iter()
+                .map(|o| o.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        ));
+    }
+    violations
+}
+
+/// How the cooperative exploration manages its levels.
+#[derive(Debug, Clone, PartialEq, Eq, Builder)]
+pub struct ExplorationStrategy {
+    #[builder(default)]
+    pub restart_policy: RestartPolicy,
+    /// What to do with the solutions that fail [solution_violations].
+    #[builder(default)]
+    pub solution_validation: SolutionValidation,
+    /// The maximum number of levels exploring at the same time. Once exceeded, the oldest level
+    /// is marked as dominated and its explorers stop.
+    #[builder(default = "2")]
+    pub max_concurrent_levels: usize,
+}
+
+impl Default for ExplorationStrategy {
+    fn default() -> Self {
+        ExplorationStrategyBuilder::default()
+            .build()
+            .expect("Failed to build default exploration strategy. Should never fail.")
+    }
+}
+
 #[derive(PartialEq, Eq, Copy, Clone, Debug)]
 pub enum ExplorationStatus {
     Optimal,
@@ -288,15 +514,60 @@ pub enum ExplorationStatus {
     Unknown,
 }
 
+/// Why an exploration stopped returning solutions.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExplorationTermination {
+    /// An exact explorer finished, so the solutions found are optimal or, if there are none, there are none at all.
+    Complete,
+    /// Every explorer finished without proving that its solutions are optimal.
+    Exhausted,
+    /// The total time-out or the improvement time-out of every level was reached.
+    TimedOut,
+    /// The orchestration was interrupted or the exploration cancelled, see [interrupt::should_stop].
+    Interrupted,
+    /// An explorer returned an invalid solution with [SolutionValidation::Abort].
+    Aborted,
+}
+
+impl ExplorationTermination {
+    /// The termination of an exploration made of independent explorations, each given with whether it found
+    /// any solution.
+    ///
+    /// If any of them is complete without solutions, there is no solution for the whole exploration either,
+    /// so it is complete. Otherwise, it is the least conclusive of them.
+    pub fn combine(
+        terminations: &[(ExplorationTermination, bool)],
+    ) -> Option<ExplorationTermination> {
+        if terminations.contains(&(ExplorationTermination::Complete, false)) {
+            return Some(ExplorationTermination::Complete);
+        }
+        [
+            ExplorationTermination::Aborted,
+            ExplorationTermination::Interrupted,
+            ExplorationTermination::TimedOut,
+            ExplorationTermination::Exhausted,
+            ExplorationTermination::Complete,
+        ]
+        .into_iter()
+        .find(|t| terminations.iter().any(|(u, _)| u == t))
+    }
+}
+
 pub fn explore_level_non_blocking(
     explorers_and_models: &[(Arc<dyn Explorer>, Arc<dyn DecisionModel>)],
     biddings: &[ExplorationBid],
     configuration: &ExplorationConfiguration,
     solutions: &HashSet<ExplorationSolution>,
-) -> (Arc<Mutex<ExplorationStatus>>, Receiver<ExplorationSolution>) {
+    level: u64,
+) -> (
+    Arc<Mutex<ExplorationStatus>>,
+    Receiver<(usize, ExplorationSolution)>,
+) {
     let status = Arc::new(Mutex::new(ExplorationStatus::Unknown));
-    let (level_tx, level_rx) = std::sync::mpsc::channel::<ExplorationSolution>();
-    for ((explorer, model), b) in explorers_and_models.iter().zip(biddings.iter()) {
+    let (level_tx, level_rx) = std::sync::mpsc::channel::<(usize, ExplorationSolution)>();
+    for (k, ((explorer, model), b)) in explorers_and_models.iter().zip(biddings.iter()).enumerate()
+    {
         let explorer = explorer.clone();
         let model = model.clone();
         let conf = configuration.to_owned();
@@ -319,6 +590,7 @@ pub fn explore_level_non_blocking(
         let level_tx = level_tx.clone();
         let this_status = status.clone();
         let is_exact = b.is_exact;
+        let explorer_id = explorer.unique_identifier();
         let time_out_duration = if configuration.improvement_timeout > 0 {
             Some(Duration::from_secs(configuration.improvement_timeout))
         } else {
@@ -339,32 +611,38 @@ pub fn explore_level_non_blocking(
             // }
             // if let Some(conf) = configurations.pop_front() {
             // let tout = conf.improvement_timeout.to_owned();
+            let _level_span = info_span!(
+                "exploration_level",
+                level,
+                explorer = explorer_id.as_str(),
+                decision_model = model.category()
+            )
+            .entered();
+            debug!("Starting exploration level");
             let iter_mutex =
                 explorer.explore(model.to_owned(), &current_solutions, conf.to_owned());
             let start = Instant::now();
             if let Ok(mut iter) = iter_mutex.lock() {
                 while let Some(sol) = iter.next() {
-                    if current_solutions
-                        .iter()
-                        .all(|cur| cur.partial_cmp(&sol) != Some(Ordering::Less))
-                        && !current_solutions.contains(&sol)
-                    {
-                        match level_tx.send(sol) {
-                            Ok(_) => (),
-                            Err(_) => return,
-                        }
+                    // dominated solutions are also sent, so that they can be traced,
+                    // and the receiver discards them
+                    match level_tx.send((k, sol)) {
+                        Ok(_) => (),
+                        Err(_) => return,
                     }
                     if let Some(duration) = time_out_duration {
                         if start.elapsed() >= duration {
                             return;
                         }
                     }
-                    if this_status
-                        .lock()
-                        .map(|x| {
-                            *x == ExplorationStatus::Dominated || *x == ExplorationStatus::Optimal
-                        })
-                        .unwrap_or(true)
+                    if interrupt::should_stop()
+                        || this_status
+                            .lock()
+                            .map(|x| {
+                                *x == ExplorationStatus::Dominated
+                                    || *x == ExplorationStatus::Optimal
+                            })
+                            .unwrap_or(true)
                     {
                         return;
                     }
@@ -403,12 +681,79 @@ pub struct MultiLevelCombinedExplorerIterator3 {
     // levels: Vec<CombinedExplorerIterator>,
     // levels_tuple: (Option<CombinedExplorerIterator>, CombinedExplorerIterator),
     current_solutions: HashSet<ExplorationSolution>,
-    level_streams: Vec<Receiver<ExplorationSolution>>,
+    level_streams: Vec<Receiver<(usize, ExplorationSolution)>>,
     levels_status: Vec<Arc<Mutex<ExplorationStatus>>>,
     levels_start: Vec<Instant>,
+    levels_id: Vec<u64>,
+    num_levels: u64,
     num_found: u64,
     // converged_to_last_level: bool,
     start: Instant,
+    strategy: ExplorationStrategy,
+    improvements_since_restart: u64,
+    last_restart: Instant,
+    trace: Option<(ExplorationTrace, usize)>,
+    any_level_timed_out: bool,
+    termination: Option<ExplorationTermination>,
+    stop: Option<Arc<AtomicBool>>,
+}
+
+impl MultiLevelCombinedExplorerIterator3 {
+    /// Records every solution received from now on in `trace`, as part of the given sub-problem.
+    pub fn with_trace(mut self, trace: ExplorationTrace, subproblem: usize) -> Self {
+        self.trace = Some((trace, subproblem));
+        self
+    }
+
+    /// Stops the exploration, as if interrupted, once `stop` is set.
+    pub fn with_stop_flag(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.stop = Some(stop);
+        self
+    }
+
+    /// Why the exploration stopped, or `None` if it has not stopped yet.
+    pub fn termination(&self) -> Option<ExplorationTermination> {
+        self.termination
+    }
+
+    fn terminate(&mut self, termination: ExplorationTermination) -> Option<ExplorationSolution> {
+        self.termination = Some(termination);
+        None
+    }
+
+    fn should_restart(&self) -> bool {
+        match self.strategy.restart_policy {
+            RestartPolicy::EveryImprovement => self.improvements_since_restart > 0,
+            RestartPolicy::AfterImprovements(n) => self.improvements_since_restart >= n.max(1),
+            RestartPolicy::AfterSeconds(t) => {
+                self.improvements_since_restart > 0
+                    && self.last_restart.elapsed() >= Duration::from_secs(t)
+            }
+            RestartPolicy::Never => false,
+        }
+    }
+
+    fn restart(&mut self) {
+        let (is_dominated, new_level) = explore_level_non_blocking(
+            &self.explorers_and_models,
+            self.biddings.as_slice(),
+            &self.exploration_configuration,
+            &self.current_solutions,
+            self.num_levels,
+        );
+        self.level_streams.push(new_level);
+        self.levels_status.push(is_dominated);
+        self.levels_start.push(Instant::now());
+        self.levels_id.push(self.num_levels);
+        self.num_levels += 1;
+        self.improvements_since_restart = 0;
+        self.last_restart = Instant::now();
+        events::emit(ProgressEvent::LevelRestart {
+            level: self.num_levels - 1,
+            active_levels: self.level_streams.len(),
+            pareto_size: self.current_solutions.len(),
+        });
+    }
 }
 
 impl Iterator for MultiLevelCombinedExplorerIterator3 {
@@ -420,27 +765,93 @@ impl Iterator for MultiLevelCombinedExplorerIterator3 {
                 && self.start.elapsed()
                     > Duration::from_secs(self.exploration_configuration.total_timeout)
             {
-                return None;
+                return self.terminate(ExplorationTermination::TimedOut);
+            }
+            if interrupt::should_stop() {
+                debug!("Exploration interrupted");
+                return self.terminate(ExplorationTermination::Interrupted);
+            }
+            if self
+                .stop
+                .as_ref()
+                .map(|s| s.load(std::sync::atomic::Ordering::SeqCst))
+                .unwrap_or(false)
+            {
+                debug!("Exploration stopped from outside");
+                return self.terminate(ExplorationTermination::Interrupted);
             }
             if self.level_streams.len() == 0 {
-                return None;
+                return if self.any_level_timed_out {
+                    self.terminate(ExplorationTermination::TimedOut)
+                } else {
+                    self.terminate(ExplorationTermination::Exhausted)
+                };
             }
-            while self.level_streams.len() > 2 {
+            while self.level_streams.len() > self.strategy.max_concurrent_levels.max(1) {
                 let _ = self.levels_status[0]
                     .lock()
                     .map(|mut x| *x = ExplorationStatus::Dominated);
                 self.levels_status.remove(0);
                 self.level_streams.remove(0);
                 self.levels_start.remove(0);
+                self.levels_id.remove(0);
             }
             for i in (0..self.level_streams.len()).rev() {
                 if let Some(level) = self.level_streams.get(i) {
                     match level.recv_timeout(Duration::from_millis(500)) {
-                        Ok(solution) => {
-                            if !self.current_solutions.iter().any(|s| {
+                        Ok((k, solution)) => {
+                            let (explorer, model) = &self.explorers_and_models[k];
+                            let violations =
+                                solution_violations(model.as_ref(), &self.biddings[k], &solution);
+                            if !violations.is_empty() {
+                                let explorer_id = explorer.unique_identifier();
+                                events::emit(ProgressEvent::InvalidSolution {
+                                    explorer: explorer_id.to_owned(),
+                                    decision_model: model.category(),
+                                    violations: violations.clone(),
+                                });
+                                match self.strategy.solution_validation {
+                                    SolutionValidation::Warn => warn!(
+                                        "Explorer {} returned an invalid solution for {}: {}",
+                                        explorer_id,
+                                        model.category(),
+                                        violations.join("; ")
+                                    ),
+                                    SolutionValidation::Drop => {
+                                        warn!(
+                                            "Explorer {} returned an invalid solution for {}: {}. Discarding it.",
+                                            explorer_id,
+                                            model.category(),
+                                            violations.join("; ")
+                                        );
+                                        continue;
+                                    }
+                                    SolutionValidation::Abort => {
+                                        error!(
+                                            "Explorer {} returned an invalid solution for {}: {}. Aborting the exploration.",
+                                            explorer_id,
+                                            model.category(),
+                                            violations.join("; ")
+                                        );
+                                        return self.terminate(ExplorationTermination::Aborted);
+                                    }
+                                }
+                            }
+                            let dominated = self.current_solutions.iter().any(|s| {
                                 s.partial_cmp(&solution) == Some(Ordering::Less)
                                     || s.partial_cmp(&solution) == Some(Ordering::Equal)
-                            }) {
+                            });
+                            if let Some((trace, subproblem)) = &self.trace {
+                                trace.record(
+                                    *subproblem,
+                                    self.levels_id[i],
+                                    &explorer.unique_identifier(),
+                                    &solution.solved.category(),
+                                    dominated,
+                                    &solution.objectives,
+                                );
+                            }
+                            if !dominated {
                                 self.num_found += 1;
                                 // let sol_dominates = self.current_solutions.is_empty()
                                 //     || self.current_solutions.iter().any(|cur_sol| {
@@ -450,21 +861,21 @@ impl Iterator for MultiLevelCombinedExplorerIterator3 {
                                 self.current_solutions.retain(|cur_sol| {
                                     solution.partial_cmp(cur_sol) != Some(Ordering::Less)
                                 });
-                                let (is_dominated, new_level) = explore_level_non_blocking(
-                                    &self.explorers_and_models,
-                                    self.biddings.as_slice(),
-                                    &self.exploration_configuration,
-                                    &self.current_solutions,
-                                );
-                                self.level_streams.push(new_level);
-                                self.levels_status.push(is_dominated);
-                                self.levels_start.push(Instant::now());
+                                self.improvements_since_restart += 1;
+                                if self.should_restart() {
+                                    self.restart();
+                                }
                                 // if sol_dominates {
                                 // }
                                 return Some(solution);
                             }
                         }
                         Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                            // time based restarts must also happen when no solution arrives
+                            if self.should_restart() {
+                                self.restart();
+                                break;
+                            }
                             let improv_timed_out =
                                 if self.exploration_configuration.improvement_timeout > 0 {
                                     self.levels_start[i].elapsed().as_secs()
@@ -473,9 +884,11 @@ impl Iterator for MultiLevelCombinedExplorerIterator3 {
                                     false
                                 };
                             if improv_timed_out {
+                                self.any_level_timed_out = true;
                                 self.level_streams.remove(i);
                                 self.levels_status.remove(i);
                                 self.levels_start.remove(i);
+                                self.levels_id.remove(i);
                                 break;
                             }
                         }
@@ -485,11 +898,12 @@ impl Iterator for MultiLevelCombinedExplorerIterator3 {
                                 .map(|x| *x == ExplorationStatus::Optimal)
                                 .unwrap_or(false);
                             if optimal {
-                                return None;
+                                return self.terminate(ExplorationTermination::Complete);
                             } else {
                                 self.level_streams.remove(i);
                                 self.levels_status.remove(i);
                                 self.levels_start.remove(i);
+                                self.levels_id.remove(i);
                             }
                             break;
                         }
@@ -693,12 +1107,30 @@ pub fn explore_cooperatively(
     current_solutions: &HashSet<ExplorationSolution>,
     exploration_configuration: &ExplorationConfiguration,
     // solution_inspector: F,
+) -> MultiLevelCombinedExplorerIterator3 {
+    explore_cooperatively_with_strategy(
+        explorers_and_models,
+        biddings,
+        current_solutions,
+        exploration_configuration,
+        &ExplorationStrategy::default(),
+    )
+}
+
+/// The same as [explore_cooperatively], but with the levels managed according to `strategy`.
+pub fn explore_cooperatively_with_strategy(
+    explorers_and_models: &[ExplorerAndModel],
+    biddings: &[ExplorationBid],
+    current_solutions: &HashSet<ExplorationSolution>,
+    exploration_configuration: &ExplorationConfiguration,
+    strategy: &ExplorationStrategy,
 ) -> MultiLevelCombinedExplorerIterator3 {
     let (is_dominated, new_level) = explore_level_non_blocking(
         explorers_and_models,
         biddings,
         exploration_configuration,
         current_solutions,
+        0,
     );
     // let combined_explorer = CombinedExplorerIterator2::create(
     //     explorers_and_models,
@@ -718,5 +1150,621 @@ pub fn explore_cooperatively(
         level_streams: vec![new_level],
         levels_status: vec![is_dominated],
         levels_start: vec![Instant::now()],
+        levels_id: vec![0],
+        num_levels: 1,
+        strategy: strategy.to_owned(),
+        improvements_since_restart: 0,
+        last_restart: Instant::now(),
+        trace: None,
+        any_level_timed_out: false,
+        termination: None,
+        stop: None,
+    }
+}
+
+pub type ExplorerAndModel = (Arc<dyn Explorer>, Arc<dyn DecisionModel>);
+
+/// Splits the explorers and decision models into independent sub-problems, i.e. groups whose
+/// decision models share no design element with the decision models of any other group.
+///
+/// The groups are returned as indexes into `explorers_and_models`, each group sorted and
+/// the groups sorted by their first index.
+pub fn independent_subproblems(explorers_and_models: &[ExplorerAndModel]) -> Vec<Vec<usize>> {
+    let parts: Vec<HashSet<String>> = explorers_and_models.iter().map(|(_, m)| m.part()).collect();
+    let mut group_of: Vec<usize> = (0..parts.len()).collect();
+    // a simple union of the groups of any two intersecting parts, until nothing changes
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in 0..parts.len() {
+            for j in (i + 1)..parts.len() {
+                if group_of[i] != group_of[j] && !parts[i].is_disjoint(&parts[j]) {
+                    let (keep, replace) =
+                        (group_of[i].min(group_of[j]), group_of[i].max(group_of[j]));
+                    for g in group_of.iter_mut() {
+                        if *g == replace {
+                            *g = keep;
+                        }
+                    }
+                    changed = true;
+                }
+            }
+        }
+    }
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for i in 0..parts.len() {
+        if group_of[i] == i {
+            groups.push((0..parts.len()).filter(|j| group_of[*j] == i).collect());
+        }
+    }
+    groups
+}
+
+/// A solution made of one solution for each independent sub-problem.
+///
+/// The objectives of the parts are merged. If the same objective name appears in more than one part,
+/// it is qualified with the index of the sub-problem, as in `nUsedPEs#1`, so that no objective is lost.
+#[derive(Clone, PartialEq)]
+pub struct ComposedSolution {
+    pub parts: Vec<ExplorationSolution>,
+    pub objectives: HashMap<String, f64>,
+}
+
+impl ComposedSolution {
+    pub fn from_parts(parts: Vec<ExplorationSolution>) -> ComposedSolution {
+        let mut occurrences: HashMap<&String, usize> = HashMap::new();
+        for part in &parts {
+            for k in part.objectives.keys() {
+                *occurrences.entry(k).or_insert(0) += 1;
+            }
+        }
+        let mut objectives = HashMap::new();
+        for (i, part) in parts.iter().enumerate() {
+            for (k, v) in &part.objectives {
+                if occurrences.get(k).copied().unwrap_or(0) > 1 {
+                    objectives.insert(format!("{}#{}", k, i), *v);
+                } else {
+                    objectives.insert(k.to_owned(), *v);
+                }
+            }
+        }
+        ComposedSolution { parts, objectives }
+    }
+
+    /// The categories of the solved decision models of all parts, joined with `+`.
+    pub fn category(&self) -> String {
+        self.parts
+            .iter()
+            .map(|p| p.solved.category())
+            .collect::<Vec<String>>()
+            .join("+")
+    }
+}
+
+impl From<ExplorationSolution> for ComposedSolution {
+    fn from(value: ExplorationSolution) -> Self {
+        ComposedSolution {
+            objectives: value.objectives.clone(),
+            parts: vec![value],
+        }
+    }
+}
+
+impl PartialOrd<ComposedSolution> for ComposedSolution {
+    fn partial_cmp(&self, other: &ComposedSolution) -> Option<Ordering> {
+        idesyde_core::pareto_dominance_partial_cmp(&self.objectives, &other.objectives)
+    }
+}
+
+type SubproblemTerminations = Arc<Mutex<Vec<Option<(ExplorationTermination, bool)>>>>;
+
+/// This iterator explores independent sub-problems in parallel, each with [explore_cooperatively_with_strategy],
+/// and returns the combinations of their solutions.
+///
+/// Each sub-problem keeps its own Pareto front. Whenever a sub-problem finds a solution
+/// that is not dominated in its front, the iterator returns it combined with every solution in
+/// the fronts of the other sub-problems, once all of them have at least one solution.
+/// In the end, the returned solutions include the Cartesian product of the final fronts.
+pub struct ComposedExplorerIterator {
+    single: Option<MultiLevelCombinedExplorerIterator3>,
+    receiver: Receiver<(usize, ExplorationSolution)>,
+    fronts: Vec<Vec<ExplorationSolution>>,
+    pending: VecDeque<ComposedSolution>,
+    /// The termination of each sub-problem and whether it found any solution, set by its thread when it stops.
+    terminations: SubproblemTerminations,
+    /// Set when a sub-problem is aborted or has no solution, or this iterator is dropped, stopping every sub-problem.
+    stop: Arc<AtomicBool>,
+    interrupted: bool,
+}
+
+impl ComposedExplorerIterator {
+    /// Why the exploration stopped, or `None` if it has not stopped yet.
+    ///
+    /// With more than one sub-problem, this is the least conclusive termination among them,
+    /// unless one of them is complete without solutions, see [ExplorationTermination::combine].
+    pub fn termination(&self) -> Option<ExplorationTermination> {
+        if let Some(single) = &self.single {
+            return single.termination();
+        }
+        if self.infeasible() {
+            return Some(ExplorationTermination::Complete);
+        }
+        if self.aborted() {
+            return Some(ExplorationTermination::Aborted);
+        }
+        if self.interrupted {
+            return Some(ExplorationTermination::Interrupted);
+        }
+        self.terminations
+            .lock()
+            .ok()
+            .and_then(|t| {
+                t.iter()
+                    .copied()
+                    .collect::<Option<Vec<(ExplorationTermination, bool)>>>()
+            })
+            .and_then(|t| ExplorationTermination::combine(&t))
+    }
+
+    /// Whether any sub-problem was aborted, which stops the others too through the shared stop flag.
+    fn aborted(&self) -> bool {
+        self.terminations
+            .lock()
+            .map(|t| {
+                t.iter()
+                    .any(|s| matches!(s, Some((ExplorationTermination::Aborted, _))))
+            })
+            .unwrap_or(false)
+    }
+
+    /// Whether any sub-problem is complete without solutions, so that no composition has any solution.
+    fn infeasible(&self) -> bool {
+        self.terminations
+            .lock()
+            .map(|t| t.contains(&Some((ExplorationTermination::Complete, false))))
+            .unwrap_or(false)
+    }
+}
+
+impl Iterator for ComposedExplorerIterator {
+    type Item = ComposedSolution;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(single) = self.single.as_mut() {
+            return single.next().map(ComposedSolution::from);
+        }
+        loop {
+            if let Some(composed) = self.pending.pop_front() {
+                return Some(composed);
+            }
+            if self.aborted() || self.infeasible() {
+                return None;
+            }
+            if interrupt::should_stop() {
+                self.interrupted = true;
+                return None;
+            }
+            let (group, solution) = match self.receiver.recv_timeout(Duration::from_millis(500)) {
+                Ok(received) => received,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return None,
+            };
+            if self.fronts[group].iter().any(|s| {
+                s.partial_cmp(&solution) == Some(Ordering::Less)
+                    || s.partial_cmp(&solution) == Some(Ordering::Equal)
+            }) {
+                continue;
+            }
+            self.fronts[group].retain(|s| solution.partial_cmp(s) != Some(Ordering::Less));
+            self.fronts[group].push(solution.clone());
+            if self.fronts.iter().all(|f| !f.is_empty()) {
+                let mut combinations: Vec<Vec<ExplorationSolution>> = vec![vec![]];
+                for (i, front) in self.fronts.iter().enumerate() {
+                    let choices: &[ExplorationSolution] = if i == group {
+                        std::slice::from_ref(&solution)
+                    } else {
+                        front.as_slice()
+                    };
+                    combinations = combinations
+                        .into_iter()
+                        .flat_map(|prefix| {
+                            choices.iter().map(move |c| {
+                                let mut combination = prefix.clone();
+                                combination.push(c.clone());
+                                combination
+                            })
+                        })
+                        .collect();
+                }
+                self.pending
+                    .extend(combinations.into_iter().map(ComposedSolution::from_parts));
+            }
+        }
+    }
+}
+
+impl Drop for ComposedExplorerIterator {
+    fn drop(&mut self) {
+        self.stop.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+/// Whether `solution` solves `model`, i.e. it has the same category and part.
+fn solves(model: &dyn DecisionModel, solution: &ExplorationSolution) -> bool {
+    model.category() == solution.solved.category() && model.part() == solution.solved.part()
+}
+
+/// Explores the given explorers and decision models, splitting them first into [independent_subproblems].
+///
+/// If there is only one sub-problem, this is the same as [explore_cooperatively_with_strategy].
+/// Otherwise, each sub-problem starts only from the `current_solutions` of its own decision models,
+/// which seed its explorers but are not returned themselves, as with a single sub-problem.
+pub fn explore_composed(
+    explorers_and_models: &[ExplorerAndModel],
+    biddings: &[ExplorationBid],
+    current_solutions: &HashSet<ExplorationSolution>,
+    exploration_configuration: &ExplorationConfiguration,
+    strategy: &ExplorationStrategy,
+    trace: &ExplorationTrace,
+) -> ComposedExplorerIterator {
+    let groups = independent_subproblems(explorers_and_models);
+    let (tx, rx) = std::sync::mpsc::channel();
+    if groups.len() <= 1 {
+        return ComposedExplorerIterator {
+            single: Some(
+                explore_cooperatively_with_strategy(
+                    explorers_and_models,
+                    biddings,
+                    current_solutions,
+                    exploration_configuration,
+                    strategy,
+                )
+                .with_trace(trace.to_owned(), 0),
+            ),
+            receiver: rx,
+            fronts: vec![],
+            pending: VecDeque::new(),
+            terminations: Arc::new(Mutex::new(vec![])),
+            stop: Arc::new(AtomicBool::new(false)),
+            interrupted: false,
+        };
+    }
+    let terminations = Arc::new(Mutex::new(vec![None; groups.len()]));
+    let stop = Arc::new(AtomicBool::new(false));
+    for (g, group) in groups.iter().enumerate() {
+        let group_explorers_and_models: Vec<_> = group
+            .iter()
+            .map(|i| explorers_and_models[*i].to_owned())
+            .collect();
+        let group_biddings: Vec<ExplorationBid> =
+            group.iter().map(|i| biddings[*i].to_owned()).collect();
+        debug!(
+            "Sub-problem {} explores {}",
+            g,
+            group_explorers_and_models
+                .iter()
+                .map(|(e, m)| format!("{} with {}", m.category(), e.unique_identifier()))
+                .collect::<Vec<String>>()
+                .join(" and ")
+        );
+        let current_solutions: HashSet<ExplorationSolution> = current_solutions
+            .iter()
+            .filter(|s| {
+                group
+                    .iter()
+                    .any(|i| solves(explorers_and_models[*i].1.as_ref(), s))
+            })
+            .cloned()
+            .collect();
+        let configuration = exploration_configuration.to_owned();
+        let strategy = strategy.to_owned();
+        let group_tx = tx.clone();
+        let trace = trace.to_owned();
+        let group_terminations = terminations.clone();
+        let group_stop = stop.clone();
+        std::thread::spawn(move || {
+            let mut iter = explore_cooperatively_with_strategy(
+                group_explorers_and_models.as_slice(),
+                group_biddings.as_slice(),
+                &current_solutions,
+                &configuration,
+                &strategy,
+            )
+            .with_trace(trace, g)
+            .with_stop_flag(group_stop.clone());
+            // the warm start solutions count as found, since they are still solutions of this sub-problem
+            let mut found = !current_solutions.is_empty();
+            for solution in iter.by_ref() {
+                if group_tx.send((g, solution)).is_err() {
+                    return;
+                }
+                found = true;
+            }
+            let termination = iter.termination().map(|t| (t, found));
+            if matches!(
+                termination,
+                Some((ExplorationTermination::Aborted, _))
+                    | Some((ExplorationTermination::Complete, false))
+            ) {
+                group_stop.store(true, std::sync::atomic::Ordering::SeqCst);
+            }
+            if let Ok(mut t) = group_terminations.lock() {
+                t[g] = termination;
+            }
+        });
+    }
+    ComposedExplorerIterator {
+        single: None,
+        receiver: rx,
+        fronts: vec![vec![]; groups.len()],
+        pending: VecDeque::new(),
+        terminations,
+        stop,
+        interrupted: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use idesyde_core::ExplorationBidBuilder;
+
+    #[test]
+    fn restart_policy_from_str() {
+        assert_eq!(
+            "every".parse::<RestartPolicy>(),
+            Ok(RestartPolicy::EveryImprovement)
+        );
+        assert_eq!(" Never ".parse::<RestartPolicy>(), Ok(RestartPolicy::Never));
+        assert_eq!(
+            "improvements:3".parse::<RestartPolicy>(),
+            Ok(RestartPolicy::AfterImprovements(3))
+        );
+        assert_eq!(
+            "SECONDS:10".parse::<RestartPolicy>(),
+            Ok(RestartPolicy::AfterSeconds(10))
+        );
+        assert!("improvements:many".parse::<RestartPolicy>().is_err());
+        assert!("seconds".parse::<RestartPolicy>().is_err());
+        assert!("sometimes".parse::<RestartPolicy>().is_err());
+    }
+
+    #[test]
+    fn solution_validation_from_str() {
+        assert_eq!(
+            "warn".parse::<SolutionValidation>(),
+            Ok(SolutionValidation::Warn)
+        );
+        assert_eq!(
+            "Drop".parse::<SolutionValidation>(),
+            Ok(SolutionValidation::Drop)
+        );
+        assert_eq!(
+            " abort".parse::<SolutionValidation>(),
+            Ok(SolutionValidation::Abort)
+        );
+        assert!("ignore".parse::<SolutionValidation>().is_err());
+    }
+
+    fn opaque(category: &str, part: &[&str]) -> Arc<dyn DecisionModel> {
+        Arc::new(OpaqueDecisionModel {
+            category: category.to_string(),
+            part: part.iter().map(|e| e.to_string()).collect(),
+            body_json: None,
+            body_msgpack: None,
+            body_cbor: None,
+        })
+    }
+
+    #[test]
+    fn solution_violations_of_category_part_and_objectives() {
+        let explored = opaque("Explored", &["a", "b"]);
+        let bid = ExplorationBidBuilder::default()
+            .can_explore(true)
+            .target_objectives(HashSet::from(["cost".to_string()]))
+            .build()
+            .expect("Bid of the tests is complete. Should never fail.");
+        let valid = ExplorationSolution {
+            solved: opaque("Explored", &["a", "b"]),
+            objectives: HashMap::from([("cost".to_string(), 1.0)]),
+        };
+        assert!(solution_violations(explored.as_ref(), &bid, &valid).is_empty());
+        let invalid = ExplorationSolution {
+            solved: opaque("Other", &["a"]),
+            objectives: HashMap::new(),
+        };
+        assert_eq!(
+            solution_violations(explored.as_ref(), &bid, &invalid).len(),
+            3
+        );
+    }
+
+    /// An exact explorer that solves any decision model as is, once for each of the given objectives.
+    struct Fixed(Vec<HashMap<String, f64>>);
+
+    impl Explorer for Fixed {
+        fn unique_identifier(&self) -> String {
+            "Fixed".to_string()
+        }
+
+        fn bid(&self, _m: Arc<dyn DecisionModel>) -> ExplorationBid {
+            ExplorationBidBuilder::default()
+                .can_explore(true)
+                .is_exact(true)
+                .target_objectives(
+                    self.0
+                        .iter()
+                        .flat_map(|o| o.keys().cloned())
+                        .collect::<HashSet<String>>(),
+                )
+                .build()
+                .expect("Bid of the tests is complete. Should never fail.")
+        }
+
+        fn explore(
+            &self,
+            m: Arc<dyn DecisionModel>,
+            _currrent_solutions: &HashSet<ExplorationSolution>,
+            _exploration_configuration: ExplorationConfiguration,
+        ) -> Arc<Mutex<dyn Iterator<Item = ExplorationSolution> + Send + Sync>> {
+            let solutions: Vec<ExplorationSolution> = self
+                .0
+                .iter()
+                .map(|objectives| ExplorationSolution {
+                    solved: m.clone(),
+                    objectives: objectives.clone(),
+                })
+                .collect();
+            Arc::new(Mutex::new(solutions.into_iter()))
+        }
+    }
+
+    fn fixed(objectives: &[&[(&str, f64)]]) -> Arc<dyn Explorer> {
+        Arc::new(Fixed(
+            objectives
+                .iter()
+                .map(|o| o.iter().map(|(k, v)| (k.to_string(), *v)).collect())
+                .collect(),
+        ))
+    }
+
+    fn explore_fixed(
+        explorers_and_models: &[ExplorerAndModel],
+        current_solutions: &HashSet<ExplorationSolution>,
+    ) -> (Vec<ComposedSolution>, Option<ExplorationTermination>) {
+        let biddings: Vec<ExplorationBid> = explorers_and_models
+            .iter()
+            .map(|(e, m)| e.bid(m.clone()))
+            .collect();
+        let configuration = ExplorationConfigurationBuilder::default()
+            .total_timeout(10)
+            .improvement_timeout(0)
+            .time_resolution(100)
+            .memory_resolution(100)
+            .build()
+            .expect("Configuration of the tests is complete. Should never fail.");
+        let mut iter = explore_composed(
+            explorers_and_models,
+            &biddings,
+            current_solutions,
+            &configuration,
+            &ExplorationStrategy::default(),
+            &ExplorationTrace::new(),
+        );
+        let solutions: Vec<ComposedSolution> = iter.by_ref().collect();
+        (solutions, iter.termination())
+    }
+
+    #[test]
+    fn independent_subproblems_share_no_element() {
+        let explorer = fixed(&[]);
+        let explorers_and_models: Vec<ExplorerAndModel> = [
+            opaque("M", &["a", "b"]),
+            opaque("M", &["c"]),
+            opaque("M", &["d", "e"]),
+            opaque("M", &["e", "c"]),
+            opaque("M", &["f"]),
+            opaque("N", &["b"]),
+        ]
+        .into_iter()
+        .map(|m| (explorer.clone(), m))
+        .collect();
+        assert_eq!(
+            independent_subproblems(&explorers_and_models),
+            vec![vec![0, 5], vec![1, 2, 3], vec![4]]
+        );
+    }
+
+    #[test]
+    fn composed_solution_qualifies_repeated_objectives() {
+        let part = |objectives: &[(&str, f64)]| ExplorationSolution {
+            solved: opaque("M", &["a"]),
+            objectives: objectives
+                .iter()
+                .map(|(k, v)| (k.to_string(), *v))
+                .collect(),
+        };
+        let composed = ComposedSolution::from_parts(vec![
+            part(&[("cost", 1.0), ("time", 2.0)]),
+            part(&[("cost", 3.0)]),
+        ]);
+        assert_eq!(
+            composed.objectives,
+            HashMap::from([
+                ("cost#0".to_string(), 1.0),
+                ("cost#1".to_string(), 3.0),
+                ("time".to_string(), 2.0),
+            ])
+        );
+        assert_eq!(composed.category(), "M+M");
+    }
+
+    #[test]
+    fn composed_exploration_returns_the_product_of_the_fronts() {
+        let explorer = fixed(&[
+            &[("cost", 1.0), ("time", 2.0)],
+            &[("cost", 2.0), ("time", 1.0)],
+        ]);
+        let (solutions, termination) = explore_fixed(
+            &[
+                (explorer.clone(), opaque("M", &["a"])),
+                (explorer, opaque("M", &["b"])),
+            ],
+            &HashSet::new(),
+        );
+        let combinations: HashSet<(u64, u64)> = solutions
+            .iter()
+            .map(|s| (s.objectives["cost#0"] as u64, s.objectives["cost#1"] as u64))
+            .collect();
+        assert_eq!(
+            combinations,
+            HashSet::from([(1, 1), (1, 2), (2, 1), (2, 2)])
+        );
+        assert_eq!(termination, Some(ExplorationTermination::Complete));
+    }
+
+    #[test]
+    fn composed_exploration_without_solutions_for_a_subproblem_is_complete() {
+        let (solutions, termination) = explore_fixed(
+            &[
+                (fixed(&[&[("cost", 1.0)]]), opaque("M", &["a"])),
+                (fixed(&[]), opaque("M", &["b"])),
+            ],
+            &HashSet::new(),
+        );
+        assert!(solutions.is_empty());
+        assert_eq!(termination, Some(ExplorationTermination::Complete));
+        assert_eq!(
+            ExplorationTermination::combine(&[
+                (ExplorationTermination::TimedOut, true),
+                (ExplorationTermination::Complete, false),
+            ]),
+            Some(ExplorationTermination::Complete)
+        );
+        assert_eq!(
+            ExplorationTermination::combine(&[
+                (ExplorationTermination::TimedOut, true),
+                (ExplorationTermination::Complete, true),
+            ]),
+            Some(ExplorationTermination::TimedOut)
+        );
+    }
+
+    #[test]
+    fn composed_exploration_does_not_return_the_warm_start_solutions() {
+        let warm = ExplorationSolution {
+            solved: opaque("M", &["a"]),
+            objectives: HashMap::from([("cost".to_string(), 0.0)]),
+        };
+        let (solutions, _) = explore_fixed(
+            &[
+                (fixed(&[&[("cost", 1.0)]]), opaque("M", &["a"])),
+                (fixed(&[&[("cost", 1.0)]]), opaque("M", &["b"])),
+            ],
+            &HashSet::from([warm]),
+        );
+        assert!(solutions
+            .iter()
+            .all(|s| s.objectives.get("cost#0") != Some(&0.0)));
     }
 }
@@ -0,0 +1,1594 @@
+//! Modules and explorers that run as servers, reached over HTTP and websockets.
+//!
+//! The orchestrator starts the module executables and JARs it finds as local servers, or connects to
+//! already running ones by URL, see [crate::find_modules_with_config] and [crate::modules_from_urls].
+//! Only available with the `http-modules` feature.
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use std::io::BufRead;
+use std::io::BufReader;
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Child;
+use std::process::ExitStatus;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::RwLock;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::exploration::ExternalExplorerBuilder;
+use crate::ModuleClientConfiguration;
+
+use idesyde_blueprints::{IdentificationResultCompactMessage, ProtocolInfo, FEATURE_BATCHED_CACHE};
+use idesyde_core::DecisionModel;
+use idesyde_core::DesignModel;
+use idesyde_core::Explorer;
+use idesyde_core::IdentificationResult;
+use idesyde_core::IdentificationRuleLike;
+use idesyde_core::Module;
+
+use idesyde_core::OpaqueDecisionModel;
+use idesyde_core::OpaqueDesignModel;
+use idesyde_core::ReverseIdentificationResult;
+use idesyde_core::ReverseIdentificationRuleLike;
+use rayon::prelude::*;
+use reqwest::blocking::multipart::Form;
+use tracing::debug;
+use tracing::info;
+use tracing::warn;
+use url::Url;
+
+use base64::{engine::general_purpose, Engine as _};
+use reqwest::blocking::multipart::Part;
+use serde::de::DeserializeOwned;
+
+trait LocalServerLike {
+    fn get_process(&self) -> Arc<Mutex<Child>>;
+
+    /// Returns the exit status of the server if it is no longer running.
+    fn exit_status(&self) -> Option<ExitStatus> {
+        if let Ok(mut server_guard) = self.get_process().lock() {
+            return server_guard.try_wait().ok().flatten();
+        }
+        None
+    }
+
+    fn kill(&self) -> Option<()> {
+        if let Ok(mut server_guard) = self.get_process().lock() {
+            return server_guard.kill().ok();
+        }
+        None
+    }
+}
+
+/// How long a local module has to print its `INITIALIZED <port> <name>` line before it is
+/// considered not to be a module server.
+const LOCAL_MODULE_STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Waits until the child prints its `INITIALIZED` line, or until the timeout is over.
+///
+/// The standard output of the child is drained in the background afterwards so that
+/// a module that keeps writing to it never blocks on a full pipe.
+fn wait_for_initialized_line(child: &mut Child, timeout: Duration) -> Option<String> {
+    let stdout = child.stdout.take()?;
+    let (tx, rx) = std::sync::mpsc::channel::<String>();
+    std::thread::spawn(move || {
+        let mut buf = BufReader::new(stdout);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match buf.read_line(&mut line) {
+                Ok(0) | Err(_) => return,
+                Ok(_) => {
+                    if line.starts_with("INITIALIZED") {
+                        let _ = tx.send(line.trim().to_string());
+                        break;
+                    }
+                }
+            }
+        }
+        let _ = std::io::copy(&mut buf, &mut std::io::sink());
+    });
+    rx.recv_timeout(timeout).ok()
+}
+
+impl ModuleClientConfiguration {
+    pub fn build_client(&self) -> reqwest::blocking::Client {
+        reqwest::blocking::Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.read_timeout)
+            .build()
+            .expect("Failed to build the module HTTP client. Should never fail.")
+    }
+}
+
+/// Sends the request built by `make_request`, sending it again with an exponential backoff
+/// if it fails to connect, times out or gets a server error.
+///
+/// Client errors, like a `404` for an endpoint the module does not have, are not retried
+/// and are returned as any other response.
+pub(crate) fn send_with_retries<F>(
+    configuration: &ModuleClientConfiguration,
+    description: &str,
+    make_request: F,
+) -> Option<reqwest::blocking::Response>
+where
+    F: Fn() -> reqwest::blocking::RequestBuilder,
+{
+    let mut backoff = configuration.retry_backoff;
+    for attempt in 0..=configuration.max_retries {
+        let is_last = attempt == configuration.max_retries;
+        match make_request().send() {
+            Ok(response) if !response.status().is_server_error() || is_last => {
+                return Some(response)
+            }
+            Ok(response) => debug!(
+                "{} answered with {}. Retrying in {:?}.",
+                description,
+                response.status(),
+                backoff
+            ),
+            Err(e) if is_last => {
+                warn!(
+                    "{} failed after {} attempt(s). Trying to proceed anyway.",
+                    description,
+                    attempt + 1
+                );
+                debug!("Last error was: {}", e);
+                return None;
+            }
+            Err(e) => debug!(
+                "{} failed with: {}. Retrying in {:?}.",
+                description, e, backoff
+            ),
+        }
+        std::thread::sleep(backoff);
+        backoff *= 2;
+    }
+    None
+}
+
+/// A model ready to be added to the cache of a module.
+struct CachePayload {
+    hash: Vec<u8>,
+    json: String,
+    cbor: Option<Vec<u8>>,
+}
+
+/// The form field used by the `cache/add` endpoints of each kind of cache.
+fn cache_field(kind: &str) -> &'static str {
+    match kind {
+        "design" => "designModel",
+        "solved" => "solvedModel",
+        "reversed" => "reversedModel",
+        _ => "decisionModel",
+    }
+}
+
+fn encode_hashes(hashes: &[Vec<u8>]) -> String {
+    let encoded: Vec<String> = hashes
+        .iter()
+        .map(|h| general_purpose::STANDARD_NO_PAD.encode(h))
+        .collect();
+    serde_json::to_string(&encoded).expect("Failed to serialize hashes. Should never fail.")
+}
+
+/// Asks the module at `url` for its [ProtocolInfo] and checks it against the one of the orchestrator.
+///
+/// Modules without the `/info/protocol` endpoint predate the handshake and are assumed
+/// to speak the [ProtocolInfo::legacy] protocol. The error explains why the module cannot be used.
+fn negotiate_protocol(
+    client: &reqwest::blocking::Client,
+    configuration: &ModuleClientConfiguration,
+    url: &Url,
+) -> Result<ProtocolInfo, String> {
+    let response = url.join("/info/protocol").ok().and_then(|u| {
+        send_with_retries(configuration, "Querying the module protocol", || {
+            client.get(u.clone())
+        })
+    });
+    let protocol = match response {
+        Some(r) if r.status().is_success() => r
+            .text()
+            .ok()
+            .and_then(|t| ProtocolInfo::from_json_str(&t).ok())
+            .ok_or("its protocol information could not be read".to_string())?,
+        Some(r) if r.status() == reqwest::StatusCode::NOT_FOUND => ProtocolInfo::legacy(),
+        Some(r) => {
+            return Err(format!(
+                "it answered {} when asked for its protocol",
+                r.status()
+            ))
+        }
+        None => return Err("it could not be reached".to_string()),
+    };
+    ProtocolInfo::current().check_compatibility(&protocol)?;
+    Ok(protocol)
+}
+
+/// Turns off the parts of the configuration that the module cannot handle.
+fn adapt_to_protocol(
+    name: &str,
+    protocol: &ProtocolInfo,
+    mut configuration: ModuleClientConfiguration,
+) -> ModuleClientConfiguration {
+    if configuration.use_cbor && !protocol.supports_encoding("cbor") {
+        info!(
+            "Module {} does not accept CBOR. Sending it JSON instead.",
+            name
+        );
+        configuration.use_cbor = false;
+    }
+    configuration
+}
+
+/// How often a module server is asked for its identifier to check that it still responds.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long a module server has to answer a health check.
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many lines of the standard error of a dead module are shown in its warning.
+const STDERR_TAIL_LINES: usize = 10;
+
+/// Starts `command_path` as a module server and waits until it is initialized.
+///
+/// The standard error of the server is appended to `stderr_path`, if given, and discarded otherwise.
+/// Returns the running child, the port it listens to and the name it announced, if any.
+pub(crate) fn spawn_local_server(
+    command_path: &Path,
+    stderr_path: Option<&Path>,
+) -> Option<(Child, usize, Option<String>)> {
+    let is_java = command_path
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s == "jar")
+        .unwrap_or(false);
+    let mut command = match is_java {
+        true => {
+            let mut c = std::process::Command::new("java");
+            c.arg("-jar").arg(command_path);
+            c
+        }
+        false => std::process::Command::new(command_path),
+    };
+    let stderr = stderr_path
+        .and_then(|p| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(p)
+                .ok()
+        })
+        .map(Stdio::from)
+        .unwrap_or(Stdio::null());
+    let child_res = command
+        .arg("--server")
+        .arg("http")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(stderr)
+        .spawn();
+    // the test involves just exitting it
+    if let Ok(mut server_child) = child_res {
+        if let Some(initialized_line) =
+            wait_for_initialized_line(&mut server_child, LOCAL_MODULE_STARTUP_TIMEOUT)
+        {
+            // a line without a port is treated as a failed start below.
+            let mut split = initialized_line
+                .strip_prefix("INITIALIZED")
+                .map(str::trim)
+                .unwrap_or_default()
+                .split(' ');
+            let port_opt = split.next().and_then(|x| x.parse::<usize>().ok());
+            if let Some(port) = port_opt {
+                let name = split.next().map(|x| x.to_string());
+                return Some((server_child, port, name));
+            }
+        }
+        debug!(
+            "{} did not start as a module server. Stopping it.",
+            command_path.display()
+        );
+        if let Err(e) = server_child.kill() {
+            debug!(
+                "Ignoring error whilst killing {}: {}",
+                command_path.display(),
+                e
+            );
+        };
+    }
+    None
+}
+
+/// Returns the last `n` lines of a log file, to be shown when a module dies.
+fn log_tail(path: &Path, n: usize) -> Vec<String> {
+    std::fs::read_to_string(path)
+        .map(|content| {
+            let lines: Vec<&str> = content.lines().collect();
+            lines[lines.len().saturating_sub(n)..]
+                .iter()
+                .map(|l| l.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// How many bytes of JSON [UploadedModels] keeps by default for a module.
+const UPLOADED_MODELS_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+/// Models sent to a module server, as JSON, by the cache they were sent to and their hash.
+///
+/// Beyond `max_bytes`, the oldest models are forgotten. This is only a warm-up for a restarted
+/// module, since every request sends the models it needs that are missing in the caches.
+#[derive(Debug)]
+struct UploadedModels {
+    models: HashMap<(String, Vec<u8>), String>,
+    order: VecDeque<(String, Vec<u8>)>,
+    bytes: usize,
+    max_bytes: usize,
+}
+
+impl Default for UploadedModels {
+    fn default() -> Self {
+        UploadedModels::with_max_bytes(UPLOADED_MODELS_MAX_BYTES)
+    }
+}
+
+impl UploadedModels {
+    fn with_max_bytes(max_bytes: usize) -> UploadedModels {
+        UploadedModels {
+            models: HashMap::new(),
+            order: VecDeque::new(),
+            bytes: 0,
+            max_bytes,
+        }
+    }
+
+    fn insert(&mut self, kind: &str, hash: Vec<u8>, json: String) {
+        let key = (kind.to_string(), hash);
+        if self.models.contains_key(&key) {
+            return;
+        }
+        self.bytes += json.len();
+        self.order.push_back(key.clone());
+        self.models.insert(key, json);
+        while self.bytes > self.max_bytes {
+            match self.order.pop_front() {
+                Some(oldest) => {
+                    if let Some(json) = self.models.remove(&oldest) {
+                        self.bytes -= json.len();
+                    }
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Forgets the models of the `kind` cache, e.g. after it is cleared.
+    fn forget(&mut self, kind: &str) {
+        self.order.retain(|(k, _)| k != kind);
+        self.models.retain(|(k, _), _| k != kind);
+        self.bytes = self.models.values().map(|json| json.len()).sum();
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&(String, Vec<u8>), &String)> {
+        self.models.iter()
+    }
+}
+
+/// A module server process started by the orchestrator, with what is needed to start it again.
+#[derive(Debug, Clone)]
+struct LocalServerProcess {
+    child: Arc<Mutex<Child>>,
+    command_path: PathBuf,
+    stderr_path: Option<PathBuf>,
+}
+
+impl LocalServerLike for LocalServerProcess {
+    fn get_process(&self) -> Arc<Mutex<Child>> {
+        self.child.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExternalServerModule {
+    name: String,
+    url: Arc<RwLock<Url>>,
+    client: Arc<reqwest::blocking::Client>,
+    process: Option<LocalServerProcess>,
+    configuration: ModuleClientConfiguration,
+    protocol: ProtocolInfo,
+    /// The latest models sent to the module, by cache and hash, so that the cache
+    /// of the module can be populated again after a restart.
+    uploaded: Arc<Mutex<UploadedModels>>,
+    last_health_check: Arc<Mutex<Instant>>,
+}
+
+impl ExternalServerModule {
+    pub fn try_create_local(command_path_: PathBuf) -> Option<ExternalServerModule> {
+        ExternalServerModule::try_create_local_with_config(
+            command_path_,
+            None,
+            ModuleClientConfiguration::default(),
+        )
+    }
+
+    /// Same as [ExternalServerModule::try_create_local], but the standard error of the module
+    /// is appended to a `<module file name>.stderr.log` file inside `logs_path`, if given,
+    /// and the module is reached with the given client configuration.
+    ///
+    /// Modules that speak an incompatible protocol are stopped and `None` is returned.
+    pub fn try_create_local_with_config(
+        command_path_: PathBuf,
+        logs_path: Option<&Path>,
+        configuration: ModuleClientConfiguration,
+    ) -> Option<ExternalServerModule> {
+        let stderr_path = logs_path.and_then(|p| {
+            command_path_
+                .file_name()
+                .and_then(|x| x.to_str())
+                .map(|x| p.join(format!("{}.stderr.log", x)))
+        });
+        let (mut server_child, port, announced_name) =
+            spawn_local_server(&command_path_, stderr_path.as_deref())?;
+        let name = announced_name.unwrap_or(
+            command_path_
+                .file_name()
+                .and_then(|x| x.to_str())
+                .and_then(|x| x.split('.').next())
+                .expect("Could not fetch name from imodule file name.")
+                .to_string(),
+        );
+        let url = Url::parse(&format!("http://127.0.0.1:{}", port))
+            .expect("Failed to build imodule url. Should always succeed.");
+        let client = configuration.build_client();
+        let protocol = match negotiate_protocol(&client, &configuration, &url) {
+            Ok(protocol) => protocol,
+            Err(e) => {
+                warn!(
+                    "Module {} ({}) cannot be used by this orchestrator: {}. Ignoring it.",
+                    name,
+                    command_path_.display(),
+                    e
+                );
+                if let Err(e) = server_child.kill() {
+                    debug!("Ignoring error whilst killing imodule {}: {}", name, e);
+                }
+                return None;
+            }
+        };
+        let child = Arc::new(Mutex::new(server_child));
+        idesyde_core::register_child_process(&child);
+        Some(ExternalServerModule {
+            configuration: adapt_to_protocol(&name, &protocol, configuration),
+            name,
+            url: Arc::new(RwLock::new(url)),
+            client: Arc::new(client),
+            process: Some(LocalServerProcess {
+                child,
+                command_path: command_path_,
+                stderr_path,
+            }),
+            protocol,
+            uploaded: Arc::new(Mutex::new(UploadedModels::default())),
+            last_health_check: Arc::new(Mutex::new(Instant::now())),
+        })
+    }
+
+    /// Connects to the module at `url` with the default client configuration, as in
+    /// [ExternalServerModule::from_with_config].
+    #[deprecated(
+        note = "use ExternalServerModule::from_with_config, which takes the client configuration"
+    )]
+    pub fn from(url: &Url, default_name: &str) -> Result<ExternalServerModule, String> {
+        ExternalServerModule::from_with_config(
+            url,
+            default_name,
+            ModuleClientConfiguration::default(),
+        )
+    }
+
+    /// Connects to the module at `url` with the given client configuration, after checking
+    /// that it speaks a compatible protocol. The error explains why the module cannot be used.
+    ///
+    /// Only modules that do not know `/info/protocol` at all are assumed to speak [ProtocolInfo::legacy].
+    pub fn from_with_config(
+        url: &Url,
+        default_name: &str,
+        configuration: ModuleClientConfiguration,
+    ) -> Result<ExternalServerModule, String> {
+        let client = configuration.build_client();
+        let name = url
+            .join("/info/unique_identifier")
+            .ok()
+            .and_then(|u| {
+                send_with_retries(&configuration, "Querying the module identifier", || {
+                    client.get(u.clone())
+                })
+            })
+            .filter(|res| res.status().is_success())
+            .and_then(|res| res.text().ok())
+            .unwrap_or(default_name.to_string());
+        let protocol = negotiate_protocol(&client, &configuration, url)?;
+        Ok(ExternalServerModule {
+            configuration: adapt_to_protocol(&name, &protocol, configuration),
+            name,
+            url: Arc::new(RwLock::new(url.to_owned())),
+            client: Arc::new(client),
+            process: None,
+            protocol,
+            uploaded: Arc::new(Mutex::new(UploadedModels::default())),
+            last_health_check: Arc::new(Mutex::new(Instant::now())),
+        })
+    }
+
+    /// The protocol agreed with the module.
+    pub fn protocol(&self) -> &ProtocolInfo {
+        &self.protocol
+    }
+
+    /// The current address of the module, which changes if the module is restarted.
+    pub fn url(&self) -> Url {
+        self.url
+            .read()
+            .map(|u| u.to_owned())
+            .expect("Failed to read the module url. Should never fail.")
+    }
+
+    /// Checks that the module responds to `/info/unique_identifier`.
+    fn is_responsive(&self) -> bool {
+        self.url()
+            .join("/info/unique_identifier")
+            .ok()
+            .and_then(|u| self.client.get(u).timeout(HEALTH_CHECK_TIMEOUT).send().ok())
+            .map(|r| r.status().is_success())
+            .unwrap_or(false)
+    }
+
+    /// Returns the hashes that are not in the `kind` cache of the module.
+    ///
+    /// The hashes are queried in batches through `cache/missing`, or one by one through
+    /// `cache/exists` if the module does not have the batched endpoint.
+    fn missing_in_cache(&self, kind: &str, hashes: &[Vec<u8>]) -> Vec<Vec<u8>> {
+        if !self.protocol.supports_feature(FEATURE_BATCHED_CACHE) {
+            return self.missing_in_cache_one_by_one(kind, hashes);
+        }
+        let mut missing = Vec::new();
+        for (i, batch) in hashes
+            .chunks(self.configuration.batch_size.max(1))
+            .enumerate()
+        {
+            let response = self
+                .url()
+                .join(&format!("/{}/cache/missing", kind))
+                .ok()
+                .and_then(|u| {
+                    send_with_retries(
+                        &self.configuration,
+                        &format!(
+                            "Querying the {} cache of {}",
+                            kind,
+                            self.unique_identifier()
+                        ),
+                        || self.client.post(u.clone()).body(encode_hashes(batch)),
+                    )
+                });
+            match response {
+                Some(r) if r.status() == reqwest::StatusCode::NOT_FOUND => {
+                    let rest = &hashes[i * self.configuration.batch_size.max(1)..];
+                    missing.extend(self.missing_in_cache_one_by_one(kind, rest));
+                    break;
+                }
+                Some(r) => {
+                    let missing_in_batch = r
+                        .text()
+                        .ok()
+                        .and_then(|t| serde_json::from_str::<Vec<String>>(&t).ok())
+                        .map(|v| {
+                            v.iter()
+                                .filter_map(|h| general_purpose::STANDARD_NO_PAD.decode(h).ok())
+                                .collect::<Vec<Vec<u8>>>()
+                        })
+                        .unwrap_or(batch.to_vec());
+                    missing.extend(missing_in_batch);
+                }
+                None => missing.extend(batch.iter().cloned()),
+            }
+        }
+        missing
+    }
+
+    fn missing_in_cache_one_by_one(&self, kind: &str, hashes: &[Vec<u8>]) -> Vec<Vec<u8>> {
+        hashes
+            .par_iter()
+            .filter(|hash| {
+                self.url()
+                    .join(&format!("/{}/cache/exists", kind))
+                    .ok()
+                    .and_then(|u| {
+                        send_with_retries(
+                            &self.configuration,
+                            &format!(
+                                "Querying the {} cache of {}",
+                                kind,
+                                self.unique_identifier()
+                            ),
+                            || self.client.get(u.clone()).body(hash.to_vec()),
+                        )
+                    })
+                    .and_then(|r| r.text().ok())
+                    .map(|x| x.eq_ignore_ascii_case("false"))
+                    .unwrap_or(true)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Empties the `kind` cache of the module.
+    fn clear_cache(&self, kind: &str) {
+        if let Ok(mut uploaded) = self.uploaded.lock() {
+            uploaded.forget(kind);
+        }
+        if let Ok(u) = self.url().join(&format!("/{}/cache/clear", kind)) {
+            send_with_retries(
+                &self.configuration,
+                &format!(
+                    "Clearing the {} cache of {}",
+                    kind,
+                    self.unique_identifier()
+                ),
+                || self.client.post(u.clone()),
+            );
+        }
+    }
+
+    /// Adds the models to the `kind` cache of the module and remembers them in case the module is restarted.
+    ///
+    /// The models are sent in batches through `cache/add_all`, or one by one through
+    /// `cache/add` if the module does not have the batched endpoint.
+    fn add_to_cache(&self, kind: &str, payloads: Vec<CachePayload>) {
+        if let Ok(mut uploaded) = self.uploaded.lock() {
+            for payload in &payloads {
+                uploaded.insert(kind, payload.hash.to_owned(), payload.json.to_owned());
+            }
+        }
+        if !self.protocol.supports_feature(FEATURE_BATCHED_CACHE) {
+            self.add_to_cache_one_by_one(kind, &payloads);
+            return;
+        }
+        let field = cache_field(kind);
+        let description = format!(
+            "Adding models to the {} cache of {}",
+            kind,
+            self.unique_identifier()
+        );
+        for (i, batch) in payloads
+            .chunks(self.configuration.batch_size.max(1))
+            .enumerate()
+        {
+            let response = self
+                .url()
+                .join(&format!("/{}/cache/add_all", kind))
+                .ok()
+                .and_then(|u| {
+                    send_with_retries(&self.configuration, &description, || {
+                        let mut form = Form::new();
+                        for payload in batch {
+                            let part = match &payload.cbor {
+                                Some(cbor) => Part::bytes(cbor.to_owned())
+                                    .mime_str("application/cbor")
+                                    .expect("Failed to set the CBOR mime type. Should never fail."),
+                                None => Part::text(payload.json.to_owned()),
+                            };
+                            form = form.part(field, part);
+                        }
+                        self.client.post(u.clone()).multipart(form)
+                    })
+                });
+            if let Some(r) = response {
+                if r.status() == reqwest::StatusCode::NOT_FOUND {
+                    let rest = &payloads[i * self.configuration.batch_size.max(1)..];
+                    self.add_to_cache_one_by_one(kind, rest);
+                    break;
+                } else if !r.status().is_success() {
+                    warn!(
+                        "Module {} refused {} model(s) for its {} cache. Trying to proceed anyway.",
+                        self.unique_identifier(),
+                        batch.len(),
+                        kind
+                    );
+                }
+            }
+        }
+    }
+
+    fn add_to_cache_one_by_one(&self, kind: &str, payloads: &[CachePayload]) {
+        let field = cache_field(kind);
+        let description = format!(
+            "Adding a model to the {} cache of {}",
+            kind,
+            self.unique_identifier()
+        );
+        payloads.par_iter().for_each(|payload| {
+            if let Ok(add_url) = self.url().join(&format!("/{}/cache/add", kind)) {
+                send_with_retries(&self.configuration, &description, || {
+                    let form = Form::new().text(field, payload.json.to_owned());
+                    self.client.post(add_url.clone()).multipart(form)
+                });
+            }
+        });
+    }
+
+    /// Fetches the models with the given hashes from the `kind` cache of the module.
+    ///
+    /// The models are fetched in batches through `cache/fetch_all`, or one by one through
+    /// `cache/fetch` if the module does not have the batched endpoint.
+    fn fetch_from_cache<T, F>(&self, kind: &str, hashes: &[Vec<u8>], parse: F) -> Vec<T>
+    where
+        T: DeserializeOwned + Send,
+        F: Fn(&str) -> Option<T> + Sync,
+    {
+        let description = format!(
+            "Fetching models from the {} cache of {}",
+            kind,
+            self.unique_identifier()
+        );
+        if !self.protocol.supports_feature(FEATURE_BATCHED_CACHE) {
+            return self.fetch_from_cache_one_by_one(kind, hashes, &parse);
+        }
+        let mut fetched = Vec::new();
+        for (i, batch) in hashes
+            .chunks(self.configuration.batch_size.max(1))
+            .enumerate()
+        {
+            let response = self
+                .url()
+                .join(&format!("/{}/cache/fetch_all", kind))
+                .ok()
+                .and_then(|u| {
+                    send_with_retries(&self.configuration, &description, || {
+                        self.client.post(u.clone()).body(encode_hashes(batch))
+                    })
+                });
+            match response {
+                Some(r) if r.status() == reqwest::StatusCode::NOT_FOUND => {
+                    let rest = &hashes[i * self.configuration.batch_size.max(1)..];
+                    let one_by_one = self.fetch_from_cache_one_by_one(kind, rest, &parse);
+                    fetched.extend(one_by_one);
+                    break;
+                }
+                Some(r) => {
+                    if let Some(models) = r
+                        .text()
+                        .ok()
+                        .and_then(|t| serde_json::from_str::<Vec<T>>(&t).ok())
+                    {
+                        fetched.extend(models);
+                    }
+                }
+                None => {}
+            }
+        }
+        fetched
+    }
+
+    fn fetch_from_cache_one_by_one<T, F>(&self, kind: &str, hashes: &[Vec<u8>], parse: &F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(&str) -> Option<T> + Sync,
+    {
+        let description = format!(
+            "Fetching a model from the {} cache of {}",
+            kind,
+            self.unique_identifier()
+        );
+        hashes
+            .par_iter()
+            .filter_map(|hash| {
+                self.url()
+                    .join(&format!("/{}/cache/fetch", kind))
+                    .ok()
+                    .and_then(|u| {
+                        send_with_retries(&self.configuration, &description, || {
+                            self.client.get(u.clone()).body(hash.to_owned())
+                        })
+                    })
+                    .and_then(|r| r.text().ok())
+                    .and_then(|txt| parse(txt.as_str()))
+            })
+            .collect()
+    }
+
+    fn to_payload<S: serde::Serialize>(&self, hash: Vec<u8>, model: &S) -> Option<CachePayload> {
+        let json = serde_json::to_string(model).ok()?;
+        let cbor = if self.configuration.use_cbor {
+            let mut buf: Vec<u8> = Vec::new();
+            ciborium::into_writer(model, &mut buf).ok().map(|_| buf)
+        } else {
+            None
+        };
+        Some(CachePayload { hash, json, cbor })
+    }
+
+    /// Makes sure that the `kind` cache of the module has all the design models.
+    fn sync_design_models(&self, kind: &str, design_models: &[Arc<dyn DesignModel>]) {
+        let hashes: Vec<Vec<u8>> = design_models.iter().map(|m| m.global_sha2_hash()).collect();
+        let missing: HashSet<Vec<u8>> = self.missing_in_cache(kind, &hashes).into_iter().collect();
+        let payloads: Vec<CachePayload> = design_models
+            .par_iter()
+            .zip(hashes.into_par_iter())
+            .filter(|(_, hash)| missing.contains(hash))
+            .filter_map(|(m, hash)| self.to_payload(hash, &OpaqueDesignModel::from(m.as_ref())))
+            .collect();
+        if !payloads.is_empty() {
+            self.add_to_cache(kind, payloads);
+        }
+    }
+
+    /// Makes sure that the `kind` cache of the module has all the decision models.
+    pub(crate) fn sync_decision_models(
+        &self,
+        kind: &str,
+        decision_models: &[Arc<dyn DecisionModel>],
+    ) {
+        let hashes: Vec<Vec<u8>> = decision_models
+            .iter()
+            .map(|m| m.global_sha2_hash())
+            .collect();
+        let missing: HashSet<Vec<u8>> = self.missing_in_cache(kind, &hashes).into_iter().collect();
+        let payloads: Vec<CachePayload> = decision_models
+            .par_iter()
+            .zip(hashes.into_par_iter())
+            .filter(|(_, hash)| missing.contains(hash))
+            .filter_map(|(m, hash)| self.to_payload(hash, &OpaqueDecisionModel::from(m.as_ref())))
+            .collect();
+        if !payloads.is_empty() {
+            self.add_to_cache(kind, payloads);
+        }
+    }
+
+    /// Makes sure that the module is still running and responding, restarting it if it was
+    /// started by the orchestrator and has died or hangs.
+    ///
+    /// The process is checked at every call, while the HTTP health check is done
+    /// at most once every [HEALTH_CHECK_INTERVAL]. Returns whether the module is usable.
+    pub fn ensure_alive(&self) -> bool {
+        if let Some(local) = &self.process {
+            if let Some(status) = local.exit_status() {
+                warn!(
+                    "Module {} died with {}. Restarting it.",
+                    self.unique_identifier(),
+                    status
+                );
+                self.warn_stderr_tail(local);
+                return self.restart(local);
+            }
+        }
+        if let Ok(mut last_check) = self.last_health_check.lock() {
+            if last_check.elapsed() < HEALTH_CHECK_INTERVAL {
+                return true;
+            }
+            *last_check = Instant::now();
+        }
+        if self.is_responsive() {
+            return true;
+        }
+        match &self.process {
+            Some(local) => {
+                warn!(
+                    "Module {} stopped responding. Restarting it.",
+                    self.unique_identifier()
+                );
+                self.warn_stderr_tail(local);
+                self.restart(local)
+            }
+            None => {
+                warn!(
+                    "Module {} at {} is not responding. Its results are likely to be missing.",
+                    self.unique_identifier(),
+                    self.url()
+                );
+                false
+            }
+        }
+    }
+
+    fn warn_stderr_tail(&self, local: &LocalServerProcess) {
+        if let Some(stderr_path) = &local.stderr_path {
+            let tail = log_tail(stderr_path, STDERR_TAIL_LINES);
+            if !tail.is_empty() {
+                warn!(
+                    "Last lines of the error output of {} (full log in {}):\n{}",
+                    self.unique_identifier(),
+                    stderr_path.display(),
+                    tail.join("\n")
+                );
+            }
+        }
+    }
+
+    /// Starts the module process again and sends it every model it had before.
+    fn restart(&self, local: &LocalServerProcess) -> bool {
+        if let Ok(mut child_guard) = local.child.lock() {
+            // another user of this module may have restarted it while we waited for the lock
+            if child_guard.try_wait().ok().flatten().is_none() && self.is_responsive() {
+                return true;
+            }
+            if let Err(e) = child_guard.kill() {
+                debug!(
+                    "Ignoring error whilst killing imodule {}: {}",
+                    self.unique_identifier(),
+                    e
+                );
+            }
+            match spawn_local_server(&local.command_path, local.stderr_path.as_deref()) {
+                Some((new_child, port, _)) => {
+                    *child_guard = new_child;
+                    if let Ok(mut url) = self.url.write() {
+                        *url = Url::parse(&format!("http://127.0.0.1:{}", port))
+                            .expect("Failed to build imodule url. Should always succeed.");
+                    }
+                    if let Ok(mut last_check) = self.last_health_check.lock() {
+                        *last_check = Instant::now();
+                    }
+                    self.repopulate_cache();
+                    info!("Module {} restarted.", self.unique_identifier());
+                    return true;
+                }
+                None => {
+                    warn!(
+                        "Failed to restart module {}. Trying to proceed without it.",
+                        self.unique_identifier()
+                    );
+                }
+            }
+        }
+        false
+    }
+
+    /// Sends all remembered models to the cache of a freshly started module.
+    fn repopulate_cache(&self) {
+        let mut by_kind: HashMap<String, Vec<CachePayload>> = HashMap::new();
+        if let Ok(uploaded) = self.uploaded.lock() {
+            for ((kind, hash), json) in uploaded.iter() {
+                // parsed back so that the models are sent again in the configured encoding
+                let payload = match kind.as_str() {
+                    "design" | "reversed" => OpaqueDesignModel::from_json_str(json)
+                        .ok()
+                        .and_then(|m| self.to_payload(hash.to_owned(), &m)),
+                    _ => OpaqueDecisionModel::from_json_str(json)
+                        .ok()
+                        .and_then(|m| self.to_payload(hash.to_owned(), &m)),
+                };
+                by_kind
+                    .entry(kind.to_owned())
+                    .or_default()
+                    .push(payload.unwrap_or(CachePayload {
+                        hash: hash.to_owned(),
+                        json: json.to_owned(),
+                        cbor: None,
+                    }));
+            }
+        }
+        for (kind, payloads) in by_kind {
+            debug!(
+                "Sending {} models back to the {} cache of {} after restarting",
+                payloads.len(),
+                kind,
+                self.unique_identifier()
+            );
+            self.add_to_cache(&kind, payloads);
+        }
+    }
+}
+
+impl PartialEq for ExternalServerModule {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.url() == other.url()
+        // && self.inputs_path == other.inputs_path
+        // && self.identified_path == other.identified_path
+        // && self.solved_path == other.solved_path
+        // && self.reverse_path == other.reverse_path
+        // && self.output_path_ == other.output_path_
+    }
+}
+
+impl Eq for ExternalServerModule {}
+
+impl Hash for ExternalServerModule {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+    }
+}
+
+impl Drop for ExternalServerModule {
+    fn drop(&mut self) {
+        if let Some(local_process) = self.process.take() {
+            // clones of this module share the process, so only the last one stops it
+            if Arc::strong_count(&local_process.child) > 1 {
+                return;
+            }
+            if local_process.kill().is_none() {
+                debug!(
+                    "Ignoring error whilst killing imodule {}",
+                    self.unique_identifier(),
+                );
+            }
+        }
+    }
+}
+
+/// Exposes the identification step of an [ExternalServerModule] as an identification rule,
+/// so that the external module takes part in the identification procedure like any other module.
+struct ExternalServerIdentificationRule {
+    module: ExternalServerModule,
+}
+
+impl IdentificationRuleLike for ExternalServerIdentificationRule {
+    fn identify(
+        &self,
+        design_models: &[Arc<dyn DesignModel>],
+        decision_models: &[Arc<dyn DecisionModel>],
+    ) -> IdentificationResult {
+        self.module
+            .identification_step(&decision_models.to_vec(), &design_models.to_vec())
+    }
+}
+
+/// Exposes the reverse identification of an [ExternalServerModule] as a reverse identification rule.
+struct ExternalServerReverseIdentificationRule {
+    module: ExternalServerModule,
+}
+
+impl ReverseIdentificationRuleLike for ExternalServerReverseIdentificationRule {
+    fn reverse_identify(
+        &self,
+        decision_models: &[Arc<dyn DecisionModel>],
+        design_models: &[Arc<dyn DesignModel>],
+    ) -> ReverseIdentificationResult {
+        (
+            self.module
+                .reverse_identification(&decision_models.to_vec(), &design_models.to_vec()),
+            vec![],
+        )
+    }
+}
+
+impl Module for ExternalServerModule {
+    fn unique_identifier(&self) -> String {
+        self.name.clone()
+    }
+
+    fn location_url(&self) -> Option<Url> {
+        Some(self.url())
+    }
+
+    fn identification_rules(&self) -> Vec<Arc<dyn IdentificationRuleLike>> {
+        vec![Arc::new(ExternalServerIdentificationRule {
+            module: self.clone(),
+        })]
+    }
+
+    fn reverse_identification_rules(&self) -> Vec<Arc<dyn ReverseIdentificationRuleLike>> {
+        vec![Arc::new(ExternalServerReverseIdentificationRule {
+            module: self.clone(),
+        })]
+    }
+
+    fn identification_step(
+        &self,
+        decision_models: &Vec<Arc<dyn DecisionModel>>,
+        design_models: &Vec<Arc<dyn DesignModel>>,
+    ) -> IdentificationResult {
+        if !self.ensure_alive() {
+            return (
+                vec![],
+                vec![format!(
+                    "Module {} is not available for identification",
+                    self.unique_identifier()
+                )],
+            );
+        }
+        // let mut mut_url = self.url.clone();
+        // mut_url
+        //     .set_scheme("ws")
+        //     .expect("Failed to set scheme to 'ws'.");
+        // if let Ok(identify_url) = mut_url.join("/identify") {
+        //     if let Some((ws, _)) = mut_url
+        //         .socket_addrs(|| None)
+        //         .ok()
+        //         .and_then(|addrs| addrs.first().cloned())
+        //         .and_then(|addr| std::net::TcpStream::connect(addr).ok())
+        //         .and_then(|stream| tungstenite::client(identify_url, stream).ok())
+        //     {
+        //         return Box::new(ExternalServerIdentifiticationIterator::new(
+        //             initial_design_models,
+        //             initial_decision_models,
+        //             ws,
+        //         ));
+        //     }
+        // }
+        // Box::new(idesyde_core::empty_identification_iter())
+        self.sync_design_models("design", design_models);
+        self.sync_decision_models("decision", decision_models);
+        if let Some(identified_message) = self
+            .url()
+            .join("/identify")
+            .ok()
+            .and_then(|identify| {
+                send_with_retries(
+                    &self.configuration,
+                    &format!("Identification step of {}", self.unique_identifier()),
+                    || self.client.post(identify.clone()),
+                )
+            })
+            .and_then(|res| res.text().ok())
+            .and_then(|txt| {
+                // debug!("Received identification result: {}", txt.as_str());
+                serde_json::from_str::<IdentificationResultCompactMessage>(txt.as_str()).ok()
+            })
+        {
+            let identified_hashes = identified_message
+                .identified
+                .iter()
+                .map(|s| general_purpose::STANDARD_NO_PAD.decode(s).ok())
+                .flatten()
+                .collect::<Vec<Vec<u8>>>();
+            let identified_models = self
+                .fetch_from_cache("decision", &identified_hashes, |txt| {
+                    OpaqueDecisionModel::from_json_str(txt).ok()
+                })
+                .into_iter()
+                .map(|x: OpaqueDecisionModel| Arc::new(x) as Arc<dyn DecisionModel>)
+                .collect();
+            return (
+                identified_models,
+                identified_message.messages.into_iter().collect(),
+            );
+        }
+        (vec![], vec![])
+    }
+
+    fn reverse_identification(
+        &self,
+        solved_decision_models: &Vec<Arc<dyn DecisionModel>>,
+        design_models: &Vec<Arc<dyn DesignModel>>,
+    ) -> Vec<Arc<dyn DesignModel>> {
+        if !self.ensure_alive() {
+            return vec![];
+        }
+        // let mut mut_url = self.url.clone();
+        // mut_url
+        //     .set_scheme("ws")
+        //     .expect("Failed to set scheme to 'ws'.");
+        if let Ok(reverse_url) = self.url().join("/reverse") {
+            self.sync_design_models("design", design_models);
+            // the module reverses all the solved decision models it has, so only the given ones are kept
+            self.clear_cache("solved");
+            self.sync_decision_models("solved", solved_decision_models);
+            // let mut form = Form::new();
+            // for m in opaques {
+            //     if let Ok(bodyj) = m.to_json() {
+            //         form = form.text(format!("design{}", m.category()), bodyj);
+            //     }
+            // }
+            // for m in solved {
+            //     if let Ok(bodyj) = m.to_json() {
+            //         // let part = Part::text(bodyj);
+            //         form = form.text(format!("solved{}", m.category()), bodyj);
+            //     }
+            // }
+            let reversed_hash_str: Vec<String> = send_with_retries(
+                &self.configuration,
+                &format!("Reverse identification of {}", self.unique_identifier()),
+                || self.client.post(reverse_url.clone()),
+            )
+            .and_then(|res| res.text().ok())
+            .and_then(|txt| serde_json::from_str::<Vec<String>>(txt.as_str()).ok())
+            .unwrap_or(vec![]);
+            let reversed_hashes = reversed_hash_str
+                .iter()
+                .map(|s| general_purpose::STANDARD_NO_PAD.decode(s).ok())
+                .flatten()
+                .collect::<Vec<Vec<u8>>>();
+            let reversed_models = self
+                .fetch_from_cache("reversed", &reversed_hashes, |txt| {
+                    OpaqueDesignModel::from_json_str(txt).ok()
+                })
+                .into_iter()
+                .map(|x: OpaqueDesignModel| Arc::new(x) as Arc<dyn DesignModel>)
+                .collect();
+            return reversed_models;
+            // if let Some((mut ws, _)) = mut_url
+            //     .socket_addrs(|| None)
+            //     .ok()
+            //     .and_then(|addrs| addrs.first().cloned())
+            //     .and_then(|addr| {
+            //         std::net::TcpStream::connect_timeout(&addr, Duration::from_millis(200)).ok()
+            //     })
+            //     .and_then(|stream| tungstenite::client(reverse_url, stream).ok())
+            // {
+            //     // send solved decision models
+            //     for m in solved_decision_models {
+            //         if let Ok(decision_json) = OpaqueDecisionModel::from(m).to_json() {
+            //             if let Err(e) = ws.send(tungstenite::Message::text(decision_json)) {
+            //                 debug!("Decision JSON upload error {}", e.to_string());
+            //             }
+            //         }
+            //     }
+            //     // same for design models
+            //     for m in design_models {
+            //         if let Ok(design_json) = OpaqueDesignModel::from(m.as_ref()).to_json() {
+            //             if let Err(e) = ws.send(tungstenite::Message::text(design_json)) {
+            //                 debug!("Design JSON upload error {}", e.to_string());
+            //             };
+            //         };
+            //     }
+            //     if let Err(e) = ws.send(tungstenite::Message::text("done")) {
+            //         debug!("Failed to send 'done': {}", e.to_string());
+            //     };
+            //     let (identified_tx, identified_rx) =
+            //         std::sync::mpsc::channel::<Arc<dyn DesignModel>>();
+            //     // the way in which the things are being reversed is currently a bit hacky.
+            //     // in the future it is best if the reciever does NOT depend on any time-outs.
+            //     // Currently it seems like the websocket connetions are a bit janky, so we do this workaround.
+            //     ws.flush().expect("Failed to flush info for reversing");
+            //     let imodule_name = self.unique_identifier().to_owned();
+            //     std::thread::spawn(move || {
+            //         while let Ok(message) = ws.read() {
+            //             // besides the answer, also read the module's messages
+            //             match message {
+            //                 tungstenite::Message::Text(txt_msg) => {
+            //                     if txt_msg.eq_ignore_ascii_case("done") {
+            //                         debug!("Reverse done for {}", imodule_name);
+            //                         break;
+            //                     } else if let Ok(opaque) =
+            //                         OpaqueDesignModel::from_json_str(txt_msg.as_str())
+            //                     {
+            //                         let opaquea = Arc::new(opaque) as Arc<dyn DesignModel>;
+            //                         if let Err(e) = identified_tx.send(opaquea) {
+            //                             debug!(
+            //                                 "Failed to recover an identified design model with: {}",
+            //                                 e.to_string()
+            //                             );
+            //                         };
+            //                     }
+            //                 }
+            //                 tungstenite::Message::Binary(decision_cbor) => {
+            //                     if let Ok(opaque) =
+            //                         OpaqueDesignModel::from_cbor(decision_cbor.as_slice())
+            //                     {
+            //                         let opaquea = Arc::new(opaque) as Arc<dyn DesignModel>;
+            //                         if let Err(e) = identified_tx.send(opaquea) {
+            //                             debug!(
+            //                                 "Failed to recover an identified design model with: {}",
+            //                                 e.to_string()
+            //                             );
+            //                         };
+            //                     }
+            //                 }
+            //                 tungstenite::Message::Ping(_) => {
+            //                     if let Err(_) = ws.send(tungstenite::Message::Pong(vec![])) {
+            //                         debug!(
+            //                             "Failed to send ping message to other end. Trying to proceed anyway."
+            //                         );
+            //                     };
+            //                 }
+            //                 tungstenite::Message::Pong(_) => {
+            //                     if let Err(_) = ws.send(tungstenite::Message::Ping(vec![])) {
+            //                         debug!(
+            //                             "Failed to send pong message to other end. Trying to proceed anyway."
+            //                         );
+            //                     };
+            //                 }
+            //                 _ => break,
+            //             }
+            //         }
+            //     });
+            //     let mut reverse_identified = Vec::new();
+            //     // println!("Reverse done for {}", self.unique_identifier());
+            //     while let Ok(m) = identified_rx.recv_timeout(Duration::from_millis(
+            //         250 * ((solved_decision_models.len() + design_models.len()) as u64),
+            //     )) {
+            //         reverse_identified.push(m);
+            //     }
+            //     // get all last without blocking
+            //     identified_rx
+            //         .try_iter()
+            //         .for_each(|m| reverse_identified.push(m));
+            //     return reverse_identified;
+            // }
+        }
+        vec![]
+    }
+
+    fn explorers(&self) -> Vec<Arc<dyn idesyde_core::Explorer>> {
+        if !self.ensure_alive() {
+            return Vec::new();
+        }
+        if let Ok(explorers_url) = self.url().join("/explorers") {
+            match send_with_retries(
+                &self.configuration,
+                &format!("Querying the explorers of {}", self.unique_identifier()),
+                || self.client.get(explorers_url.clone()),
+            ) {
+                Some(result) => match result.text() {
+                    Ok(text) => match serde_json::from_str::<Vec<String>>(&text) {
+                        Ok(names) => {
+                            return names
+                                .iter()
+                                .map(|name| {
+                                    Arc::new(
+                                        ExternalExplorerBuilder::default()
+                                            .name(name.to_owned())
+                                            .url(self.url())
+                                            .module(Some(self.clone()))
+                                            .configuration(self.configuration.to_owned())
+                                            .client(self.client.to_owned())
+                                            .build()
+                                            .expect("Failed to build an external explorer. Should never fail."),
+                                    )
+                                })
+                                .map(|x| x as Arc<dyn Explorer>)
+                                .collect();
+                        }
+                        Err(_) => {
+                            warn!(
+                                "Explorer {} failed deserialize its decision model. Trying to proceed anyway.",
+                                self.unique_identifier()
+                            );
+                            debug!(
+                                "Explorer {} failed to deserialize explorer names from {}",
+                                self.unique_identifier(),
+                                &text
+                            );
+                        }
+                    },
+                    Err(err) => {
+                        warn!(
+                            "Explorer {} failed to process request. Trying to proceed anyway.",
+                            self.unique_identifier()
+                        );
+                        debug!(
+                            "Explorer {} failed to transform into text with: {}",
+                            self.unique_identifier(),
+                            err.to_string()
+                        );
+                    }
+                },
+                None => {
+                    warn!(
+                        "Explorer {} failed to accept request. Trying to proceed anyway.",
+                        self.unique_identifier()
+                    );
+                }
+            }
+        }
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use std::sync::{Arc, Mutex};
+
+    use idesyde_blueprints::server::{ModuleServer, ModuleServerHandle};
+    use idesyde_core::{
+        DecisionModel, DesignModel, ExplorationBid, ExplorationBidBuilder,
+        ExplorationConfiguration, ExplorationSolution, Explorer, MarkedIdentificationRule, Module,
+        OpaqueDecisionModel, OpaqueDesignModel, ReverseIdentificationResult,
+        ReverseIdentificationRuleLike, RustEmbeddedModule,
+    };
+    use url::Url;
+
+    use super::{ExternalServerModule, UploadedModels};
+    use crate::manifest::{RunManifest, Stage};
+    use crate::orchestrator::OrchestratorBuilder;
+    use crate::ModuleClientConfiguration;
+
+    struct ReverseToDesign;
+
+    impl ReverseIdentificationRuleLike for ReverseToDesign {
+        fn reverse_identify(
+            &self,
+            decision_models: &[Arc<dyn DecisionModel>],
+            _design_models: &[Arc<dyn DesignModel>],
+        ) -> ReverseIdentificationResult {
+            let reversed = decision_models
+                .iter()
+                .map(|m| {
+                    Arc::new(OpaqueDesignModel {
+                        category: "Reversed".to_string(),
+                        elements: m.part(),
+                        format: "json".to_string(),
+                        body: Some("{}".to_string()),
+                    }) as Arc<dyn DesignModel>
+                })
+                .collect();
+            (reversed, vec![])
+        }
+    }
+
+    fn copying_module() -> RustEmbeddedModule {
+        RustEmbeddedModule::builder()
+            .unique_identifier("CopyingModule".to_string())
+            .identification_rules(vec![Arc::new(
+                MarkedIdentificationRule::DesignModelOnlyIdentificationRule(
+                    |design_models: &[Arc<dyn DesignModel>], _: &[Arc<dyn DecisionModel>]| {
+                        let identified = design_models
+                            .iter()
+                            .map(|m| {
+                                Arc::new(OpaqueDecisionModel {
+                                    category: "Copied".to_string(),
+                                    part: m.elements(),
+                                    body_json: Some("{}".to_string()),
+                                    body_msgpack: None,
+                                    body_cbor: None,
+                                }) as Arc<dyn DecisionModel>
+                            })
+                            .collect();
+                        (identified, vec![])
+                    },
+                ),
+            )])
+            .reverse_identification_rules(vec![Arc::new(ReverseToDesign)])
+            .build()
+            .expect("Failed to build the test module. Should never fail.")
+    }
+
+    /// Solves every decision model once, as is.
+    struct SolveAsIs;
+
+    impl Explorer for SolveAsIs {
+        fn unique_identifier(&self) -> String {
+            "SolveAsIs".to_string()
+        }
+
+        fn bid(&self, _m: Arc<dyn DecisionModel>) -> ExplorationBid {
+            ExplorationBidBuilder::default()
+                .can_explore(true)
+                .target_objectives(HashSet::from(["cost".to_string()]))
+                .build()
+                .expect("Failed to build the test bid. Should never fail.")
+        }
+
+        fn explore(
+            &self,
+            m: Arc<dyn DecisionModel>,
+            _currrent_solutions: &HashSet<ExplorationSolution>,
+            _exploration_configuration: ExplorationConfiguration,
+        ) -> Arc<Mutex<dyn Iterator<Item = ExplorationSolution> + Send + Sync>> {
+            let solution = ExplorationSolution {
+                solved: m,
+                objectives: HashMap::from([("cost".to_string(), 1.0)]),
+            };
+            Arc::new(Mutex::new(std::iter::once(solution)))
+        }
+    }
+
+    fn design(elements: &[&str]) -> Arc<dyn DesignModel> {
+        Arc::new(OpaqueDesignModel {
+            category: "Input".to_string(),
+            elements: elements.iter().map(|e| e.to_string()).collect(),
+            format: "json".to_string(),
+            body: Some(format!("{:?}", elements)),
+        })
+    }
+
+    fn solved(elements: &[&str]) -> Arc<dyn DecisionModel> {
+        Arc::new(OpaqueDecisionModel {
+            category: "Copied".to_string(),
+            part: elements.iter().map(|e| e.to_string()).collect(),
+            body_json: Some("{}".to_string()),
+            body_msgpack: None,
+            body_cbor: None,
+        })
+    }
+
+    fn connect(server: &ModuleServerHandle) -> ExternalServerModule {
+        let url = Url::parse(&server.url().expect("The test module server has no URL."))
+            .expect("The test module server URL is invalid.");
+        ExternalServerModule::from_with_config(
+            &url,
+            "unnamed",
+            ModuleClientConfiguration::default(),
+        )
+        .expect("Failed to connect to the test module server.")
+    }
+
+    #[test]
+    fn uploaded_models_forget_the_oldest_beyond_the_limit() {
+        let mut uploaded = UploadedModels::with_max_bytes(10);
+        uploaded.insert("design", vec![0], "{\"a\":1}".to_string());
+        uploaded.insert("decision", vec![1], "{\"b\":1}".to_string());
+        let kept: Vec<&(String, Vec<u8>)> = uploaded.iter().map(|(k, _)| k).collect();
+        assert_eq!(kept, vec![&("decision".to_string(), vec![1])]);
+        uploaded.insert("solved", vec![2], "{}".to_string());
+        assert_eq!(uploaded.iter().count(), 2);
+        uploaded.forget("solved");
+        assert_eq!(uploaded.iter().count(), 1);
+        assert_eq!(uploaded.bytes, 7);
+    }
+
+    #[test]
+    fn client_against_in_process_server() {
+        let server = ModuleServer::bind(Arc::new(copying_module()), "127.0.0.1:0")
+            .expect("Failed to bind the test module server.")
+            .spawn();
+        let client = connect(&server);
+        assert_eq!(client.unique_identifier(), "CopyingModule");
+
+        let design = design(&["a", "b"]);
+        let (identified, _) =
+            client.identification_rules()[0].identify(std::slice::from_ref(&design), &[]);
+        assert_eq!(identified.len(), 1);
+        assert_eq!(identified[0].category(), "Copied");
+        assert_eq!(identified[0].part(), design.elements());
+
+        let (reversed, _) =
+            client.reverse_identification_rules()[0].reverse_identify(&identified, &[design]);
+        assert_eq!(reversed.len(), 1);
+        assert_eq!(reversed[0].category(), "Reversed");
+    }
+
+    #[test]
+    fn reverse_only_the_given_solutions() {
+        let server = ModuleServer::bind(Arc::new(copying_module()), "127.0.0.1:0")
+            .expect("Failed to bind the test module server.")
+            .spawn();
+        let client = connect(&server);
+        let rule = client.reverse_identification_rules()[0].clone();
+        let (first, _) = rule.reverse_identify(&[solved(&["a"])], &[]);
+        assert_eq!(first.len(), 1);
+        let (second, _) = rule.reverse_identify(&[solved(&["b"])], &[]);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].elements(), HashSet::from(["b".to_string()]));
+        let (both, _) = rule.reverse_identify(&[solved(&["a"]), solved(&["b"])], &[]);
+        assert_eq!(both.len(), 2);
+    }
+
+    #[test]
+    fn reversed_models_of_an_external_module_link_to_their_solutions() {
+        let server = ModuleServer::bind(Arc::new(copying_module()), "127.0.0.1:0")
+            .expect("Failed to bind the test module server.")
+            .spawn();
+        let explorers = RustEmbeddedModule::builder()
+            .unique_identifier("Explorers".to_string())
+            .explorers(vec![Arc::new(SolveAsIs) as Arc<dyn Explorer>])
+            .build()
+            .expect("Failed to build the test module. Should never fail.");
+        let run_path =
+            std::env::temp_dir().join(format!("idesyde-external-reverse-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&run_path);
+        let report = OrchestratorBuilder::default()
+            .modules(vec![
+                Arc::new(connect(&server)) as Arc<dyn Module>,
+                Arc::new(explorers) as Arc<dyn Module>,
+            ])
+            .design_models(vec![design(&["a"]), design(&["b"])])
+            .run_path(run_path.clone())
+            .build()
+            .expect("Failed to build the test orchestrator. Should never fail.")
+            .run();
+        assert_eq!(report.reversed, 2);
+        let manifest = RunManifest::load(&run_path).expect("The run has no manifest.");
+        let reversed = manifest.by_stage(Stage::ReverseIdentification);
+        assert_eq!(reversed.len(), 2);
+        // each reversed model comes from the explored model that solves its only element
+        let parents: HashSet<&String> = reversed
+            .iter()
+            .inspect(|artifact| assert_eq!(artifact.parents.len(), 1))
+            .flat_map(|artifact| artifact.parents.iter())
+            .collect();
+        assert_eq!(parents.len(), 2);
+        let _ = std::fs::remove_dir_all(&run_path);
+    }
+}
@@ -0,0 +1,89 @@
+//! Include and exclude filters for modules, explorers and decision models.
+//!
+//! Each filter is a pair of lists of glob patterns, as understood by [glob::Pattern],
+//! e.g. `*PartitionedMemoryMappable*` or `MiniZinc*`. A name is accepted if it matches
+//! at least one include pattern, or if there are no include patterns, and it matches
+//! no exclude pattern. Exclusion always wins over inclusion.
+use glob::Pattern;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NameFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl NameFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<NameFilter, String> {
+        let parse = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|p| Pattern::new(p).map_err(|e| format!("invalid pattern '{}': {}", p, e)))
+                .collect::<Result<Vec<Pattern>, String>>()
+        };
+        Ok(NameFilter {
+            include: parse(include)?,
+            exclude: parse(exclude)?,
+        })
+    }
+
+    pub fn accepts(&self, name: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| p.matches(name)))
+            && !self.exclude.iter().any(|p| p.matches(name))
+    }
+
+    /// Whether the filter accepts every name.
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+}
+
+/// The filters applied by the orchestrator: modules are filtered before identification,
+/// explorers before bidding, and decision models right after identification, so that
+/// the identified output, the dominance computation and the bidding all see the same models.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SelectionFilters {
+    pub modules: NameFilter,
+    pub explorers: NameFilter,
+    pub decision_models: NameFilter,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(include: &[&str], exclude: &[&str]) -> NameFilter {
+        let to_strings = |ps: &[&str]| ps.iter().map(|p| p.to_string()).collect::<Vec<String>>();
+        NameFilter::new(&to_strings(include), &to_strings(exclude))
+            .expect("Patterns of the tests are valid. Should never fail.")
+    }
+
+    #[test]
+    fn empty_filter_accepts_everything() {
+        let f = NameFilter::default();
+        assert!(f.is_empty());
+        assert!(f.accepts("MiniZincModule"));
+    }
+
+    #[test]
+    fn include_patterns_restrict_the_names() {
+        let f = filter(&["MiniZinc*", "*Jenetics*"], &[]);
+        assert!(!f.is_empty());
+        assert!(f.accepts("MiniZincModule"));
+        assert!(f.accepts("CanExploreWithJeneticsExplorer"));
+        assert!(!f.accepts("ChocoExplorer"));
+    }
+
+    #[test]
+    fn exclusion_wins_over_inclusion() {
+        let f = filter(&["*Explorer"], &["Choco*"]);
+        assert!(f.accepts("JeneticsExplorer"));
+        assert!(!f.accepts("ChocoExplorer"));
+        assert!(!filter(&[], &["*"]).accepts("AnyModule"));
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        let err = NameFilter::new(&["[".to_string()], &[]).unwrap_err();
+        assert!(err.contains("invalid pattern '['"));
+    }
+}
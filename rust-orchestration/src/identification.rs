@@ -1,15 +1,23 @@
-use std::{net::TcpStream, sync::Arc};
+use std::collections::HashMap;
+#[cfg(feature = "http-modules")]
+use std::net::TcpStream;
+use std::sync::Arc;
 
 use idesyde_core::{
-    merge_identification_results, DecisionModel, DesignModel, IdentificationResult,
-    IdentificationRuleLike, Module, OpaqueDecisionModel, OpaqueDesignModel,
+    merge_identification_results, DecisionModel, DesignModel, IdentificationRuleLike, Module,
+    OpaqueDecisionModel,
 };
+#[cfg(feature = "http-modules")]
+use idesyde_core::{IdentificationResult, OpaqueDesignModel};
 
-use log::debug;
+use crate::events::{self, ProgressEvent};
+use tracing::{debug, debug_span, info_span};
+#[cfg(feature = "http-modules")]
 use tungstenite::WebSocket;
 
 use rayon::prelude::*;
 
+#[cfg(feature = "http-modules")]
 pub struct ExternalServerIdentifiticationIterator {
     design_models: Vec<Arc<dyn DesignModel>>,
     decision_models: Vec<Arc<dyn DecisionModel>>,
@@ -19,6 +27,7 @@ pub struct ExternalServerIdentifiticationIterator {
     messages: Vec<String>,
 }
 
+#[cfg(feature = "http-modules")]
 impl ExternalServerIdentifiticationIterator {
     pub fn new(
         design_models: &Vec<Arc<dyn DesignModel>>,
@@ -36,6 +45,7 @@ impl ExternalServerIdentifiticationIterator {
     }
 }
 
+#[cfg(feature = "http-modules")]
 impl Iterator for ExternalServerIdentifiticationIterator {
     type Item = IdentificationResult;
 
@@ -127,6 +137,7 @@ impl Iterator for ExternalServerIdentifiticationIterator {
     }
 }
 
+#[cfg(feature = "http-modules")]
 impl Drop for ExternalServerIdentifiticationIterator {
     fn drop(&mut self) {
         if self.websocket.can_write() {
@@ -146,26 +157,80 @@ pub fn identification_procedure(
     pre_identified: &Vec<Arc<dyn DecisionModel>>,
     starting_iter: i32,
 ) -> (Vec<Arc<dyn DecisionModel>>, Vec<(String, String)>) {
+    let (identified, messages, _) = identification_procedure_with_origins(
+        imodules,
+        design_models,
+        pre_identified,
+        starting_iter,
+    );
+    (identified, messages)
+}
+
+/// The module that identified each decision model, by its category and [DecisionModel::global_sha2_hash].
+pub type IdentificationOrigins = HashMap<(String, Vec<u8>), String>;
+
+/// The identified decision models, the identification messages and the [IdentificationOrigins].
+pub type IdentificationWithOrigins = (
+    Vec<Arc<dyn DecisionModel>>,
+    Vec<(String, String)>,
+    IdentificationOrigins,
+);
+
+/// Same as [identification_procedure], also returning the module that identified each decision model.
+///
+/// The pre-identified decision models have no origin, unless a module identified a replacement for them.
+pub fn identification_procedure_with_origins(
+    imodules: &Vec<Arc<dyn Module>>,
+    design_models: &Vec<Arc<dyn DesignModel>>,
+    pre_identified: &Vec<Arc<dyn DecisionModel>>,
+    starting_iter: i32,
+) -> IdentificationWithOrigins {
     let mut step = starting_iter;
     let mut identified: Vec<Arc<dyn DecisionModel>> = pre_identified.clone();
     let mut messages: Vec<(String, String)> = Vec::new();
+    let mut origins: IdentificationOrigins = HashMap::new();
     let mut fix_point = false;
-    let irules: Vec<Arc<dyn IdentificationRuleLike>> = imodules
+    // rules are kept with their module and their position in it, so that their records can be told apart
+    let irules: Vec<(String, usize, Arc<dyn IdentificationRuleLike>)> = imodules
         .iter()
-        .flat_map(|imodule| imodule.identification_rules().into_iter())
+        .flat_map(|imodule| {
+            imodule
+                .identification_rules()
+                .into_iter()
+                .enumerate()
+                .map(|(i, irule)| (imodule.unique_identifier(), i, irule))
+        })
         .collect();
     debug!("Using {} identification rules", irules.len());
     while !fix_point {
         fix_point = true;
+        let step_span = info_span!("identification_step", step);
+        let _step_guard = step_span.enter();
+        // every model is kept with the module that identified it
         let (identified_models, msgs) = irules
             .par_iter()
-            .map(|irule| {
-                irule.identify(&design_models.as_slice(), identified.as_slice())
+            .map(|(module, rule, irule)| {
+                let (models, msgs) =
+                    debug_span!(parent: &step_span, "identification_rule", module, rule).in_scope(
+                        || irule.identify(&design_models.as_slice(), identified.as_slice()),
+                    );
+                (
+                    models
+                        .into_iter()
+                        .map(|m| (module.to_owned(), m))
+                        .collect::<Vec<_>>(),
+                    msgs,
+                )
+            })
+            .reduce_with(|(mut models1, msgs1), (models2, msgs2)| {
+                let (_, msgs) = merge_identification_results((vec![], msgs1), (vec![], msgs2));
+                models1.extend(models2);
+                (models1, msgs)
             })
-            .reduce_with(merge_identification_results)
             .unwrap_or((vec![], vec![]));
+        let mut changed = 0;
         // add completely new models or replace opaque deicion mdoels for non-opaque ones
-        for m in &identified_models {
+        for (module, m) in &identified_models {
             if let Some(previous_idx) = identified.iter().position(|x| {
                 (x.partial_cmp(m) == Some(std::cmp::Ordering::Less)
                     || x.partial_cmp(m) == Some(std::cmp::Ordering::Equal))
@@ -175,14 +240,18 @@ pub fn identification_procedure(
                 // debug!("Replaced {}", identified[previous_idx].category());
                 identified.remove(previous_idx);
                 identified.push(m.to_owned());
+                origins.insert((m.category(), m.global_sha2_hash()), module.to_owned());
                 fix_point = false;
+                changed += 1;
             } else if !identified.iter().any(|x| {
                 x.partial_cmp(m) == Some(std::cmp::Ordering::Greater)
                     || x.partial_cmp(m) == Some(std::cmp::Ordering::Equal)
             }) {
                 // debug!("added {}", m.category());
                 identified.push(m.to_owned());
+                origins.insert((m.category(), m.global_sha2_hash()), module.to_owned());
                 fix_point = false;
+                changed += 1;
             };
         }
         for msg in msgs {
@@ -190,13 +259,20 @@ pub fn identification_procedure(
             messages.push(("DEBUG".to_string(), msg.to_owned()));
         }
         debug!(
+            identified = identified.len(),
+            changed,
             "{} total decision models identified at step {}",
             identified.len(),
             step
         );
+        events::emit(ProgressEvent::IdentificationStep {
+            step,
+            identified: identified.len(),
+            changed,
+        });
         step += 1;
     }
-    (identified, messages)
+    (identified, messages, origins)
 }
 
 // pub fn get_sqlite_for_identification(url: &str) -> Result<rusqlite::Connection, rusqlite::Error> {
@@ -0,0 +1,184 @@
+//! Decision models given directly as inputs of the orchestration.
+//!
+//! Besides design models, the orchestrator accepts decision models produced by other tools or by a
+//! previous run, which are fed to the identification as pre-identified. A decision model input is
+//! recognised by its extension and content:
+//!
+//! - a JSON or CBOR file with a `category` and the body in `body_json`, `body_cbor` or `body_msgpack`,
+//!   as written by [OpaqueDecisionModel::to_json] and [OpaqueDecisionModel::to_cbor];
+//! - a JSON or CBOR file with a `category` and the body as a nested object in `body`;
+//! - a body file written by a previous run, `body_<prefix>_<category>_<suffix>.<json|cbor|msgpack>`,
+//!   whose category is taken from its name.
+//!
+//! The decision models of [idesyde_common] are read into their typed versions, so that their part
+//! is computed from their body. The others are kept opaque, with the part given in the file, if any.
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use idesyde_core::{DecisionModel, OpaqueDecisionModel};
+use serde::Deserialize;
+
+/// A decision model with its body as a nested object.
+#[derive(Debug, Clone, Deserialize)]
+struct DecisionModelInput {
+    category: String,
+    #[serde(default)]
+    part: HashSet<String>,
+    body: serde_json::Value,
+}
+
+impl DecisionModelInput {
+    fn into_opaque(self) -> OpaqueDecisionModel {
+        OpaqueDecisionModel {
+            category: self.category,
+            part: self.part,
+            body_json: Some(self.body.to_string()),
+            body_msgpack: None,
+            body_cbor: None,
+        }
+    }
+}
+
+fn has_body(m: &OpaqueDecisionModel) -> bool {
+    m.body_json.is_some() || m.body_cbor.is_some() || m.body_msgpack.is_some()
+}
+
+/// Reads a body file of a previous run, trying every part of its name as the category.
+/// Only categories of [idesyde_common] are accepted, as otherwise the part of the model is unknown.
+fn read_body_file(path: &Path, extension: &str, bytes: Vec<u8>) -> Option<Arc<dyn DecisionModel>> {
+    let stem = path.file_stem()?.to_str()?.strip_prefix("body_")?;
+    let mut opaque = OpaqueDecisionModel {
+        category: String::new(),
+        part: HashSet::new(),
+        body_json: None,
+        body_msgpack: None,
+        body_cbor: None,
+    };
+    match extension {
+        "json" => opaque.body_json = Some(String::from_utf8(bytes).ok()?),
+        "cbor" => opaque.body_cbor = Some(bytes),
+        "msgpack" => opaque.body_msgpack = Some(bytes),
+        _ => return None,
+    };
+    stem.split('_').find_map(|category| {
+        opaque.category = category.to_owned();
+        idesyde_common::decision_model_from_opaque(&opaque)
+    })
+}
+
+/// Reads `path` as a decision model, returning `None` if it is not one.
+pub fn read_decision_model(path: &Path) -> Option<Arc<dyn DecisionModel>> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    let bytes = std::fs::read(path).ok()?;
+    let opaque = match extension.as_str() {
+        "json" => std::str::from_utf8(&bytes)
+            .ok()
+            .and_then(|s| OpaqueDecisionModel::from_json_str(s).ok())
+            .filter(has_body)
+            .or_else(|| {
+                serde_json::from_slice::<DecisionModelInput>(&bytes)
+                    .ok()
+                    .map(DecisionModelInput::into_opaque)
+            }),
+        "cbor" => OpaqueDecisionModel::from_cbor(bytes.as_slice())
+            .ok()
+            .filter(has_body)
+            .or_else(|| {
+                ciborium::from_reader::<DecisionModelInput, &[u8]>(bytes.as_slice())
+                    .ok()
+                    .map(DecisionModelInput::into_opaque)
+            }),
+        _ => None,
+    };
+    match opaque {
+        Some(m) => Some(
+            idesyde_common::decision_model_from_opaque(&m)
+                .unwrap_or_else(|| Arc::new(m) as Arc<dyn DecisionModel>),
+        ),
+        None => read_body_file(path, &extension, bytes),
+    }
+}
+
+/// A decision model input and the path it was read from.
+pub type DecisionModelInputPath = (PathBuf, Arc<dyn DecisionModel>);
+
+/// Splits `inputs` into the decision models among them, with their paths, and the remaining paths,
+/// which are design models.
+pub fn read_decision_models(inputs: &[PathBuf]) -> (Vec<DecisionModelInputPath>, Vec<PathBuf>) {
+    let mut decision_models = vec![];
+    let mut design_model_paths = vec![];
+    for input in inputs {
+        match read_decision_model(input) {
+            Some(m) => decision_models.push((input.to_owned(), m)),
+            None => design_model_paths.push(input.to_owned()),
+        }
+    }
+    (decision_models, design_model_paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for the files of one test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("idesyde-inputs-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("Failed to create the test directory.");
+        dir
+    }
+
+    #[test]
+    fn reads_a_nested_body_as_opaque() {
+        let dir = test_dir("nested");
+        let path = dir.join("model.json");
+        std::fs::write(
+            &path,
+            r#"{"category": "SomeUnknownModel", "part": ["a", "b"], "body": {"x": 1}}"#,
+        )
+        .expect("Failed to write the test input.");
+        let m = read_decision_model(&path).expect("The input is a decision model.");
+        assert_eq!(m.category(), "SomeUnknownModel");
+        assert_eq!(m.part(), HashSet::from(["a".to_string(), "b".to_string()]));
+        assert_eq!(m.body_as_json(), Some(r#"{"x":1}"#.to_string()));
+    }
+
+    #[test]
+    fn reads_an_opaque_decision_model_file() {
+        let dir = test_dir("opaque");
+        let path = dir.join("opaque.json");
+        let opaque = OpaqueDecisionModel {
+            category: "SomeUnknownModel".to_string(),
+            part: HashSet::from(["a".to_string()]),
+            body_json: Some("{}".to_string()),
+            body_msgpack: None,
+            body_cbor: None,
+        };
+        std::fs::write(&path, opaque.to_json().expect("Should never fail."))
+            .expect("Failed to write the test input.");
+        let m = read_decision_model(&path).expect("The input is a decision model.");
+        assert_eq!(m.category(), opaque.category);
+        assert_eq!(m.part(), opaque.part);
+    }
+
+    #[test]
+    fn splits_design_models_from_decision_models() {
+        let dir = test_dir("split");
+        let decision = dir.join("decision.json");
+        let design = dir.join("design.json");
+        let other = dir.join("design.fiodl");
+        std::fs::write(&decision, r#"{"category": "M", "body": {}}"#)
+            .expect("Failed to write the test input.");
+        std::fs::write(&design, r#"{"nodes": []}"#).expect("Failed to write the test input.");
+        std::fs::write(&other, "systemgraph {}").expect("Failed to write the test input.");
+        let (decision_models, design_models) =
+            read_decision_models(&[decision.clone(), design.clone(), other.clone()]);
+        assert_eq!(decision_models.len(), 1);
+        assert_eq!(decision_models[0].0, decision);
+        assert_eq!(design_models, vec![design, other]);
+    }
+}
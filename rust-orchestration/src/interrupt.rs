@@ -0,0 +1,59 @@
+//! Graceful interruption of the orchestration with SIGINT (Ctrl-C) or SIGTERM.
+//!
+//! The first signal only marks the orchestration as interrupted: the exploration iterators
+//! check [is_interrupted] and stop, so that the orchestrator can still write the dominant
+//! solutions found so far, reverse identify them and stop its child processes.
+//! A second signal aborts immediately, killing the child processes registered with
+//! [idesyde_core::register_child_process].
+//!
+//! The exploration can also be cancelled without interrupting the orchestration, as the `--watch`
+//! mode does when the inputs change, so that it restarts with the new inputs. The iterators check
+//! [should_stop], which covers both.
+//!
+//! Like the progress events, the interruption state is process-wide.
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use tracing::warn;
+
+/// The exit code of an orchestration that was interrupted, following the shell convention for SIGINT.
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+static INTERRUPTIONS: AtomicUsize = AtomicUsize::new(0);
+
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// Installs the SIGINT and SIGTERM handler. Can only be called once per process.
+pub fn install_handler() -> Result<(), String> {
+    ctrlc::set_handler(|| {
+        if INTERRUPTIONS.fetch_add(1, Ordering::SeqCst) == 0 {
+            warn!("Interrupted. Stopping the exploration and writing the results found so far. Interrupt again to abort immediately.");
+        } else {
+            warn!("Interrupted again. Aborting.");
+            idesyde_core::terminate_child_processes();
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        }
+    })
+    .map_err(|e| e.to_string())
+}
+
+pub fn is_interrupted() -> bool {
+    INTERRUPTIONS.load(Ordering::SeqCst) > 0
+}
+
+/// Stops the ongoing exploration, keeping the orchestration running. Cleared by [reset_cancellation].
+pub fn cancel_exploration() {
+    CANCELLED.store(true, Ordering::SeqCst);
+}
+
+pub fn reset_cancellation() {
+    CANCELLED.store(false, Ordering::SeqCst);
+}
+
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
+
+/// Whether the exploration should stop, because the orchestration was interrupted or the exploration cancelled.
+pub fn should_stop() -> bool {
+    is_interrupted() || is_cancelled()
+}
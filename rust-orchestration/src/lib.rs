@@ -1,715 +1,350 @@
+pub mod benchmark;
+#[cfg(feature = "http-modules")]
+pub mod conformance;
+pub mod events;
+pub mod explain;
 pub mod exploration;
+#[cfg(feature = "http-modules")]
+mod external_server;
+pub mod filters;
 pub mod identification;
+pub mod inputs;
+pub mod interrupt;
+pub mod logging;
+pub mod manifest;
+pub mod module_paths;
+pub mod orchestrator;
+pub mod quarantine;
+pub mod report;
+pub mod trace;
+pub mod watch;
 
-use std::borrow::BorrowMut;
 use std::cmp::Ordering;
-use std::hash::Hash;
-
-use std::io::BufRead;
-use std::io::BufReader;
-use std::io::BufWriter;
-use std::io::Write;
-
-use std::ops::DerefMut;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::path::Path;
-
 use std::path::PathBuf;
-use std::process::Child;
-use std::process::ChildStdout;
-use std::process::Stdio;
 use std::sync::Arc;
-use std::sync::Mutex;
+use std::time::Duration;
 
-use exploration::ExternalExplorerBuilder;
-
-use idesyde_blueprints::IdentificationResultCompactMessage;
-use idesyde_bridge_java::java_modules_from_jar_paths;
+use derive_builder::Builder;
 use idesyde_core::DecisionModel;
-use idesyde_core::DesignModel;
-use idesyde_core::Explorer;
-use idesyde_core::IdentificationResult;
 use idesyde_core::Module;
-
-use idesyde_core::OpaqueDecisionModel;
-use idesyde_core::OpaqueDesignModel;
-use log::debug;
-use log::warn;
-use rayon::prelude::*;
-use reqwest::blocking::multipart::Form;
+use tracing::warn;
+#[cfg(feature = "http-modules")]
 use url::Url;
 
-use base64::{engine::general_purpose, Engine as _};
+#[cfg(feature = "http-modules")]
+pub(crate) use external_server::send_with_retries;
+#[cfg(feature = "http-modules")]
+pub use external_server::ExternalServerModule;
 
-trait LocalServerLike {
-    fn get_process(&self) -> Arc<Mutex<Child>>;
+/// How the orchestrator talks to external modules over HTTP.
+#[derive(Debug, Clone, PartialEq, Eq, Builder)]
+pub struct ModuleClientConfiguration {
+    /// Maximum time to establish a connection with a module.
+    #[builder(default = "Duration::from_secs(10)")]
+    pub connect_timeout: Duration,
+    /// Maximum time for a module to answer a request, including whole identification steps.
+    #[builder(default = "Duration::from_secs(600)")]
+    pub read_timeout: Duration,
+    /// How many times a request that failed to connect, timed out or got a server error is sent again.
+    #[builder(default = "3")]
+    pub max_retries: u32,
+    /// Waiting time before the first retry, doubled at every following retry.
+    #[builder(default = "Duration::from_millis(250)")]
+    pub retry_backoff: Duration,
+    /// Maximum number of models queried, uploaded or fetched in one request.
+    #[builder(default = "64")]
+    pub batch_size: usize,
+    /// Whether batched uploads send the models as CBOR instead of JSON.
+    #[builder(default = "false")]
+    pub use_cbor: bool,
+}
 
-    fn write_line_to_input(&self, s: &str) -> Option<()> {
-        if let Ok(mut server_guard) = self.get_process().lock() {
-            let server = server_guard.deref_mut();
-            if let Some(childin) = &mut server.stdin {
-                let mut buf = BufWriter::new(childin);
-                let res = writeln!(buf, "{}", s);
-                return buf.flush().and(res).ok();
-            }
-        }
-        None
+impl Default for ModuleClientConfiguration {
+    fn default() -> Self {
+        ModuleClientConfigurationBuilder::default()
+            .build()
+            .expect("Failed to build the default module client configuration. Should never fail.")
     }
+}
 
-    fn read_line_from_output(&self) -> Option<String> {
-        if let Ok(mut server_guard) = self.get_process().lock() {
-            let server = server_guard.deref_mut();
-            if let Some(out) = &mut server.stdout {
-                let mut buf = BufReader::new(out);
-                let mut line: String = "".to_string();
-                if let Ok(_) = buf.read_line(&mut line) {
-                    return Some(line.trim().to_string());
-                };
-            }
-        }
-        None
-    }
+pub fn find_modules(modules_path: &Path) -> Vec<Arc<dyn Module>> {
+    find_modules_with_config(modules_path, 0, None, &ModuleClientConfiguration::default())
+}
 
-    fn read_lines_from_output(&self) -> Vec<String> {
-        if let Ok(mut server_guard) = self.get_process().lock() {
-            let server = server_guard.deref_mut();
-            if let Some(out) = &mut server.stdout {
-                let buf = BufReader::new(out);
-                return buf.lines().flatten().collect();
-            }
-        }
-        vec![]
+/// Finds and starts all modules in `modules_path`, guarded with [quarantine::guard_module].
+///
+/// If `logs_path` is given, the standard error of every module started as a local
+/// server is kept there, one file per module. These modules are reached with
+/// `client_configuration`.
+pub fn find_modules_with_config(
+    modules_path: &Path,
+    jvm_max_heap_in_mb: usize,
+    logs_path: Option<&Path>,
+    client_configuration: &ModuleClientConfiguration,
+) -> Vec<Arc<dyn Module>> {
+    let mut modules: Vec<Arc<dyn Module>> = Vec::new();
+    if let Ok(read_dir) = modules_path.read_dir() {
+        let module_files: Vec<PathBuf> = read_dir
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_file())
+            .map(|p| p.read_link().unwrap_or(p))
+            .collect();
+        let (java_modules, other_files) = load_java_modules(module_files, jvm_max_heap_in_mb);
+        modules.extend(java_modules);
+        // the remaining JARs and executables are launched as local module servers.
+        let server_candidates: Vec<PathBuf> = other_files
+            .into_iter()
+            .filter(|p| {
+                p.extension()
+                    .map(|ext| ext.eq_ignore_ascii_case("jar"))
+                    .unwrap_or(false)
+                    || is_executable(p)
+            })
+            .collect();
+        modules.extend(start_server_modules(
+            &server_candidates,
+            logs_path,
+            client_configuration,
+        ));
     }
+    quarantine::guard_modules(modules, &modules_path.display().to_string())
+}
 
-    fn map_output<F, O>(&self, f: F) -> Option<O>
-    where
-        F: Fn(BufReader<&mut ChildStdout>) -> O,
-    {
-        if let Ok(mut server_guard) = self.get_process().lock() {
-            let server = server_guard.deref_mut();
-            if let Some(out) = &mut server.stdout {
-                return Some(f(BufReader::new(out)));
-            }
+/// Loads the JARs among `module_files` that are JNI modules into the embedded JVM,
+/// returning them and the remaining files.
+#[cfg(feature = "java")]
+fn load_java_modules(
+    module_files: Vec<PathBuf>,
+    jvm_max_heap_in_mb: usize,
+) -> (Vec<Arc<dyn Module>>, Vec<PathBuf>) {
+    let (jar_modules, other_files): (Vec<PathBuf>, Vec<PathBuf>) =
+        module_files.into_iter().partition(|p| {
+            p.extension()
+                .map(|ext| ext.eq_ignore_ascii_case("jar"))
+                .unwrap_or(false)
+                && idesyde_bridge_java::is_jni_module_jar(p)
+        });
+    let mut modules: Vec<Arc<dyn Module>> = Vec::new();
+    if !jar_modules.is_empty() {
+        let modules_result = idesyde_bridge_java::java_modules_from_jar_paths(
+            jar_modules.as_slice(),
+            jvm_max_heap_in_mb,
+        );
+        for module in modules_result.result {
+            modules.push(Arc::new(module) as Arc<dyn Module>);
         }
-        None
-    }
-
-    fn read_line_from_err(&self) -> Option<String> {
-        if let Ok(mut server_guard) = self.get_process().lock() {
-            let server = server_guard.deref_mut();
-            if let Some(out) = &mut server.stderr {
-                let mut buf = BufReader::new(out);
-                let mut line = "".to_string();
-                return match buf.read_line(line.borrow_mut()) {
-                    Ok(_) => Some(line.to_owned()),
-                    Err(_) => None,
-                };
-                // return buf.lines().map(|x| x.ok()).;
-            }
+        for warn_msg in modules_result.warn {
+            warn!("{}", warn_msg);
         }
-        None
     }
+    (modules, other_files)
 }
 
-#[derive(Debug, Clone)]
-pub struct ExternalServerModule {
-    name: String,
-    url: Url,
-    client: Arc<reqwest::blocking::Client>,
-    process: Option<Arc<Mutex<Child>>>,
+/// Without the `java` feature, no JAR is loaded as a JNI module.
+#[cfg(not(feature = "java"))]
+fn load_java_modules(
+    module_files: Vec<PathBuf>,
+    _jvm_max_heap_in_mb: usize,
+) -> (Vec<Arc<dyn Module>>, Vec<PathBuf>) {
+    (vec![], module_files)
 }
 
-impl ExternalServerModule {
-    pub fn try_create_local(command_path_: PathBuf) -> Option<ExternalServerModule> {
-        // first check if it is indeed a serve-able module
-        let is_java = command_path_
-            .extension()
-            .and_then(|s| s.to_str())
-            .map(|s| s == "jar")
-            .unwrap_or(false);
-        let child_res = match is_java {
-            true => std::process::Command::new("java")
-                .arg("-jar")
-                .arg(&command_path_)
-                .arg("--server")
-                .arg("http")
-                .stdin(Stdio::null())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::null())
-                .spawn(),
-            false => std::process::Command::new(&command_path_)
-                .arg("--server")
-                .arg("http")
-                .stdin(Stdio::null())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::null())
-                .spawn(),
-        };
-        // the test involves just exitting it
-        if let Ok(mut server_child) = child_res {
-            if let Some(childout) = &mut server_child.stdout {
-                let buf = BufReader::new(childout);
-                let initialized_line_opt = buf
-                    .lines()
-                    .flatten()
-                    .filter(|l| l.starts_with("INITIALIZED"))
-                    .next();
-                if let Some(initialized_line) = initialized_line_opt {
-                    let mut split = initialized_line[12..].split(" ");
-                    let port_opt = split.next().and_then(|x| x.parse::<usize>().ok());
-                    if let Some(port) = port_opt {
-                        let name = split
-                            .next()
-                            .unwrap_or(
-                                command_path_
-                                    .clone()
-                                    .file_name()
-                                    .and_then(|x| x.to_str())
-                                    .and_then(|x| x.split('.').next())
-                                    .expect("Could not fetch name from imodule file name."),
-                            )
-                            .to_string();
-                        return Some(ExternalServerModule {
-                            name,
-                            url: Url::parse(&format!("http://127.0.0.1:{}", port))
-                                .expect("Failed to build imodule url. Should always succeed."),
-                            client: Arc::new(reqwest::blocking::Client::new()),
-                            process: Some(Arc::new(Mutex::new(server_child))),
-                        });
+/// Starts the `candidates` as local module servers.
+///
+/// They are started concurrently since each one can take a while to initialize.
+#[cfg(feature = "http-modules")]
+fn start_server_modules(
+    candidates: &[PathBuf],
+    logs_path: Option<&Path>,
+    client_configuration: &ModuleClientConfiguration,
+) -> Vec<Arc<dyn Module>> {
+    let server_modules: Vec<ExternalServerModule> = std::thread::scope(|scope| {
+        let handles: Vec<_> = candidates
+            .iter()
+            .map(|p| {
+                scope.spawn(move || {
+                    let module = ExternalServerModule::try_create_local_with_config(
+                        p.to_owned(),
+                        logs_path,
+                        client_configuration.to_owned(),
+                    );
+                    if module.is_none() {
+                        warn!(
+                            "Could not start {} as a module. Trying to proceed anyway.",
+                            p.display()
+                        );
                     }
-                }
-            }
-        }
-        None
-    }
-
-    pub fn from(url: &Url, default_name: &str) -> ExternalServerModule {
-        let name = url
-            .join("/info/unique_identifier")
-            .ok()
-            .and_then(|u| reqwest::blocking::get(u).ok())
-            .and_then(|res| res.text().ok())
-            .unwrap_or(default_name.to_string());
-        ExternalServerModule {
-            name,
-            url: url.to_owned(),
-            client: Arc::new(reqwest::blocking::Client::new()),
-            process: None,
-        }
-    }
-}
-
-// impl LocalServerLike for ExternalServerIdentificationModule {
-//     fn get_process(&self) -> Arc<Mutex<Child>> {
-//         self.process.clone()
-//     }
-// }
-
-impl PartialEq for ExternalServerModule {
-    fn eq(&self, other: &Self) -> bool {
-        self.name == other.name && self.url == other.url
-        // && self.inputs_path == other.inputs_path
-        // && self.identified_path == other.identified_path
-        // && self.solved_path == other.solved_path
-        // && self.reverse_path == other.reverse_path
-        // && self.output_path_ == other.output_path_
-    }
+                    module
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|h| h.join().ok().flatten())
+            .collect()
+    });
+    server_modules
+        .into_iter()
+        .map(|module| Arc::new(module) as Arc<dyn Module>)
+        .collect()
 }
 
-impl Eq for ExternalServerModule {}
-
-impl Hash for ExternalServerModule {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.name.hash(state);
+/// Without the `http-modules` feature, module servers cannot be started.
+#[cfg(not(feature = "http-modules"))]
+fn start_server_modules(
+    candidates: &[PathBuf],
+    _logs_path: Option<&Path>,
+    _client_configuration: &ModuleClientConfiguration,
+) -> Vec<Arc<dyn Module>> {
+    for p in candidates {
+        warn!(
+            "Module {} runs as a server, which needs the http-modules feature. Ignoring it.",
+            p.display()
+        );
     }
+    vec![]
 }
 
-impl Drop for ExternalServerModule {
-    fn drop(&mut self) {
-        if let Some(local_process) = self.process.clone() {
-            if let Ok(mut server_guard) = local_process.lock() {
-                if let Err(e) = server_guard.kill() {
-                    debug!(
-                        "Ignoring error whilst killing imodule {}: {}",
-                        self.unique_identifier(),
-                        e.to_string(),
-                    );
+/// Finds and starts all modules in `modules_paths`, which are ordered by priority, as in [find_modules_with_config].
+///
+/// If modules of different directories have the same unique identifier, only the one of the
+/// directory that comes first is kept.
+pub fn find_modules_in_paths(
+    modules_paths: &[PathBuf],
+    jvm_max_heap_in_mb: usize,
+    logs_path: Option<&Path>,
+    client_configuration: &ModuleClientConfiguration,
+) -> Vec<Arc<dyn Module>> {
+    let mut modules: Vec<Arc<dyn Module>> = Vec::new();
+    let mut origins: HashMap<String, &Path> = HashMap::new();
+    for modules_path in modules_paths {
+        for module in find_modules_with_config(
+            modules_path,
+            jvm_max_heap_in_mb,
+            logs_path,
+            client_configuration,
+        ) {
+            let id = module.unique_identifier();
+            match origins.get(&id) {
+                Some(origin) => warn!(
+                    "Module {} in {} is already provided by {}. Ignoring it.",
+                    id,
+                    modules_path.display(),
+                    origin.display()
+                ),
+                None => {
+                    origins.insert(id, modules_path.as_path());
+                    modules.push(module);
                 }
             }
         }
     }
+    modules
 }
 
-impl Module for ExternalServerModule {
-    fn unique_identifier(&self) -> String {
-        self.name.clone()
-    }
-
-    fn identification_step(
-        &self,
-        decision_models: &Vec<Arc<dyn DecisionModel>>,
-        design_models: &Vec<Arc<dyn DesignModel>>,
-    ) -> IdentificationResult {
-        // let mut mut_url = self.url.clone();
-        // mut_url
-        //     .set_scheme("ws")
-        //     .expect("Failed to set scheme to 'ws'.");
-        // if let Ok(identify_url) = mut_url.join("/identify") {
-        //     if let Some((ws, _)) = mut_url
-        //         .socket_addrs(|| None)
-        //         .ok()
-        //         .and_then(|addrs| addrs.first().cloned())
-        //         .and_then(|addr| std::net::TcpStream::connect(addr).ok())
-        //         .and_then(|stream| tungstenite::client(identify_url, stream).ok())
-        //     {
-        //         return Box::new(ExternalServerIdentifiticationIterator::new(
-        //             initial_design_models,
-        //             initial_decision_models,
-        //             ws,
-        //         ));
-        //     }
-        // }
-        // Box::new(idesyde_core::empty_identification_iter())
-        design_models
-            .par_iter()
-            .filter(|m| {
-                // let mut form = Form::new();
-                // form = form.text("category", m.category());
-                // for e in m.elements() {
-                //     form = form.text("elements", e);
-                // }
-                let hash = m.global_sha2_hash();
-                if let Ok(cache_url) = self.url.join("/design/cache/exists") {
-                    return self
-                        .client
-                        .get(cache_url)
-                        .body(hash)
-                        .send()
-                        .ok()
-                        .and_then(|r| r.text().ok())
-                        .map(|x| x.eq_ignore_ascii_case("false"))
-                        .unwrap_or(true);
-                }
-                true
-            })
-            .map(|m| OpaqueDesignModel::from(m.as_ref()))
-            .for_each(|m| {
-                if let Ok(bodyj) = m.to_json() {
-                    if let Ok(design_add_url) = self.url.join("/design/cache/add") {
-                        let form = Form::new().text("designModel", bodyj);
-                        if let Err(e) = self.client.post(design_add_url).multipart(form).send() {
-                            debug!(
-                                "Failed to send design model to identify with: {}",
-                                e.to_string()
-                            );
-                        };
-                    }
-                }
-            });
-        decision_models
-            .par_iter()
-            .filter(|m| {
-                // let mut form = Form::new();
-                // form = form.text("category", m.category());
-                // for e in m.part() {
-                //     form = form.text("part", e);
-                // }
-                let hash = m.global_sha2_hash();
-                if let Ok(cache_url) = self.url.join("/decision/cache/exists") {
-                    return self
-                        .client
-                        .get(cache_url)
-                        .body(hash)
-                        .send()
-                        .ok()
-                        .and_then(|r| r.text().ok())
-                        .map(|x| x.eq_ignore_ascii_case("false"))
-                        .unwrap_or(true);
-                }
-                true
-            })
-            .map(|m| OpaqueDecisionModel::from(m.as_ref()))
-            .for_each(|m| {
-                // debug!(
-                //     "Sending {:?} to module {:?}",
-                //     m.category(),
-                //     self.unique_identifier()
-                // );
-                if let Ok(bodyj) = m.to_json() {
-                    if let Ok(decision_add_url) = self.url.join("/decision/cache/add") {
-                        let form = Form::new().text("decisionModel", bodyj);
-                        if let Err(e) = self.client.post(decision_add_url).multipart(form).send() {
-                            debug!(
-                                "Failed to send design model to identify  with: {}",
-                                e.to_string()
-                            );
-                        };
-                    }
-                }
-            });
-        if let Ok(identify) = self.url.join("/identify") {
-            if let Some(identified_message) = self
-                .client
-                .post(identify)
-                // .multipart(form)
-                .send()
-                .ok()
-                .and_then(|res| res.text().ok())
-                .and_then(|txt| {
-                    // debug!("Received identification result: {}", txt.as_str());
-                    serde_json::from_str::<IdentificationResultCompactMessage>(txt.as_str()).ok()
-                })
-            {
-                let reversed_hashes = identified_message
-                    .identified
-                    .iter()
-                    .map(|s| general_purpose::STANDARD_NO_PAD.decode(s).ok())
-                    .flatten()
-                    .collect::<Vec<Vec<u8>>>();
-                // debug!("Reversed hashes: {:?}", reversed_hashes);
-                let identified_models = reversed_hashes
-                    .into_par_iter()
-                    .flat_map(|hash| {
-                        // let hash_str = general_purpose::STANDARD.encode(hash);
-                        if let Ok(cache_url) = self.url.join("/decision/cache/fetch") {
-                            return self
-                                .client
-                                .get(cache_url)
-                                .body(hash)
-                                .send()
-                                .ok()
-                                .and_then(|r| r.text().ok())
-                                .and_then(|txt| {
-                                    OpaqueDecisionModel::from_json_str(txt.as_str()).ok()
-                                })
-                                .map(|x| Arc::new(x) as Arc<dyn DecisionModel>);
-                        }
-                        None
-                    })
-                    .collect();
-                return (
-                    identified_models,
-                    identified_message.messages.into_iter().collect(),
+/// Keeps the first of the modules with the same unique identifier, warning about the others.
+pub fn deduplicate_modules(modules: Vec<Arc<dyn Module>>) -> Vec<Arc<dyn Module>> {
+    let mut ids: HashSet<String> = HashSet::new();
+    modules
+        .into_iter()
+        .filter(|m| {
+            let first = ids.insert(m.unique_identifier());
+            if !first {
+                warn!(
+                    "Module {} is provided more than once. Ignoring the duplicate.",
+                    m.unique_identifier()
                 );
             }
-        }
-        (vec![], vec![])
-    }
+            first
+        })
+        .collect()
+}
 
-    fn reverse_identification(
-        &self,
-        solved_decision_models: &Vec<Arc<dyn DecisionModel>>,
-        design_models: &Vec<Arc<dyn DesignModel>>,
-    ) -> Vec<Arc<dyn DesignModel>> {
-        // let mut mut_url = self.url.clone();
-        // mut_url
-        //     .set_scheme("ws")
-        //     .expect("Failed to set scheme to 'ws'.");
-        if let Ok(reverse_url) = self.url.join("/reverse") {
-            design_models
-                .par_iter()
-                .filter(|m| {
-                    let hash = m.global_sha2_hash();
-                    if let Ok(cache_url) = self.url.join("/design/cache/exists") {
-                        return self
-                            .client
-                            .get(cache_url)
-                            .body(hash)
-                            .send()
-                            .ok()
-                            .and_then(|r| r.text().ok())
-                            .map(|x| x.eq_ignore_ascii_case("false"))
-                            .unwrap_or(true);
-                    }
-                    true
-                })
-                .map(|m| OpaqueDesignModel::from(m.as_ref()))
-                .for_each(|m| {
-                    if let Ok(bodyj) = m.to_json() {
-                        if let Ok(design_add_url) = self.url.join("/design/cache/add") {
-                            let form = Form::new().text("designModel", bodyj);
-                            if let Err(e) = self.client.post(design_add_url).multipart(form).send()
-                            {
-                                debug!(
-                                    "Failed to send design model to reverse with: {}",
-                                    e.to_string()
-                                );
-                            };
-                        }
-                    }
-                });
-            solved_decision_models
-                .par_iter()
-                .filter(|m| {
-                    let hash = m.global_sha2_hash();
-                    if let Ok(cache_url) = self.url.join("/solved/cache/exists") {
-                        return self
-                            .client
-                            .get(cache_url)
-                            .body(hash)
-                            .send()
-                            .ok()
-                            .and_then(|r| r.text().ok())
-                            .map(|x| x.eq_ignore_ascii_case("false"))
-                            .unwrap_or(true);
-                    }
-                    true
-                })
-                .map(|m| OpaqueDecisionModel::from(m.as_ref()))
-                .for_each(|m| {
-                    if let Ok(bodyj) = m.to_json() {
-                        if let Ok(decision_add_url) = self.url.join("/solved/cache/add") {
-                            let form = Form::new().text("solvedModel", bodyj);
-                            if let Err(e) =
-                                self.client.post(decision_add_url).multipart(form).send()
-                            {
-                                debug!(
-                                    "Failed to send design model to reverse with: {}",
-                                    e.to_string()
-                                );
-                            };
-                        }
-                    }
-                });
-            // let mut form = Form::new();
-            // for m in opaques {
-            //     if let Ok(bodyj) = m.to_json() {
-            //         form = form.text(format!("design{}", m.category()), bodyj);
-            //     }
-            // }
-            // for m in solved {
-            //     if let Ok(bodyj) = m.to_json() {
-            //         // let part = Part::text(bodyj);
-            //         form = form.text(format!("solved{}", m.category()), bodyj);
-            //     }
-            // }
-            let reversed_hash_str: Vec<String> = self
-                .client
-                .post(reverse_url)
-                // .multipart(form)
-                .send()
-                .ok()
-                .and_then(|res| res.text().ok())
-                .and_then(|txt| serde_json::from_str::<Vec<String>>(txt.as_str()).ok())
-                .unwrap_or(vec![]);
-            let reversed_hashes = reversed_hash_str
-                .iter()
-                .map(|s| general_purpose::STANDARD_NO_PAD.decode(s).ok())
-                .flatten()
-                .collect::<Vec<Vec<u8>>>();
-            let reversed_models = reversed_hashes
-                .into_par_iter()
-                .flat_map(|hash| {
-                    // let hash_str = general_purpose::STANDARD.encode(hash);
-                    if let Ok(cache_url) = self.url.join("/reversed/cache/fetch") {
-                        return self
-                            .client
-                            .get(cache_url)
-                            .body(hash)
-                            .send()
-                            .ok()
-                            .and_then(|r| r.text().ok())
-                            .and_then(|txt| OpaqueDesignModel::from_json_str(txt.as_str()).ok())
-                            .map(|x| Arc::new(x) as Arc<dyn DesignModel>);
-                    }
-                    None
-                })
-                .collect();
-            return reversed_models;
-            // if let Some((mut ws, _)) = mut_url
-            //     .socket_addrs(|| None)
-            //     .ok()
-            //     .and_then(|addrs| addrs.first().cloned())
-            //     .and_then(|addr| {
-            //         std::net::TcpStream::connect_timeout(&addr, Duration::from_millis(200)).ok()
-            //     })
-            //     .and_then(|stream| tungstenite::client(reverse_url, stream).ok())
-            // {
-            //     // send solved decision models
-            //     for m in solved_decision_models {
-            //         if let Ok(decision_json) = OpaqueDecisionModel::from(m).to_json() {
-            //             if let Err(e) = ws.send(tungstenite::Message::text(decision_json)) {
-            //                 debug!("Decision JSON upload error {}", e.to_string());
-            //             }
-            //         }
-            //     }
-            //     // same for design models
-            //     for m in design_models {
-            //         if let Ok(design_json) = OpaqueDesignModel::from(m.as_ref()).to_json() {
-            //             if let Err(e) = ws.send(tungstenite::Message::text(design_json)) {
-            //                 debug!("Design JSON upload error {}", e.to_string());
-            //             };
-            //         };
-            //     }
-            //     if let Err(e) = ws.send(tungstenite::Message::text("done")) {
-            //         debug!("Failed to send 'done': {}", e.to_string());
-            //     };
-            //     let (identified_tx, identified_rx) =
-            //         std::sync::mpsc::channel::<Arc<dyn DesignModel>>();
-            //     // the way in which the things are being reversed is currently a bit hacky.
-            //     // in the future it is best if the reciever does NOT depend on any time-outs.
-            //     // Currently it seems like the websocket connetions are a bit janky, so we do this workaround.
-            //     ws.flush().expect("Failed to flush info for reversing");
-            //     let imodule_name = self.unique_identifier().to_owned();
-            //     std::thread::spawn(move || {
-            //         while let Ok(message) = ws.read() {
-            //             // besides the answer, also read the module's messages
-            //             match message {
-            //                 tungstenite::Message::Text(txt_msg) => {
-            //                     if txt_msg.eq_ignore_ascii_case("done") {
-            //                         debug!("Reverse done for {}", imodule_name);
-            //                         break;
-            //                     } else if let Ok(opaque) =
-            //                         OpaqueDesignModel::from_json_str(txt_msg.as_str())
-            //                     {
-            //                         let opaquea = Arc::new(opaque) as Arc<dyn DesignModel>;
-            //                         if let Err(e) = identified_tx.send(opaquea) {
-            //                             debug!(
-            //                                 "Failed to recover an identified design model with: {}",
-            //                                 e.to_string()
-            //                             );
-            //                         };
-            //                     }
-            //                 }
-            //                 tungstenite::Message::Binary(decision_cbor) => {
-            //                     if let Ok(opaque) =
-            //                         OpaqueDesignModel::from_cbor(decision_cbor.as_slice())
-            //                     {
-            //                         let opaquea = Arc::new(opaque) as Arc<dyn DesignModel>;
-            //                         if let Err(e) = identified_tx.send(opaquea) {
-            //                             debug!(
-            //                                 "Failed to recover an identified design model with: {}",
-            //                                 e.to_string()
-            //                             );
-            //                         };
-            //                     }
-            //                 }
-            //                 tungstenite::Message::Ping(_) => {
-            //                     if let Err(_) = ws.send(tungstenite::Message::Pong(vec![])) {
-            //                         debug!(
-            //                             "Failed to send ping message to other end. Trying to proceed anyway."
-            //                         );
-            //                     };
-            //                 }
-            //                 tungstenite::Message::Pong(_) => {
-            //                     if let Err(_) = ws.send(tungstenite::Message::Ping(vec![])) {
-            //                         debug!(
-            //                             "Failed to send pong message to other end. Trying to proceed anyway."
-            //                         );
-            //                     };
-            //                 }
-            //                 _ => break,
-            //             }
-            //         }
-            //     });
-            //     let mut reverse_identified = Vec::new();
-            //     // println!("Reverse done for {}", self.unique_identifier());
-            //     while let Ok(m) = identified_rx.recv_timeout(Duration::from_millis(
-            //         250 * ((solved_decision_models.len() + design_models.len()) as u64),
-            //     )) {
-            //         reverse_identified.push(m);
-            //     }
-            //     // get all last without blocking
-            //     identified_rx
-            //         .try_iter()
-            //         .for_each(|m| reverse_identified.push(m));
-            //     return reverse_identified;
-            // }
-        }
-        vec![]
-    }
+/// The modules compiled into the orchestrator, guarded as in [quarantine::guard_module].
+///
+/// The MiniZinc module is only included with the `minizinc` feature.
+pub fn embedded_modules() -> Vec<Arc<dyn Module>> {
+    let modules: Vec<Arc<dyn Module>> = vec![
+        Arc::new(idesyde_common::make_module()),
+        #[cfg(feature = "minizinc")]
+        Arc::new(idesyde_bridge_minizinc::make_module()),
+    ];
+    quarantine::guard_modules(modules, "the orchestrator")
+}
 
-    fn explorers(&self) -> Vec<Arc<dyn idesyde_core::Explorer>> {
-        if let Ok(explorers_url) = self.url.join("/explorers") {
-            match self.client.get(explorers_url).send() {
-                Ok(result) => match result.text() {
-                    Ok(text) => match serde_json::from_str::<Vec<String>>(&text) {
-                        Ok(names) => {
-                            return names
-                                .iter()
-                                .map(|name| {
-                                    Arc::new(
-                                        ExternalExplorerBuilder::default()
-                                            .name(name.to_owned())
-                                            .url(self.url.to_owned())
-                                            .client(self.client.to_owned())
-                                            .build()
-                                            .expect("Failed to build an external explorer. Should never fail."),
-                                    )
-                                })
-                                .map(|x| x as Arc<dyn Explorer>)
-                                .collect();
-                        }
-                        Err(_) => {
-                            warn!(
-                                "Explorer {} failed deserialize its decision model. Trying to proceed anyway.",
-                                self.unique_identifier()
-                            );
-                            debug!(
-                                "Explorer {} failed to deserialize explorer names from {}",
-                                self.unique_identifier(),
-                                &text
-                            );
-                        }
-                    },
-                    Err(err) => {
-                        warn!(
-                            "Explorer {} failed to process request. Trying to proceed anyway.",
-                            self.unique_identifier()
-                        );
-                        debug!(
-                            "Explorer {} failed to transform into text with: {}",
-                            self.unique_identifier(),
-                            err.to_string()
-                        );
-                    }
-                },
-                Err(err) => {
-                    warn!(
-                        "Explorer {} failed to accept request. Trying to proceed anyway.",
-                        self.unique_identifier()
-                    );
-                    debug!(
-                        "Explorer {} failed to request with: {}",
-                        self.unique_identifier(),
-                        err.to_string()
-                    );
+/// Connects to the external modules at `urls`, which are not started or stopped by the orchestrator.
+///
+/// Only `http` URLs are supported. The modules that cannot be used are ignored with a warning.
+#[cfg(feature = "http-modules")]
+pub fn modules_from_urls(
+    urls: &[String],
+    client_configuration: &ModuleClientConfiguration,
+) -> Vec<Arc<dyn Module>> {
+    let mut modules: Vec<Arc<dyn Module>> = vec![];
+    for url_str in urls {
+        match Url::parse(url_str.as_str()) {
+            Ok(parsed_url) if parsed_url.scheme() == "http" => {
+                match ExternalServerModule::from_with_config(
+                    &parsed_url,
+                    url_str.as_str(),
+                    client_configuration.to_owned(),
+                ) {
+                    Ok(module) => modules.extend(quarantine::guard_modules(
+                        vec![Arc::new(module)],
+                        url_str.as_str(),
+                    )),
+                    Err(e) => warn!(
+                        "Module at {} cannot be used by this orchestrator: {}. Ignoring it.",
+                        url_str, e
+                    ),
                 }
             }
+            Ok(parsed_url) => warn!(
+                "Module URL {} has the unsupported scheme {}. Ignoring it.",
+                url_str,
+                parsed_url.scheme()
+            ),
+            Err(e) => warn!(
+                "Failed to parse module URL {}: {}. Ignoring it.",
+                url_str, e
+            ),
         }
-        Vec::new()
     }
-}
-pub fn find_modules(modules_path: &Path) -> Vec<Arc<dyn Module>> {
-    find_modules_with_config(modules_path, 0)
+    modules
 }
 
-pub fn find_modules_with_config(
-    modules_path: &Path,
-    jvm_max_heap_in_mb: usize,
+/// Without the `http-modules` feature, no external module can be reached, so all are ignored with a warning.
+#[cfg(not(feature = "http-modules"))]
+pub fn modules_from_urls(
+    urls: &[String],
+    _client_configuration: &ModuleClientConfiguration,
 ) -> Vec<Arc<dyn Module>> {
-    let mut modules: Vec<Arc<dyn Module>> = Vec::new();
-    if let Ok(read_dir) = modules_path.read_dir() {
-        let jar_modules: Vec<PathBuf> = read_dir
-            .filter_map(|e| e.ok())
-            .map(|e| e.path())
-            .filter(|p| p.is_file())
-            .map(|p| p.read_link().unwrap_or(p))
-            .filter(|p| {
-                p.extension()
-                    .map(|ext| ext.eq_ignore_ascii_case("jar"))
-                    .unwrap_or(false)
-            })
-            .collect();
-        let modules_result =
-            java_modules_from_jar_paths(jar_modules.as_slice(), jvm_max_heap_in_mb);
-        for module in modules_result.result {
-            modules.push(Arc::new(module) as Arc<dyn Module>);
-        }
-        for warn_msg in modules_result.warn {
-            warn!("{}", warn_msg);
-        }
+    for url_str in urls {
+        warn!(
+            "Module at {} needs the http-modules feature. Ignoring it.",
+            url_str
+        );
     }
-    modules
+    vec![]
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.eq_ignore_ascii_case("exe"))
+        .unwrap_or(false)
 }
 
 // pub fn find_exploration_modules(modules_path: &Path) -> Vec<Arc<dyn Module>> {
@@ -746,3 +381,27 @@ pub fn compute_dominant_decision_models<'a>(
         .map(|o| o.to_owned())
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use idesyde_core::RustEmbeddedModule;
+
+    fn module(id: &str) -> Arc<dyn Module> {
+        Arc::new(
+            RustEmbeddedModule::builder()
+                .unique_identifier(id.to_string())
+                .build()
+                .expect("Module of the tests is complete. Should never fail."),
+        )
+    }
+
+    #[test]
+    fn deduplicate_modules_keeps_the_first() {
+        let (first_a, b, second_a) = (module("A"), module("B"), module("A"));
+        let deduplicated = deduplicate_modules(vec![first_a.clone(), b.clone(), second_a]);
+        assert_eq!(deduplicated.len(), 2);
+        assert!(Arc::ptr_eq(&deduplicated[0], &first_a));
+        assert!(Arc::ptr_eq(&deduplicated[1], &b));
+    }
+}
@@ -0,0 +1,148 @@
+//! Set up of the structured logging of the orchestrator.
+//!
+//! The orchestration, core and bridge crates log through `tracing`, with spans for each identification
+//! step, identification rule, bid, exploration level and reverse identification rule, so that the
+//! records of different modules, rules and explorers can be told apart. Records of crates that still
+//! use `log` are forwarded to the same subscriber.
+//!
+//! The records can be written as human-readable text or as JSON lines, one object per record with the
+//! fields of the record and of its enclosing spans, both to the standard output and to a log file.
+use std::{fmt::Display, fs::File, path::Path, str::FromStr, sync::Mutex};
+
+use tracing::{level_filters::LevelFilter, Level};
+use tracing_subscriber::{
+    field::RecordFields,
+    fmt::{
+        format::{DefaultFields, Writer},
+        FormatFields, MakeWriter,
+    },
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+    EnvFilter, Layer,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("unknown log format '{}', expected text or json", s)),
+        }
+    }
+}
+
+impl Display for LogFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogFormat::Text => write!(f, "text"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// The fields of the spans are formatted once per formatter type and shared between layers,
+/// so the log file has its own type to not get the colours of the standard output.
+struct PlainFields(DefaultFields);
+
+impl<'w> FormatFields<'w> for PlainFields {
+    fn format_fields<R: RecordFields>(&self, writer: Writer<'w>, fields: R) -> std::fmt::Result {
+        self.0.format_fields(writer, fields)
+    }
+}
+
+/// Parses a verbosity as given in the command line, e.g. `debug` or `warning`.
+pub fn parse_level(verbosity: &str) -> Option<Level> {
+    match verbosity.to_lowercase().as_str() {
+        "trace" => Some(Level::TRACE),
+        "debug" => Some(Level::DEBUG),
+        "warn" | "warning" => Some(Level::WARN),
+        "err" | "error" => Some(Level::ERROR),
+        "info" => Some(Level::INFO),
+        _ => None,
+    }
+}
+
+/// Installs the global subscriber, writing to `writer` and, if given, to `log_file`, in `format`.
+///
+/// Records are kept if they are at least as severe as `level`, unless one of the `directives` says
+/// otherwise. Directives follow the syntax of `RUST_LOG`, e.g. `idesyde_bridge_minizinc=debug`
+/// for a crate, or `[exploration_level{explorer=MiniZinc}]=debug` for the records of the spans of
+/// one explorer. The directory of the log file is created if needed and the file is truncated.
+pub fn init_logging<W>(
+    level: Level,
+    directives: &[String],
+    format: LogFormat,
+    log_file: Option<&Path>,
+    writer: W,
+) -> Result<(), String>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    // the base level goes first so that the directives refine it
+    let filter = EnvFilter::builder()
+        .parse(
+            std::iter::once(LevelFilter::from_level(level).to_string())
+                .chain(directives.iter().cloned())
+                .collect::<Vec<String>>()
+                .join(","),
+        )
+        .map_err(|e| format!("invalid log filter: {}", e))?;
+    let file = match log_file {
+        Some(path) => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("failed to create {}: {}", parent.display(), e))?;
+            }
+            Some(
+                File::create(path)
+                    .map_err(|e| format!("failed to create {}: {}", path.display(), e))?,
+            )
+        }
+        None => None,
+    };
+    let (output_layer, file_layer) = match format {
+        LogFormat::Text => (
+            tracing_subscriber::fmt::layer()
+                .with_target(false)
+                .with_writer(writer)
+                .boxed(),
+            file.map(|f| {
+                tracing_subscriber::fmt::layer()
+                    .with_target(false)
+                    .with_ansi(false)
+                    .fmt_fields(PlainFields(DefaultFields::new()))
+                    .with_writer(Mutex::new(f))
+                    .boxed()
+            }),
+        ),
+        LogFormat::Json => (
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_span_list(true)
+                .with_writer(writer)
+                .boxed(),
+            file.map(|f| {
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_span_list(true)
+                    .with_writer(Mutex::new(f))
+                    .boxed()
+            }),
+        ),
+    };
+    tracing_subscriber::registry()
+        .with(output_layer)
+        .with(file_layer)
+        .with(filter)
+        .try_init()
+        .map_err(|e| e.to_string())
+}
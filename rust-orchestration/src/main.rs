@@ -1,21 +1,30 @@
-use std::{cmp::Ordering, collections::HashSet, path::Path, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use clap::Parser;
-use env_logger::WriteStyle;
-use idesyde_core::{
-    DecisionModel, DesignModel, ExplorationBid, ExplorationSolution, Explorer, OpaqueDesignModel,
-};
+#[cfg(feature = "http-modules")]
+use idesyde_orchestration::conformance;
 use idesyde_orchestration::{
-    exploration::explore_cooperatively, identification::identification_procedure,
+    events,
+    exploration::{ExplorationStrategyBuilder, RestartPolicy, SolutionValidation},
+    filters::{NameFilter, SelectionFilters},
+    interrupt,
+    logging::{self, LogFormat},
+    module_paths,
+    orchestrator::OrchestratorBuilder,
+    ModuleClientConfigurationBuilder,
 };
-use log::{debug, info, warn, Level};
-use rayon::prelude::*;
+use tracing::{debug, error, info, warn};
 
 #[derive(Parser, Debug)]
 #[command(
     name = "orchestrator",
     author = "Rodolfo Jordao",
-    about = "Orchestration and entry point for XXxXxXx."
+    about = "Orchestration and entry point for XXxXxXx.",
+    after_help = "Exit codes: 0 success, 1 error, 2 partial coverage, 3 no identification, 4 no explorer, \
+    5 infeasible, 6 timed out, 7 invalid solution, 8 no solution, 130 interrupted. \nThe outcome is also written to report.json in the run directory."
 )]
 struct Args {
     // input files
@@ -42,6 +51,29 @@ struct Args {
     #[arg(short, long, help = "Sets the verbosity of this run.")]
     verbosity: Option<String>,
 
+    #[arg(
+        long,
+        default_value_t = LogFormat::Text,
+        help = "The format of the log records: text or json, with one JSON object per line."
+    )]
+    log_format: LogFormat,
+
+    #[arg(
+        long,
+        help = "Per-target or per-span log level, in the syntax of RUST_LOG, e.g. 'idesyde_bridge_minizinc=debug'. \nTakes precedence over the verbosity.",
+        long_help = "Per-target or per-span log level, in the syntax of RUST_LOG. Can be given multiple times. \n\
+         'idesyde_bridge_minizinc=debug' sets the level of a crate and '[exploration_level{explorer=MiniZinc}]=debug' \n\
+         the level of the records of one explorer. The spans are identification_step, identification_rule, bid, \n\
+         exploration_level and reverse_rule. Takes precedence over the verbosity."
+    )]
+    log_filter: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Also writes the log records to logs/orchestrator.log in the run directory."
+    )]
+    log_file: bool,
+
     #[arg(
         short,
         long,
@@ -51,10 +83,52 @@ struct Args {
 
     #[arg(
         long,
-        help = "Inclusion rule of which decision models are to be kept during bidding. \nIf none is given, all are included."
+        visible_alias = "include-decision-model",
+        help = "Glob pattern of decision model categories to keep after identification, e.g. '*PartitionedMemoryMappable*'. \nIf none is given, all are included."
     )]
     decision_model: Vec<String>,
 
+    #[arg(
+        long,
+        help = "Glob pattern of decision model categories to drop after identification. \nExclusions take precedence over inclusions."
+    )]
+    exclude_decision_model: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Glob pattern of explorer identifiers allowed to bid, e.g. 'MiniZinc*'. \nIf none is given, all are included."
+    )]
+    include_explorer: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Glob pattern of explorer identifiers not allowed to bid. \nExclusions take precedence over inclusions."
+    )]
+    exclude_explorer: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Glob pattern of module identifiers used for identification, exploration and reverse identification. \nIf none is given, all are included."
+    )]
+    include_module: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Glob pattern of module identifiers not to be used. \nExclusions take precedence over inclusions."
+    )]
+    exclude_module: Vec<String>,
+
+    #[arg(
+        long,
+        help = "A directory with modules. Can be given multiple times, with the first ones taking precedence.",
+        long_help = "A directory with modules. Can be given multiple times, with the first ones taking precedence. \n\
+         Modules are also searched, with decreasing precedence, in the directories of the IDESYDE_MODULES_PATH \n\
+         environment variable, in the 'modules_paths' of the user configuration file \n\
+         (e.g. ~/.config/idesyde/config.json) and in 'modules' in the working directory. \n\
+         If several modules have the same identifier, the one with the highest precedence is used."
+    )]
+    modules_path: Vec<PathBuf>,
+
     #[arg(
         long,
         default_value = "0",
@@ -108,50 +182,233 @@ struct Args {
     )]
     x_target_objectives: Vec<String>,
 
-    // #[arg(
-    //     long,
-    //     help = "An URL for external modules that are not created and destroyed by the orchestrator. Currently supported schemas are: http."
-    // )]
-    // module: Option<Vec<String>>,
+    #[arg(
+        long,
+        help = "Sets the seed given to all explorers so that explorations can be reproduced.",
+        long_help = "Sets the seed given to all explorers so that explorations can be reproduced. \nA seeded exploration with a single explorer produces the same solutions on every run. \nWith several explorers, which one finds a solution first still depends on timing."
+    )]
+    x_seed: Option<u64>,
+
+    #[arg(
+        long,
+        default_value = "every",
+        help = "When explorers are restarted with the updated Pareto set: 'every', 'never', 'improvements:<N>' or 'seconds:<T>'.",
+        long_help = "When explorers are restarted with the updated Pareto set, opening a new exploration level. \n\
+        'every' restarts on every new non-dominated solution, 'improvements:<N>' after N of them, \n\
+        'seconds:<T>' after T seconds with at least one of them, and 'never' keeps the first level running \n\
+        with the explorers isolated from each other, combining their solutions only in the orchestrator's Pareto set."
+    )]
+    x_restart_policy: RestartPolicy,
+
+    #[arg(
+        long,
+        default_value = "warn",
+        help = "What to do with invalid solutions returned by explorers: 'warn', 'drop' or 'abort'.",
+        long_help = "What to do with invalid solutions returned by explorers. \n\
+        A solution is invalid if its decision model has a different category or covers different design elements \n\
+        than the explored one, or if it lacks an objective the explorer bid for. \n\
+        'warn' logs and keeps it, 'drop' logs and discards it, and 'abort' stops the exploration and exits with code 7."
+    )]
+    x_solution_validation: SolutionValidation,
+
+    #[arg(
+        long,
+        default_value = "2",
+        help = "The maximum number of exploration levels running at the same time. Older levels are stopped first."
+    )]
+    x_max_levels: usize,
+
+    #[arg(
+        long,
+        help = "An URL for external modules that are not created and destroyed by the orchestrator. Currently supported schemas are: http."
+    )]
+    module: Vec<String>,
+
     #[arg(
         long,
         help = "If set, the exploration only returns solutions that improve the current Pareto set approximation."
     )]
     strict: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "events_socket",
+        help = "An already open file descriptor where progress events are written as JSON lines.",
+        long_help = "An already open file descriptor where progress events are written as JSON lines. \n\
+         For example, '--events-fd 3' when the orchestrator is started with '3>events.jsonl'. \n\
+         The event schema is documented in the 'events' module of the orchestration library."
+    )]
+    events_fd: Option<i32>,
+
+    #[arg(
+        long,
+        help = "A local Unix socket where progress events are written as JSON lines.",
+        long_help = "A local Unix socket where progress events are written as JSON lines. \n\
+         The socket must already be listening when the orchestrator starts. \n\
+         The event schema is documented in the 'events' module of the orchestration library."
+    )]
+    events_socket: Option<String>,
+
+    #[arg(
+        long,
+        default_value = "10",
+        help = "The maximum time in seconds to connect to an external module."
+    )]
+    module_connect_timeout: u64,
+
+    #[arg(
+        long,
+        default_value = "600",
+        help = "The maximum time in seconds for an external module to answer a request, including identification steps."
+    )]
+    module_read_timeout: u64,
+
+    #[arg(
+        long,
+        default_value = "3",
+        help = "How many times a failed request to an external module is retried, with an increasing delay."
+    )]
+    module_retries: u32,
+
+    #[arg(
+        long,
+        default_value = "64",
+        help = "The maximum number of models queried, sent or fetched in one request to an external module."
+    )]
+    module_batch_size: usize,
+
+    #[arg(
+        long,
+        help = "If set, models are sent to external modules as CBOR instead of JSON whenever they accept batches."
+    )]
+    module_cbor: bool,
+
+    #[arg(
+        long,
+        help = "Checks whether a module follows the module protocol and exits. \nThe module is either the URL of a running module or the path of a module executable or JAR.",
+        long_help = "Checks whether a module follows the module protocol and exits. \n\
+        The module is either the URL of a running module or the path of a module executable or JAR, \n\
+        which is launched for the check. Every endpoint is exercised with small fixture models \n\
+        and a pass or fail line is printed for each. The exit code is non-zero if any endpoint fails."
+    )]
+    check_module: Option<String>,
+
+    #[arg(
+        long,
+        help = "Runs identification and bidding only and explains what the exploration would do, without exploring."
+    )]
+    explain: bool,
+
+    #[arg(
+        long,
+        help = "Keeps running and starts over whenever an input changes, until interrupted.",
+        long_help = "Keeps running and starts over whenever an input changes, until interrupted. \n\
+         A change cancels the ongoing exploration. The next cycle reuses the decision models identified \n\
+         from the inputs that did not change, and the dominant solutions of the decision models that did not \n\
+         change as warm start, which are hints for the explorers and not results of the new cycle. \n\
+         Only the decision models identified from decision model inputs are reused, since the elements of \n\
+         design model files are not known to the orchestrator. \n\
+         Interrupting while waiting for changes exits with the outcome of the last cycle."
+    )]
+    watch: bool,
+
+    #[arg(
+        long,
+        default_value = "500",
+        help = "How often, in milliseconds, the inputs are checked for changes in watch mode."
+    )]
+    watch_interval: u64,
+}
+
+/// Exits with the given code, stopping the child processes first, since exiting skips the
+/// destructors of the modules that would stop them.
+fn exit(code: i32) -> ! {
+    let killed = idesyde_core::terminate_child_processes();
+    debug!("Terminated {} child process(es) before exiting", killed);
+    std::process::exit(code)
 }
 
 fn main() {
     let args = Args::parse();
     let verbosity = args
         .verbosity
-        .and_then(|s| match s.to_lowercase().as_str() {
-            "debug" => Some(Level::Debug),
-            "warn" | "warning" => Some(Level::Warn),
-            "err" | "error" => Some(Level::Error),
-            "info" => Some(Level::Info),
-            _ => None,
-        })
-        .unwrap_or(Level::Info);
-    env_logger::Builder::new()
-        .target(env_logger::Target::Stdout)
-        .filter(None, verbosity.to_level_filter())
-        .write_style(WriteStyle::Always)
-        .format_target(false)
-        .format_module_path(false)
-        .init();
+        .as_deref()
+        .and_then(logging::parse_level)
+        .unwrap_or(tracing::Level::INFO);
+    let log_file = args.log_file.then(|| {
+        Path::new(args.run_path.as_deref().unwrap_or("run"))
+            .join("logs")
+            .join("orchestrator.log")
+    });
+    if let Err(e) = logging::init_logging(
+        verbosity,
+        &args.log_filter,
+        args.log_format,
+        log_file.as_deref(),
+        std::io::stdout,
+    ) {
+        eprintln!("Failed to set up logging: {}", e);
+        exit(1);
+    }
+    if let Err(e) = interrupt::install_handler() {
+        warn!(
+            "Failed to install the interruption handler: {}. Interrupting will abort immediately.",
+            e
+        );
+    }
+    if let Some(fd) = args.events_fd {
+        match events::open_event_stream_fd(fd) {
+            Ok(_) => debug!("Writing progress events to file descriptor {}", fd),
+            Err(e) => warn!(
+                "Failed to open progress events file descriptor {}: {}",
+                fd, e
+            ),
+        }
+    } else if let Some(socket_path) = &args.events_socket {
+        match events::open_event_stream_socket(Path::new(socket_path)) {
+            Ok(_) => debug!("Writing progress events to socket {}", socket_path),
+            Err(e) => warn!(
+                "Failed to connect to progress events socket {}: {}",
+                socket_path, e
+            ),
+        }
+    }
+    if let Some(target) = &args.check_module {
+        #[cfg(feature = "http-modules")]
+        {
+            let report = conformance::check_module(target);
+            println!("{}", report);
+            exit(if report.passed() { 0 } else { 1 });
+        }
+        #[cfg(not(feature = "http-modules"))]
+        {
+            error!(
+                "Cannot check {}: modules are only reachable with the http-modules feature.",
+                target
+            );
+            exit(1);
+        }
+    }
     if args.inputs.len() > 0 {
+        let filters = match (
+            NameFilter::new(&args.include_module, &args.exclude_module),
+            NameFilter::new(&args.include_explorer, &args.exclude_explorer),
+            NameFilter::new(&args.decision_model, &args.exclude_decision_model),
+        ) {
+            (Ok(modules), Ok(explorers), Ok(decision_models)) => SelectionFilters {
+                modules,
+                explorers,
+                decision_models,
+            },
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                error!("Failed to parse the selection filters: {}", e);
+                exit(1);
+            }
+        };
         rayon::ThreadPoolBuilder::new()
             .num_threads(args.parallel_jobs.unwrap_or(1))
             .build_global()
             .unwrap();
-        // let mut hasher = sha3::Sha3_224::new();
-        let mut sorted_inputs = args.inputs.clone();
-        sorted_inputs.sort();
-        // for input in &sorted_inputs {
-        //     if let Ok(f) = fs::read(input) {
-        //         hasher.update(f.as_slice());
-        //     }
-        // }
 
         let run_path_str = &args.run_path.unwrap_or("run".to_string());
         info!("Run directory is {}", &run_path_str);
@@ -161,429 +418,78 @@ fn main() {
         info!("Final output set to {}", output_path_str);
 
         let run_path = Path::new(run_path_str);
-        // let output_path = Path::new(output_path_str);
-        let inputs_path = &run_path.join("inputs");
-        let modules_path = &std::env::current_dir()
-            .expect("Failed to get working directory.")
-            .join("modules");
-        let identified_path = run_path.join("identified");
-        let explored_path = &run_path.join("explored");
-        let reverse_path = &run_path.join("reversed");
-
-        std::fs::create_dir_all(run_path)
-            .expect("Failed to create run path directory during identification.");
-        std::fs::create_dir_all(inputs_path)
-            .expect("Failed to create input directory during identification.");
-        std::fs::create_dir_all(modules_path)
-            .expect("Failed to create imodules directory during identification.");
-        std::fs::create_dir_all(&identified_path)
-            .expect("Failed to create identified directory during identification.");
-        std::fs::create_dir_all(&explored_path)
-            .expect("Failed to create explored directory during identification.");
-        std::fs::create_dir_all(&reverse_path)
-            .expect("Failed to create explored directory during identification.");
-
-        // let mut is_input_incremental = false;
-        // let mut current_input_stamp: Vec<(String, u64)> = sorted_inputs
-        //     .iter()
-        //     .map(|x| {
-        //         (
-        //             x.to_owned(),
-        //             std::fs::metadata(std::path::PathBuf::from(x))
-        //                 .ok()
-        //                 .and_then(|y| y.modified().ok())
-        //                 .and_then(|y| y.duration_since(UNIX_EPOCH).ok())
-        //                 .map(|y| y.as_secs())
-        //                 .unwrap_or(0),
-        //         )
-        //     })
-        //     .collect();
-        // if let Ok(f) = std::fs::read(run_path.join("input_stamp.json")) {
-        //     let previous_input_stream: Vec<(String, u64)> =
-        //         serde_json::from_slice(&f).unwrap_or(Vec::new());
-        //     for x in &current_input_stamp {
-        //         if !previous_input_stream.contains(x) {
-        //             is_input_incremental = false;
-        //             break;
-        //         }
-        //     }
-        //     for x in previous_input_stream {
-        //         if !current_input_stamp.contains(&x) {
-        //             current_input_stamp.push(x);
-        //         }
-        //     }
-        // }
-
-        // if !is_input_incremental {
-        //     debug!("Detected that the inputs are not incremental. Cleaning the workspace before proceeding");
-        //     for dir in vec![inputs_path, &identified_path, &explored_path, &reverse_path] {
-        //         if let Ok(d) = std::fs::read_dir(dir) {
-        //             for x in d {
-        //                 if let Ok(f) = x {
-        //                     if std::fs::remove_file(f.path()).is_err() {
-        //                         warn!("Failed to remove workspace file during incremental reset. Trying to proceed");
-        //                     };
-        //                 }
-        //             }
-        //         };
-        //     }
-        // }
-        // if let Ok(f) = std::fs::File::create(run_path.join("input_stamp.json")) {
-        //     if serde_json::to_writer(f, &current_input_stamp).is_err() {
-        //         warn!("Failed to produce increment stamps. Incremetability might not work")
-        //     };
-        // } else {
-        //     warn!("Failed to create increment stamps. Incremetability might not work")
-        // }
+        let logs_path = &run_path.join("logs");
+        std::fs::create_dir_all(logs_path)
+            .expect("Failed to create logs directory during identification.");
 
         debug!("Initializing modules");
-        // let mut imodules: Vec<Arc<dyn IdentificationModule>> = Vec::new();
-        // let mut emodules: Vec<Arc<dyn ExplorationModule>> = Vec::new();
-        let mut modules =
-            idesyde_orchestration::find_modules_with_config(modules_path, args.jvm_max_heap);
-
-        // add embedded modules
-        modules.push(Arc::new(idesyde_common::make_module()));
-        modules.push(Arc::new(idesyde_bridge_minizinc::make_module()));
-
-        // add externally declared modules
-        // if let Some(external_modules) = args.module {
-        //     for url_str in external_modules {
-        //         if let Ok(parsed_url) = url::Url::parse(url_str.as_str()) {
-        //             modules.push(Arc::new(ExternalServerModule::from(
-        //                 &parsed_url,
-        //                 url_str.as_str(),
-        //             )));
-        //         }
-        //     }
-        // }
-
-        for eximod in &modules {
-            debug!(
-                "Registered module with identifier {}",
-                &eximod.unique_identifier()
-            );
-        }
-
-        let explorers: Vec<Arc<dyn Explorer>> =
-            modules.iter().flat_map(|x| x.explorers()).collect();
-
-        for explorer in &explorers {
-            debug!(
-                "Registered explorer with identifier {}",
-                explorer.unique_identifier()
-            );
-        }
-
-        info!(
-            "A total of {} modules and {} explorers were detected.",
-            modules.len(),
-            explorers.len()
-        );
-
-        // continue
-        debug!("Reading and preparing input files");
-        // add an "Opaque" design model header so that all modules are aware of the input models
-        let design_models: Vec<Arc<dyn DesignModel>> = sorted_inputs
-            .par_iter()
-            .flat_map(|s| OpaqueDesignModel::try_from(Path::new(s)))
-            .map(|s| Arc::new(s))
-            .flat_map(|m| {
-                if m.body_as_string().is_none() {
-                    warn!(
-                        "Failed to read and prepare input {}. Trying to proceed anyway.",
-                        m.category()
-                    );
-                    None
-                } else {
-                    Some(m as Arc<dyn DesignModel>)
-                }
-            })
-            .collect();
-        for m in &design_models {
-            m.write_to_dir(&inputs_path, "input", "Orchestratror");
-        }
-        let pre_identified: Vec<Arc<dyn DecisionModel>> = Vec::new();
-        info!(
-            "Starting identification with {} pre-identified decision models",
-            pre_identified.len()
-        );
-        let identification_time = std::time::Instant::now();
-        let (identified, _) =
-            identification_procedure(&modules, &design_models, &pre_identified, 0);
-        debug!(
-            "Time spent identifying (ms): {}",
-            identification_time.elapsed().as_millis()
-        );
-        info!("Identified {} decision model(s)", identified.len());
-        debug!(
-            "identified categories: {}",
-            identified
-                .iter()
-                .map(|x| x.category())
-                .reduce(|s1, s2| s1.clone() + ", " + &s2)
-                .unwrap_or("None".to_string())
-        );
-        for (i, m) in identified.iter().enumerate() {
-            m.write_to_dir(
-                &identified_path,
-                format!("final_{}", i).as_str(),
-                "Orchestratror",
-            );
-        }
-        // println!(
-        //     "{}",
-        //     identified
-        //         .iter()
-        //         .map(|x| x.body_as_json().unwrap_or("NONE".to_string()))
-        //         .reduce(|s1, s2| s1.clone() + ";\n " + &s2)
-        //         .unwrap_or("None".to_string())
-        // );
-
-        // let dominant = compute_dominant_decision_models(&identified_refs);
-
-        // let dominant_without_biddings = compute_dominant_decision_models(&identified_refs);
-        let bidding_time = std::time::Instant::now();
-        let dominant_partial_identification =
-            idesyde_core::compute_dominant_identification(&identified);
-        let biddings: Vec<(Arc<dyn Explorer>, Arc<dyn DecisionModel>, ExplorationBid)> = explorers
-            .iter()
-            .flat_map(|explorer| {
-                dominant_partial_identification
-                    .iter()
-                    .map(|x| (explorer.clone(), x.clone(), explorer.bid(x.clone())))
-            })
-            .filter(|(_, _, b)| b.can_explore)
-            .filter(|(_, m, _)| {
-                args.decision_model.len() == 0 || args.decision_model.contains(&m.category())
-            })
+        let client_configuration = ModuleClientConfigurationBuilder::default()
+            .connect_timeout(Duration::from_secs(args.module_connect_timeout))
+            .read_timeout(Duration::from_secs(args.module_read_timeout))
+            .max_retries(args.module_retries)
+            .batch_size(args.module_batch_size)
+            .use_cbor(args.module_cbor)
+            .build()
+            .expect("Failed to build the module client configuration. Should never fail.");
+        let modules_paths: Vec<PathBuf> = module_paths::modules_paths(&args.modules_path)
+            .into_iter()
+            .map(|m| m.path)
             .collect();
-        debug!(
-            "Time spent bidding (ms): {}",
-            bidding_time.elapsed().as_millis()
+        let mut modules = idesyde_orchestration::find_modules_in_paths(
+            &modules_paths,
+            args.jvm_max_heap,
+            Some(logs_path),
+            &client_configuration,
         );
-        let dominant_biddings_idx: Vec<usize> = idesyde_core::compute_dominant_biddings(&biddings);
-        info!(
-            "Acquired {} dominant bidding(s) out of {} bidding(s)",
-            dominant_biddings_idx.len(),
-            biddings.len()
-        );
-        // let dominant_bidding_opt =
-        //     idesyde_core::compute_dominant_bidding(biddings.iter().map(|(_, _, b)| b));
-        let total_identifieable_elements: HashSet<String> = design_models
-            .iter()
-            .map(|x| x.elements())
-            .flatten()
-            .collect();
-        if dominant_biddings_idx.len() > 0 {
-            if !dominant_biddings_idx.iter().any(|i| {
-                biddings[*i]
-                    .1
-                    .part()
-                    .is_superset(&total_identifieable_elements)
-            }) {
-                warn!("No dominant bidding captures all partially identified elements. Double-check any final reversed models if any is produced. You can see the non-identified elements by setting using DEBUG verbosity.");
-                debug!(
-                    "Elements that are not covered are: {:?}",
-                    total_identifieable_elements
-                        .difference(
-                            &dominant_biddings_idx
-                                .iter()
-                                .map(|i| biddings[*i].1.part())
-                                .flatten()
-                                .collect()
-                        )
-                        .map(|s| s.to_owned())
-                        .reduce(|s1, s2| format!("{}, {}", s1, s2))
-                        .unwrap_or("{}".to_string())
-                );
-            }
-            match (args.x_total_time_out, args.x_max_solutions) {
-                (Some(t), Some(n)) => info!(
-                    "Starting exploration up to {} total time-out seconds and {} solution(s)",
-                    t, n
-                ),
-                (Some(t), None) => {
-                    info!("Starting exploration up to {} total time-out second(s)", t)
-                }
-                (None, Some(n)) => info!("Starting exploration up to {} solution(s)", n),
-                (None, None) => info!("Starting exploration until completion"),
-            }
-            // let (mut tx, rx) = spmc::channel();
-            // let mut total_reversed = 0;
-            debug!(
-                "Proceeding to explore {}",
-                dominant_biddings_idx
+        // modules found in the directories take precedence over the embedded and declared ones
+        modules.extend(idesyde_orchestration::embedded_modules());
+        modules.extend(idesyde_orchestration::modules_from_urls(
+            &args.module,
+            &client_configuration,
+        ));
+
+        let conf = idesyde_core::ExplorationConfigurationBuilder::default()
+            .max_sols(args.x_max_solutions.unwrap_or(-1))
+            .total_timeout(args.x_total_time_out.unwrap_or(0))
+            .time_resolution(args.x_time_resolution.unwrap_or(0))
+            .memory_resolution(args.x_memory_resolution.unwrap_or(0))
+            .strict(args.strict)
+            .improvement_timeout(args.x_improvement_time_out.unwrap_or(0))
+            .improvement_iterations(args.x_improvement_iterations.unwrap_or(-1))
+            .seed(args.x_seed)
+            .target_objectives(
+                args.x_target_objectives
                     .iter()
-                    .map(|i| biddings[*i].1.category()
-                        + " with "
-                        + &biddings[*i].0.unique_identifier())
-                    .reduce(|a, b| a + " and " + &b)
-                    .unwrap_or("No explorer".to_string())
-            );
-            let mut dominant_sols: Vec<ExplorationSolution> = vec![];
-            let mut num_sols = 0;
-            let exploration_time = std::time::Instant::now();
-            let explorers_and_models: Vec<(Arc<dyn Explorer>, Arc<dyn DecisionModel>)> =
-                dominant_biddings_idx
-                    .iter()
-                    .map(|i| (biddings[*i].0.to_owned(), biddings[*i].1.to_owned()))
-                    .collect();
-            let dominant_biddings: Vec<ExplorationBid> = dominant_biddings_idx
-                .iter()
-                .map(|i| biddings[*i].2.to_owned())
-                .collect();
-            let conf = idesyde_core::ExplorationConfigurationBuilder::default()
-                .max_sols(args.x_max_solutions.unwrap_or(-1))
-                .total_timeout(args.x_total_time_out.unwrap_or(0))
-                .time_resolution(args.x_time_resolution.unwrap_or(0))
-                .memory_resolution(args.x_memory_resolution.unwrap_or(0))
-                .strict(args.strict)
-                .improvement_timeout(args.x_improvement_time_out.unwrap_or(0))
-                .improvement_iterations(args.x_improvement_iterations.unwrap_or(-1))
-                .target_objectives(
-                    args.x_target_objectives
-                        .iter()
-                        .map(|x| x.to_string())
-                        .collect(),
-                )
-                .build()
-                .expect("Failed to build explorer configuration. Should never fail.");
-            for sol in explore_cooperatively(
-                explorers_and_models.as_slice(),
-                dominant_biddings.as_slice(),
-                &HashSet::new(),
-                &conf,
-            ) {
-                // let sol_dominated = dominant_sols.iter().any(|(_, y)| {
-                //     idesyde_core::pareto_dominance_partial_cmp(&sol.1, y) == Some(Ordering::Greater)
-                // });
-                if !dominant_sols.contains(&sol) {
-                    debug!(
-                        "New solution {} with objectives: {}.",
-                        sol.solved.category(),
-                        &sol.objectives
-                            .iter()
-                            .map(|(k, v)| format!("{}: {}", k, v))
-                            .reduce(|s1, s2| format!("{}, {}", s1, s2))
-                            .unwrap_or("None".to_owned())
-                    );
-                    dominant_sols
-                        .retain(|cur_sol| sol.partial_cmp(cur_sol) != Some(Ordering::Less));
-                    dominant_sols.push(sol.clone());
-                    sol.solved.write_to_dir(
-                        &explored_path,
-                        format!("{}_intermediate", num_sols).as_str(),
-                        "Orchestratror",
-                    );
-                    num_sols += 1;
-                    if args.x_max_solutions.unwrap_or(0) > 0
-                        && num_sols >= args.x_max_solutions.unwrap_or(0)
-                    {
-                        break;
-                    }
-                }
-                // imodules.par_iter().for_each(|imodule| {
-                //     for reverse in
-                //         imodule.reverse_identification(&vec![sol.0.clone()], &design_models)
-                //     {
-                //         // let reverse_header = reverse.header();
-                //         reverse.write_to_dir(
-                //             &reverse_path,
-                //             format!("{}_intermediate", num_sols).as_str(),
-                //             "Orchestrator",
-                //         );
-                //         debug!("Reverse identified a {} design model", reverse.category());
-                //     }
-                // });
-            }
-            // sols_found.dedup_by(|(_, a), (_, b)| a == b);
-            // let dominant_sols: Vec<ExplorationSolution> = sols_found
-            //     .iter()
-            //     .filter(|x @ (_, objs)| {
-            //         sols_found.iter().filter(|y| x != y).all(|(_, other_objs)| {
-            //             objs.iter().any(|(k, v)| v < other_objs.get(k).unwrap())
-            //         })
-            //     })
-            //     .map(|x| x.to_owned())
-            //     .collect();
-            debug!(
-                "Time spent exploring (ms): {}",
-                exploration_time.elapsed().as_millis()
-            );
-            info!(
-                "Finished exploration with {} total and {} dominant solution(s)",
-                num_sols,
-                dominant_sols.len()
-            );
-            for (i, sol) in dominant_sols.iter().enumerate() {
-                sol.solved
-                    .write_to_dir(&explored_path, format!("{}", i).as_str(), "Orchestratror");
-                debug!(
-                    "Written dominant {} with objectives: {}",
-                    sol.solved.category(),
-                    sol.objectives
-                        .iter()
-                        .map(|(k, v)| format!("{}: {}", k, v))
-                        .reduce(|s1, s2| format!("{}, {}", s1, s2))
-                        .unwrap_or("None".to_owned())
-                )
-            }
-            let solved_models: Vec<Arc<dyn DecisionModel>> = dominant_sols
-                .iter()
-                .map(|cur_sol| cur_sol.solved.clone())
-                .collect();
-            if !solved_models.is_empty() {
-                info!("Starting reverse identification");
-                let reverse_time = std::time::Instant::now();
-                // note that the reverse identification is NOT done in parallel
-                // this is because the current exploration implementation can stall a bit the rayon
-                // threadpool, so that parallel iteration becomes slower than sequential;
-                // plus, the reverse identification is usually a very small part of the whole process
-                let all_reversed: usize = modules
-                    .par_iter()
-                    .map(|module| {
-                        module
-                            .reverse_identification_rules()
-                            .par_iter()
-                            .map(|rrule| {
-                                let (models, msgs) =
-                                    rrule.reverse_identify(&solved_models, &design_models);
-                                for msg in msgs {
-                                    debug!("{}", msg);
-                                }
-                                let mut n_reversed = 0;
-                                for model in &models {
-                                    model.write_to_dir(
-                                        &reverse_path,
-                                        format!("{}", n_reversed).as_str(),
-                                        module.unique_identifier().as_str(),
-                                    );
-                                    n_reversed += 1;
-                                    debug!(
-                                        "Reverse identified a {} design model",
-                                        model.category()
-                                    );
-                                }
-                                n_reversed
-                            })
-                            .sum::<usize>()
-                    })
-                    .sum();
-                debug!(
-                    "Time spent reversing (ms): {}",
-                    reverse_time.elapsed().as_millis()
-                );
-                info!(
-                    "Finished reverse identification of {} design model(s)",
-                    all_reversed
-                );
-            } else {
-                info!("No solution to reverse identify");
-            }
-        } else {
-            info!("No dominant bidding to start exploration. Finished")
+                    .map(|x| x.to_string())
+                    .collect(),
+            )
+            .build()
+            .expect("Failed to build explorer configuration. Should never fail.");
+        let strategy = ExplorationStrategyBuilder::default()
+            .restart_policy(args.x_restart_policy)
+            .max_concurrent_levels(args.x_max_levels)
+            .solution_validation(args.x_solution_validation)
+            .build()
+            .expect("Failed to build exploration strategy. Should never fail.");
+        let orchestrator = OrchestratorBuilder::default()
+            .modules(modules)
+            .inputs(args.inputs.iter().map(PathBuf::from).collect())
+            .run_path(run_path)
+            .filters(filters)
+            .exploration_configuration(conf)
+            .strategy(strategy)
+            .build()
+            .expect("Failed to build the orchestrator. Should never fail.");
+        if args.explain {
+            println!("{}", orchestrator.explain());
+            exit(0);
         }
+        let report = if args.watch {
+            orchestrator.watch(Duration::from_millis(args.watch_interval.max(1)))
+        } else {
+            orchestrator.run()
+        };
+        exit(report.outcome().exit_code());
     } else {
         info!("At least one input design model is necessary")
     }
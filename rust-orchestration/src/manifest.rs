@@ -0,0 +1,339 @@
+//! The index of the artifacts of a run, kept in `manifest.json` in the run directory.
+//!
+//! Every model the orchestrator writes to the run directory is an [Artifact]: the input design
+//! models, the identified decision models, the explored (solved) decision models and the reverse
+//! identified design models, as well as the exploration traces. Each artifact lists the files it
+//! was written to, one per format, and the artifacts it was derived from, so that one can follow,
+//! for example, a reversed design model back to the solutions and the inputs it came from.
+//!
+//! The orchestrator saves the manifest after every stage, replacing the previous one atomically,
+//! so that readers never see a partially written manifest. [RunManifest::load] reads it back
+//! and the query methods navigate it.
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha3::Digest;
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    DesignModel,
+    DecisionModel,
+    Trace,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Input,
+    Identification,
+    Exploration,
+    ReverseIdentification,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Artifact {
+    /// Unique in the run: the directory and the file name of the artifact without the `body_` prefix
+    /// and the format extension, e.g. `explored/0_AperiodicAsynchronousDataflowToPartitionedMemoryMappableMulticore_Orchestratror`.
+    pub id: String,
+    pub kind: ArtifactKind,
+    pub stage: Stage,
+    pub category: String,
+    /// The SHA3-256 of the contents of the files of the artifact, in hexadecimal.
+    pub hash: String,
+    /// The module or explorer that produced the artifact, when known.
+    pub module: Option<String>,
+    /// The files of the artifact, relative to the run directory.
+    pub files: Vec<String>,
+    /// The ids of the artifacts this one was derived from.
+    pub parents: Vec<String>,
+    /// Whether the artifact was written during exploration and may be superseded by a later solution.
+    #[serde(default)]
+    pub intermediate: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct RunManifest {
+    pub artifacts: Vec<Artifact>,
+}
+
+/// Hashes the contents of `files`, in order. Files that cannot be read are skipped.
+fn hash_files(files: &[PathBuf]) -> String {
+    let mut hasher = sha3::Sha3_256::new();
+    for f in files {
+        if let Ok(bytes) = std::fs::read(f) {
+            hasher.update(&bytes);
+        }
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// The name of the artifact written to `file_name`, which is `body_<prefix>_<category>_<suffix>.<format>`
+/// for models, without the `body_` prefix and the format, which may itself have dots, as in `hsdf.xml`.
+fn artifact_name(file_name: &str) -> &str {
+    let name = file_name.strip_prefix("body_").unwrap_or(file_name);
+    let suffix_start = name.rfind('_').unwrap_or(0);
+    match name[suffix_start..].find('.') {
+        Some(dot) => &name[..suffix_start + dot],
+        None => name,
+    }
+}
+
+fn relative_to(run_path: &Path, file: &Path) -> String {
+    file.strip_prefix(run_path)
+        .unwrap_or(file)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+impl RunManifest {
+    pub fn new() -> RunManifest {
+        RunManifest::default()
+    }
+
+    /// Adds the artifact written to `files`, without module or parents, and returns it so that they can be set.
+    /// Returns `None` if nothing was written.
+    ///
+    /// If an artifact with the same id is already present, e.g. because its files were written again,
+    /// it is replaced.
+    pub fn record(
+        &mut self,
+        run_path: &Path,
+        files: &[PathBuf],
+        kind: ArtifactKind,
+        stage: Stage,
+        category: &str,
+    ) -> Option<&mut Artifact> {
+        let first = files.first()?;
+        let file_name = first
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let name = artifact_name(&file_name);
+        let id = match first.parent().map(|p| relative_to(run_path, p)) {
+            Some(dir) if !dir.is_empty() => format!("{}/{}", dir, name),
+            _ => name.to_string(),
+        };
+        self.artifacts.retain(|a| a.id != id);
+        self.artifacts.push(Artifact {
+            id,
+            kind,
+            stage,
+            category: category.to_owned(),
+            hash: hash_files(files),
+            module: None,
+            files: files.iter().map(|f| relative_to(run_path, f)).collect(),
+            parents: vec![],
+            intermediate: false,
+        });
+        self.artifacts.last_mut()
+    }
+
+    /// Writes the manifest to `manifest.json` in `run_path`, atomically replacing the previous one.
+    pub fn save(&self, run_path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .expect("Failed to serialize the run manifest. Should never fail.");
+        let tmp_path = run_path.join(format!("{}.tmp", MANIFEST_FILE_NAME));
+        std::fs::write(&tmp_path, json)?;
+        std::fs::rename(&tmp_path, run_path.join(MANIFEST_FILE_NAME))
+    }
+
+    /// Reads the manifest of the run in `run_path`.
+    pub fn load(run_path: &Path) -> Result<RunManifest, String> {
+        let path = run_path.join(MANIFEST_FILE_NAME);
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Artifact> {
+        self.artifacts.iter().find(|a| a.id == id)
+    }
+
+    pub fn by_stage(&self, stage: Stage) -> Vec<&Artifact> {
+        self.artifacts.iter().filter(|a| a.stage == stage).collect()
+    }
+
+    pub fn by_kind(&self, kind: ArtifactKind) -> Vec<&Artifact> {
+        self.artifacts.iter().filter(|a| a.kind == kind).collect()
+    }
+
+    pub fn by_category(&self, category: &str) -> Vec<&Artifact> {
+        self.artifacts
+            .iter()
+            .filter(|a| a.category == category)
+            .collect()
+    }
+
+    /// The artifact that has `file`, relative to the run directory, as one of its files.
+    pub fn by_file(&self, file: &str) -> Option<&Artifact> {
+        self.artifacts
+            .iter()
+            .find(|a| a.files.iter().any(|f| f == file))
+    }
+
+    pub fn parents(&self, id: &str) -> Vec<&Artifact> {
+        self.get(id)
+            .map(|a| a.parents.iter().filter_map(|p| self.get(p)).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn children(&self, id: &str) -> Vec<&Artifact> {
+        self.artifacts
+            .iter()
+            .filter(|a| a.parents.iter().any(|p| p == id))
+            .collect()
+    }
+
+    /// All the artifacts that `id` was derived from, directly or not, closest first.
+    pub fn ancestors(&self, id: &str) -> Vec<&Artifact> {
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut ancestors = vec![];
+        let mut frontier: Vec<&str> = vec![id];
+        while !frontier.is_empty() {
+            let mut next = vec![];
+            for current in frontier {
+                for parent in self.parents(current) {
+                    if visited.insert(parent.id.as_str()) {
+                        ancestors.push(parent);
+                        next.push(parent.id.as_str());
+                    }
+                }
+            }
+            frontier = next;
+        }
+        ancestors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records an artifact without writing its files, so its hash is the one of no contents.
+    fn record(
+        manifest: &mut RunManifest,
+        file: &str,
+        stage: Stage,
+        category: &str,
+        parents: &[&str],
+    ) {
+        let run_path = Path::new("/run");
+        let artifact = manifest
+            .record(
+                run_path,
+                &[run_path.join(file)],
+                ArtifactKind::DecisionModel,
+                stage,
+                category,
+            )
+            .expect("A file is given. Should never fail.");
+        artifact.parents = parents.iter().map(|p| p.to_string()).collect();
+    }
+
+    fn ids(artifacts: Vec<&Artifact>) -> Vec<&str> {
+        artifacts.iter().map(|a| a.id.as_str()).collect()
+    }
+
+    fn lineage() -> RunManifest {
+        let mut manifest = RunManifest::new();
+        record(
+            &mut manifest,
+            "inputs/input_0_In_Orchestratror.json",
+            Stage::Input,
+            "In",
+            &[],
+        );
+        record(
+            &mut manifest,
+            "identified/body_final_0_Dm_Orchestratror.hsdf.xml",
+            Stage::Identification,
+            "Dm",
+            &["inputs/input_0_In_Orchestratror"],
+        );
+        record(
+            &mut manifest,
+            "explored/body_0_Dm_Orchestratror.cbor",
+            Stage::Exploration,
+            "Dm",
+            &["identified/final_0_Dm_Orchestratror"],
+        );
+        manifest
+    }
+
+    #[test]
+    fn ids_drop_the_body_prefix_and_the_format() {
+        let manifest = lineage();
+        assert_eq!(
+            ids(manifest.artifacts.iter().collect()),
+            vec![
+                "inputs/input_0_In_Orchestratror",
+                "identified/final_0_Dm_Orchestratror",
+                "explored/0_Dm_Orchestratror",
+            ]
+        );
+        assert_eq!(
+            manifest
+                .by_file("explored/body_0_Dm_Orchestratror.cbor")
+                .map(|a| a.id.as_str()),
+            Some("explored/0_Dm_Orchestratror")
+        );
+    }
+
+    #[test]
+    fn queries_by_stage_and_category() {
+        let manifest = lineage();
+        assert_eq!(
+            ids(manifest.by_stage(Stage::Exploration)),
+            vec!["explored/0_Dm_Orchestratror"]
+        );
+        assert_eq!(manifest.by_category("Dm").len(), 2);
+        assert!(manifest.by_kind(ArtifactKind::Trace).is_empty());
+    }
+
+    #[test]
+    fn lineage_queries() {
+        let manifest = lineage();
+        assert_eq!(
+            ids(manifest.parents("explored/0_Dm_Orchestratror")),
+            vec!["identified/final_0_Dm_Orchestratror"]
+        );
+        assert_eq!(
+            ids(manifest.children("inputs/input_0_In_Orchestratror")),
+            vec!["identified/final_0_Dm_Orchestratror"]
+        );
+        assert_eq!(
+            ids(manifest.ancestors("explored/0_Dm_Orchestratror")),
+            vec![
+                "identified/final_0_Dm_Orchestratror",
+                "inputs/input_0_In_Orchestratror",
+            ]
+        );
+    }
+
+    #[test]
+    fn recording_the_same_id_replaces_the_artifact() {
+        let mut manifest = lineage();
+        record(
+            &mut manifest,
+            "explored/body_0_Dm_Orchestratror.json",
+            Stage::Exploration,
+            "Dm",
+            &[],
+        );
+        assert_eq!(manifest.artifacts.len(), 3);
+        assert!(manifest.parents("explored/0_Dm_Orchestratror").is_empty());
+    }
+}
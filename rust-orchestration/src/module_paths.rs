@@ -0,0 +1,232 @@
+//! Where the orchestrator looks for modules.
+//!
+//! Modules are searched in several directories, from the highest to the lowest priority:
+//!
+//! 1. the directories given in the command line, in the order given;
+//! 2. the directories in the `IDESYDE_MODULES_PATH` environment variable, separated as in `PATH`;
+//! 3. the `modules_paths` of the user configuration file, `idesyde/config.json` in the user
+//!    configuration directory, e.g. `~/.config/idesyde/config.json`, relative to the file;
+//! 4. `modules` in the working directory.
+//!
+//! A directory listed more than once is only searched at its highest priority, and directories that
+//! do not exist are skipped. If modules of different directories have the same unique identifier,
+//! the one of the directory with the highest priority is used, see [crate::find_modules_in_paths].
+use std::{
+    ffi::OsString,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+pub const MODULES_PATH_ENV: &str = "IDESYDE_MODULES_PATH";
+
+pub const CONFIG_FILE_NAME: &str = "config.json";
+
+/// The user-level configuration of the orchestrator, in [CONFIG_FILE_NAME].
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct UserConfiguration {
+    #[serde(default)]
+    pub modules_paths: Vec<PathBuf>,
+}
+
+impl UserConfiguration {
+    pub fn load(path: &Path) -> Result<UserConfiguration, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("failed to parse {}: {}", path.display(), e))
+    }
+}
+
+/// The path of the user configuration file, whether it exists or not.
+///
+/// It is in `$XDG_CONFIG_HOME`, or `%APPDATA%` on Windows, falling back to `~/.config`.
+pub fn user_configuration_path() -> Option<PathBuf> {
+    let non_empty = |var: &str| std::env::var_os(var).filter(|v| !v.is_empty());
+    non_empty("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| non_empty("APPDATA").map(PathBuf::from))
+        .or_else(|| {
+            non_empty("HOME")
+                .or_else(|| non_empty("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".config"))
+        })
+        .map(|dir| dir.join("idesyde").join(CONFIG_FILE_NAME))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModulesPathSource {
+    CommandLine,
+    Environment,
+    ConfigurationFile,
+    WorkingDirectory,
+}
+
+impl Display for ModulesPathSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModulesPathSource::CommandLine => write!(f, "command line"),
+            ModulesPathSource::Environment => write!(f, "{}", MODULES_PATH_ENV),
+            ModulesPathSource::ConfigurationFile => write!(f, "configuration file"),
+            ModulesPathSource::WorkingDirectory => write!(f, "working directory"),
+        }
+    }
+}
+
+/// A directory to search for modules and where it was configured.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModulesPath {
+    pub path: PathBuf,
+    pub source: ModulesPathSource,
+}
+
+/// Orders the directories of every source by priority, dropping the repeated ones.
+///
+/// `env_paths` is the value of [MODULES_PATH_ENV] and `configuration` the configuration file with its contents.
+/// Relative paths are taken from `working_dir`, or from the directory of the file for the configuration file.
+pub fn resolve_modules_paths(
+    cli_paths: &[PathBuf],
+    env_paths: Option<OsString>,
+    configuration: Option<(&Path, &UserConfiguration)>,
+    working_dir: &Path,
+) -> Vec<ModulesPath> {
+    let config_dir = configuration
+        .and_then(|(file, _)| file.parent())
+        .unwrap_or(working_dir);
+    let candidates = cli_paths
+        .iter()
+        .map(|p| (working_dir.join(p), ModulesPathSource::CommandLine))
+        .chain(
+            env_paths
+                .iter()
+                .flat_map(std::env::split_paths)
+                .filter(|p| !p.as_os_str().is_empty())
+                .map(|p| (working_dir.join(p), ModulesPathSource::Environment)),
+        )
+        .chain(configuration.into_iter().flat_map(|(_, c)| {
+            c.modules_paths
+                .iter()
+                .map(|p| (config_dir.join(p), ModulesPathSource::ConfigurationFile))
+        }))
+        .chain(std::iter::once((
+            working_dir.join("modules"),
+            ModulesPathSource::WorkingDirectory,
+        )));
+    let mut resolved: Vec<ModulesPath> = vec![];
+    for (path, source) in candidates {
+        let path = path.canonicalize().unwrap_or(path);
+        if !resolved.iter().any(|m| m.path == path) {
+            resolved.push(ModulesPath { path, source });
+        }
+    }
+    resolved
+}
+
+/// The directories to search for modules, from the command line, the environment and the user
+/// configuration file, by priority. Only the existing directories are returned.
+pub fn modules_paths(cli_paths: &[PathBuf]) -> Vec<ModulesPath> {
+    let configuration = user_configuration_path()
+        .filter(|p| p.is_file())
+        .and_then(|p| match UserConfiguration::load(&p) {
+            Ok(c) => Some((p, c)),
+            Err(e) => {
+                warn!("Ignoring the user configuration file: {}", e);
+                None
+            }
+        });
+    let working_dir = std::env::current_dir().unwrap_or_default();
+    resolve_modules_paths(
+        cli_paths,
+        std::env::var_os(MODULES_PATH_ENV),
+        configuration.as_ref().map(|(p, c)| (p.as_path(), c)),
+        &working_dir,
+    )
+    .into_iter()
+    .filter(|m| {
+        let exists = m.path.is_dir();
+        if !exists && m.source != ModulesPathSource::WorkingDirectory {
+            warn!(
+                "Modules directory {} from the {} does not exist. Ignoring it.",
+                m.path.display(),
+                m.source
+            );
+        }
+        exists
+    })
+    .inspect(|m| {
+        debug!(
+            "Searching modules in {} from the {}",
+            m.path.display(),
+            m.source
+        )
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_are_ordered_by_source_without_repetitions() {
+        let working_dir = Path::new("/nonexistent/work");
+        let configuration = UserConfiguration {
+            modules_paths: vec![
+                PathBuf::from("config_modules"),
+                PathBuf::from("/nonexistent/shared"),
+            ],
+        };
+        let env_paths = std::env::join_paths(["/nonexistent/shared", "/nonexistent/env"])
+            .expect("Test paths have no separators. Should never fail.");
+        let resolved = resolve_modules_paths(
+            &[PathBuf::from("cli"), PathBuf::from("/nonexistent/env")],
+            Some(env_paths),
+            Some((Path::new("/nonexistent/config/config.json"), &configuration)),
+            working_dir,
+        );
+        assert_eq!(
+            resolved,
+            vec![
+                ModulesPath {
+                    path: PathBuf::from("/nonexistent/work/cli"),
+                    source: ModulesPathSource::CommandLine
+                },
+                ModulesPath {
+                    path: PathBuf::from("/nonexistent/env"),
+                    source: ModulesPathSource::CommandLine
+                },
+                ModulesPath {
+                    path: PathBuf::from("/nonexistent/shared"),
+                    source: ModulesPathSource::Environment
+                },
+                ModulesPath {
+                    path: PathBuf::from("/nonexistent/config/config_modules"),
+                    source: ModulesPathSource::ConfigurationFile
+                },
+                ModulesPath {
+                    path: PathBuf::from("/nonexistent/work/modules"),
+                    source: ModulesPathSource::WorkingDirectory
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_environment_entries_are_skipped() {
+        let resolved = resolve_modules_paths(
+            &[],
+            Some(OsString::new()),
+            None,
+            Path::new("/nonexistent/work"),
+        );
+        assert_eq!(
+            resolved,
+            vec![ModulesPath {
+                path: PathBuf::from("/nonexistent/work/modules"),
+                source: ModulesPathSource::WorkingDirectory
+            }]
+        );
+    }
+}
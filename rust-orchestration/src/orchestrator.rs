@@ -0,0 +1,1111 @@
+//! The whole orchestration as a library, for tools that embed IDeSyDe.
+//!
+//! An [Orchestrator] runs the same stages as the orchestrator binary: it reads the inputs, identifies
+//! decision models with the modules, lets the explorers bid for them, explores the dominant biddings and
+//! reverse identifies the dominant solutions, writing everything to its run directory. Hooks are called
+//! for every identified decision model, new solution and reversed design model as they appear.
+//!
+//! ```no_run
+//! use idesyde_orchestration::orchestrator::OrchestratorBuilder;
+//!
+//! let orchestrator = OrchestratorBuilder::default()
+//!     .modules(idesyde_orchestration::embedded_modules())
+//!     .inputs(vec!["model.fiodl".into()])
+//!     .run_path("run")
+//!     .on_solution(|s| println!("New {} solution: {:?}", s.category(), s.objectives))
+//!     .build()
+//!     .expect("Failed to build the orchestrator.");
+//! let report = orchestrator.run();
+//! println!("Finished with outcome {}", report.outcome());
+//! ```
+//!
+//! The orchestrator uses the global rayon thread pool and the process-wide [events], [interrupt] and
+//! [quarantine] state, like the binary. Setting them up, e.g. the number of threads or the
+//! interruption handler, is left to the embedding tool.
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering as AtomicOrdering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use derive_builder::Builder;
+use idesyde_core::{
+    DecisionModel, DesignModel, ExplorationBid, ExplorationConfiguration,
+    ExplorationConfigurationBuilder, ExplorationSolution, Explorer, Module, OpaqueDecisionModel,
+    OpaqueDesignModel,
+};
+use rayon::prelude::*;
+use sha3::Digest;
+use tracing::{debug, debug_span, info, warn};
+
+use crate::{
+    events::{self, ProgressEvent},
+    explain::{self, Bidding, ExplainReport},
+    exploration::{
+        explore_composed, independent_subproblems, ComposedSolution, ExplorationStrategy,
+    },
+    filters::SelectionFilters,
+    identification::{identification_procedure_with_origins, IdentificationOrigins},
+    inputs, interrupt,
+    manifest::{ArtifactKind, RunManifest, Stage},
+    quarantine,
+    report::RunReport,
+    trace::ExplorationTrace,
+    watch::{self, CancelOnChange, InputSnapshot},
+};
+
+pub type IdentifiedHook = Arc<dyn Fn(&Arc<dyn DecisionModel>) + Send + Sync>;
+
+pub type SolutionHook = Arc<dyn Fn(&ComposedSolution) + Send + Sync>;
+
+pub type ReversedHook = Arc<dyn Fn(&Arc<dyn DesignModel>) + Send + Sync>;
+
+fn default_exploration_configuration() -> ExplorationConfiguration {
+    ExplorationConfigurationBuilder::default()
+        .total_timeout(0)
+        .improvement_timeout(0)
+        .time_resolution(0)
+        .memory_resolution(0)
+        .build()
+        .expect("Failed to build default explorer configuration. Should never fail.")
+}
+
+/// Runs the identification, exploration and reverse identification of its inputs with its modules.
+#[derive(Clone, Builder)]
+pub struct Orchestrator {
+    /// The modules, by priority: only the first of the modules with the same unique identifier is used.
+    #[builder(default)]
+    pub modules: Vec<Arc<dyn Module>>,
+    /// The input files. Decision models are pre-identified and the rest are design models, see
+    /// [inputs::read_decision_models].
+    #[builder(default)]
+    pub inputs: Vec<PathBuf>,
+    /// Design models given in memory, in addition to the inputs.
+    #[builder(default)]
+    pub design_models: Vec<Arc<dyn DesignModel>>,
+    /// Decision models given in memory, pre-identified like the decision model inputs.
+    #[builder(default)]
+    pub decision_models: Vec<Arc<dyn DecisionModel>>,
+    #[builder(default = "PathBuf::from(\"run\")", setter(into))]
+    pub run_path: PathBuf,
+    #[builder(default)]
+    pub filters: SelectionFilters,
+    #[builder(default = "default_exploration_configuration()")]
+    pub exploration_configuration: ExplorationConfiguration,
+    #[builder(default)]
+    pub strategy: ExplorationStrategy,
+    #[builder(default, setter(custom))]
+    pub identified_hooks: Vec<IdentifiedHook>,
+    #[builder(default, setter(custom))]
+    pub solution_hooks: Vec<SolutionHook>,
+    #[builder(default, setter(custom))]
+    pub reversed_hooks: Vec<ReversedHook>,
+}
+
+impl OrchestratorBuilder {
+    /// Calls `hook` with every identified decision model that passes the decision model filters.
+    pub fn on_identified(
+        &mut self,
+        hook: impl Fn(&Arc<dyn DecisionModel>) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.identified_hooks
+            .get_or_insert_with(Vec::new)
+            .push(Arc::new(hook));
+        self
+    }
+
+    /// Calls `hook` with every solution that is not dominated by the solutions found before it.
+    pub fn on_solution(
+        &mut self,
+        hook: impl Fn(&ComposedSolution) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.solution_hooks
+            .get_or_insert_with(Vec::new)
+            .push(Arc::new(hook));
+        self
+    }
+
+    /// Calls `hook` with every reversed design model. It may be called from several threads at once.
+    pub fn on_reversed(
+        &mut self,
+        hook: impl Fn(&Arc<dyn DesignModel>) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.reversed_hooks
+            .get_or_insert_with(Vec::new)
+            .push(Arc::new(hook));
+        self
+    }
+}
+
+/// The modules and the explorers of a run.
+type ModulesAndExplorers = (Vec<Arc<dyn Module>>, Vec<Arc<dyn Explorer>>);
+
+/// What a watch cycle keeps for the next one.
+#[derive(Default)]
+struct WatchCycle {
+    /// The elements of each input, design or decision model.
+    input_elements: HashMap<PathBuf, HashSet<String>>,
+    identified: Vec<Arc<dyn DecisionModel>>,
+    identified_origins: IdentificationOrigins,
+    /// The explored decision models, see [explored_key].
+    explored: HashSet<(String, Vec<u8>, Vec<u8>)>,
+    dominant: Vec<ComposedSolution>,
+}
+
+/// The identified decision models and the biddings for them, before exploration.
+struct IdentificationAndBidding {
+    design_models: Vec<Arc<dyn DesignModel>>,
+    all_identified: Vec<Arc<dyn DecisionModel>>,
+    dominant_partial_identification: Vec<Arc<dyn DecisionModel>>,
+    all_biddings: Vec<Bidding>,
+    biddings: Vec<Bidding>,
+    dominant_biddings_idx: Vec<usize>,
+    total_identifieable_elements: HashSet<String>,
+    /// Identified decision models by category and hash, to link the explored ones to them.
+    identified_artifacts: HashMap<(String, Vec<u8>), String>,
+    manifest: RunManifest,
+}
+
+/// The file prefix of the `part`-th part of the `index`-th solution, which is just the index
+/// when the solution was not combined from independent sub-problems.
+fn composed_prefix(index: usize, part: usize, num_parts: usize, suffix: &str) -> String {
+    if num_parts > 1 {
+        format!("{}_part{}{}", index, part, suffix)
+    } else {
+        format!("{}{}", index, suffix)
+    }
+}
+
+/// The category, hash and body hash of an explored decision model, which tells it apart from a
+/// decision model with the same part but different contents.
+fn explored_key(m: &dyn DecisionModel) -> (String, Vec<u8>, Vec<u8>) {
+    let body_hash = m
+        .body_as_json()
+        .map(|s| s.into_bytes())
+        .or_else(|| m.body_as_cbor())
+        .or_else(|| m.body_as_msgpack())
+        .map(|bytes| sha3::Sha3_256::digest(bytes).to_vec())
+        .unwrap_or_default();
+    (m.category(), m.global_sha2_hash(), body_hash)
+}
+
+/// Saves the run manifest, warning instead of failing, since the run itself is not affected.
+fn save_manifest(manifest: &RunManifest, run_path: &Path) {
+    if let Err(e) = manifest.save(run_path) {
+        warn!("Failed to save the run manifest: {}", e);
+    }
+}
+
+impl Orchestrator {
+    /// Runs all the stages once and returns the report, which is also saved in the run directory.
+    pub fn run(&self) -> RunReport {
+        self.reset_process_state();
+        self.create_run_directories();
+        let (modules, explorers) = self.prepare_modules();
+        self.run_cycle(&modules, &explorers, &WatchCycle::default(), &[])
+            .0
+    }
+
+    /// Runs the identification and bidding only and explains what the exploration would do, see [explain::explain].
+    pub fn explain(&self) -> ExplainReport {
+        self.reset_process_state();
+        self.create_run_directories();
+        let (modules, explorers) = self.prepare_modules();
+        let bidding = self.identify_and_bid(
+            &modules,
+            &explorers,
+            &WatchCycle::default(),
+            &[],
+            &mut WatchCycle::default(),
+            &mut RunReport::new(),
+        );
+        events::emit(ProgressEvent::Finished);
+        explain::explain(
+            &bidding.all_identified,
+            &bidding.dominant_partial_identification,
+            &bidding.all_biddings,
+            &self.filters.decision_models,
+            &bidding.total_identifieable_elements,
+        )
+    }
+
+    /// Runs like [Orchestrator::run], then starts over whenever an input changes, until interrupted.
+    ///
+    /// The inputs are checked for changes every `poll`, see [watch]. A change cancels the ongoing
+    /// exploration. The next cycle reuses the decision models identified from the inputs that did not
+    /// change, and the dominant solutions of the decision models that did not change as warm start.
+    /// Warm start solutions are only hints for the explorers and are not results of the new cycle.
+    ///
+    /// The orchestrator cannot tell the elements of a design model file, so only the decision models
+    /// identified from decision model inputs, or from design models given to the builder, are reused.
+    /// Returns the report of the last cycle.
+    pub fn watch(&self, poll: Duration) -> RunReport {
+        self.reset_process_state();
+        self.create_run_directories();
+        let (modules, explorers) = self.prepare_modules();
+        let input_paths = self.input_paths();
+        let mut snapshot = InputSnapshot::take(&input_paths);
+        let mut changed_inputs: Vec<PathBuf> = vec![];
+        let mut last = WatchCycle::default();
+        let mut cycle = 0u64;
+        loop {
+            interrupt::reset_cancellation();
+            let cancel_on_change = CancelOnChange::start(snapshot.clone(), poll);
+            info!("Starting watch cycle {}", cycle);
+            events::emit(ProgressEvent::WatchCycleStarted {
+                cycle,
+                changed: changed_inputs
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect(),
+            });
+            let (report, current) = self.run_cycle(&modules, &explorers, &last, &changed_inputs);
+            if interrupt::is_interrupted() {
+                return report;
+            }
+            drop(cancel_on_change);
+            last = current;
+            info!("Watching {} input(s) for changes", input_paths.len());
+            match watch::wait_for_change(&snapshot, poll) {
+                Some(next) => {
+                    changed_inputs = next.changed(&snapshot);
+                    snapshot = next;
+                    cycle += 1;
+                }
+                None => return report,
+            }
+        }
+    }
+
+    /// The inputs in a fixed order, so that runs are reproducible.
+    fn input_paths(&self) -> Vec<PathBuf> {
+        let mut sorted_inputs = self.inputs.clone();
+        sorted_inputs.sort();
+        sorted_inputs
+    }
+
+    /// Clears what a previous run left in the process-wide state, i.e. the quarantine of the modules of
+    /// this orchestrator and the cancellation of the exploration, see [quarantine] and [interrupt].
+    fn reset_process_state(&self) {
+        let module_ids: Vec<String> = self.modules.iter().map(|m| m.unique_identifier()).collect();
+        quarantine::release(&module_ids);
+        interrupt::reset_cancellation();
+    }
+
+    fn create_run_directories(&self) {
+        let run_path = self.run_path.as_path();
+        std::fs::create_dir_all(run_path)
+            .expect("Failed to create run path directory during identification.");
+        std::fs::create_dir_all(run_path.join("inputs"))
+            .expect("Failed to create input directory during identification.");
+        std::fs::create_dir_all(run_path.join("identified"))
+            .expect("Failed to create identified directory during identification.");
+        std::fs::create_dir_all(run_path.join("explored"))
+            .expect("Failed to create explored directory during identification.");
+        std::fs::create_dir_all(run_path.join("reversed"))
+            .expect("Failed to create explored directory during identification.");
+        std::fs::create_dir_all(run_path.join("logs"))
+            .expect("Failed to create logs directory during identification.");
+    }
+
+    /// The modules and explorers that pass the filters, in a fixed order.
+    fn prepare_modules(&self) -> ModulesAndExplorers {
+        let mut modules = crate::deduplicate_modules(self.modules.clone());
+        // modules are found in directory order, which depends on the platform
+        modules.sort_by_key(|m| m.unique_identifier());
+        modules.retain(|m| {
+            let kept = self.filters.modules.accepts(&m.unique_identifier());
+            if !kept {
+                info!(
+                    "Module {} is excluded by the module filters",
+                    m.unique_identifier()
+                );
+            }
+            kept
+        });
+
+        for eximod in &modules {
+            debug!(
+                "Registered module with identifier {}",
+                &eximod.unique_identifier()
+            );
+        }
+
+        let explorers: Vec<Arc<dyn Explorer>> = modules
+            .iter()
+            .flat_map(|x| x.explorers())
+            .filter(|e| {
+                let kept = self.filters.explorers.accepts(&e.unique_identifier());
+                if !kept {
+                    info!(
+                        "Explorer {} is excluded by the explorer filters",
+                        e.unique_identifier()
+                    );
+                }
+                kept
+            })
+            .collect();
+
+        for explorer in &explorers {
+            debug!(
+                "Registered explorer with identifier {}",
+                explorer.unique_identifier()
+            );
+        }
+
+        info!(
+            "A total of {} modules and {} explorers were detected.",
+            modules.len(),
+            explorers.len()
+        );
+        (modules, explorers)
+    }
+
+    /// Runs all the stages, reusing what is still valid of the `last` watch cycle given the inputs that changed.
+    fn run_cycle(
+        &self,
+        modules: &[Arc<dyn Module>],
+        explorers: &[Arc<dyn Explorer>],
+        last: &WatchCycle,
+        changed_inputs: &[PathBuf],
+    ) -> (RunReport, WatchCycle) {
+        let run_start = std::time::Instant::now();
+        let mut report = RunReport::new();
+        let mut current = WatchCycle::default();
+        let bidding = self.identify_and_bid(
+            modules,
+            explorers,
+            last,
+            changed_inputs,
+            &mut current,
+            &mut report,
+        );
+        if !bidding.dominant_biddings_idx.is_empty() {
+            self.explore_and_reverse(modules, bidding, last, &mut current, &mut report);
+        } else {
+            info!("No dominant bidding to start exploration. Finished")
+        }
+        events::emit(ProgressEvent::Finished);
+        report.interrupted = interrupt::is_interrupted();
+        report.quarantined_modules = quarantine::quarantined_modules();
+        if !report.quarantined_modules.is_empty() {
+            warn!(
+                "Module(s) {} failed and were not used for the rest of the run. See the run report for the reasons.",
+                report
+                    .quarantined_modules
+                    .iter()
+                    .map(|q| q.module.to_owned())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            );
+        }
+        report.elapsed_ms = run_start.elapsed().as_millis() as u64;
+        let outcome = report.conclude();
+        info!(
+            "Finished with outcome {} (exit code {})",
+            outcome,
+            outcome.exit_code()
+        );
+        if let Err(e) = report.save(&self.run_path) {
+            warn!("Failed to save the run report: {}", e);
+        }
+        (report, current)
+    }
+
+    fn identify_and_bid(
+        &self,
+        modules: &[Arc<dyn Module>],
+        explorers: &[Arc<dyn Explorer>],
+        last: &WatchCycle,
+        changed_inputs: &[PathBuf],
+        current: &mut WatchCycle,
+        report: &mut RunReport,
+    ) -> IdentificationAndBidding {
+        let run_path = self.run_path.as_path();
+        let inputs_path = &run_path.join("inputs");
+        let identified_path = run_path.join("identified");
+        debug!("Reading and preparing input files");
+        // decision models given as inputs are pre-identified, the other inputs are design models
+        let (pre_identified_inputs, design_inputs) =
+            inputs::read_decision_models(&self.input_paths());
+        let pre_identified: Vec<Arc<dyn DecisionModel>> = pre_identified_inputs
+            .iter()
+            .map(|(_, m)| m.clone())
+            .chain(self.decision_models.iter().cloned())
+            .collect();
+        for m in &pre_identified {
+            debug!("Read a {} decision model from the inputs", m.category());
+            if m.downcast_ref::<OpaqueDecisionModel>().is_some() && m.part().is_empty() {
+                warn!(
+                    "The input {} decision model is not known to the orchestrator and has no part. Trying to proceed anyway.",
+                    m.category()
+                );
+            }
+        }
+        // add an "Opaque" design model header so that all modules are aware of the input models
+        let design_inputs_and_models: Vec<(PathBuf, Arc<dyn DesignModel>)> = design_inputs
+            .par_iter()
+            .flat_map(|p| OpaqueDesignModel::try_from(p.as_path()).map(|m| (p, Arc::new(m))))
+            .flat_map(|(p, m)| {
+                if m.body_as_string().is_none() {
+                    warn!(
+                        "Failed to read and prepare input {}. Trying to proceed anyway.",
+                        m.category()
+                    );
+                    None
+                } else {
+                    Some((p.to_owned(), m as Arc<dyn DesignModel>))
+                }
+            })
+            .collect();
+        let design_models: Vec<Arc<dyn DesignModel>> = design_inputs_and_models
+            .iter()
+            .map(|(_, m)| m.clone())
+            .chain(self.design_models.iter().cloned())
+            .collect();
+        current.input_elements = design_inputs_and_models
+            .iter()
+            .map(|(p, m)| (p.to_owned(), m.elements()))
+            .chain(
+                pre_identified_inputs
+                    .iter()
+                    .map(|(p, m)| (p.to_owned(), m.part())),
+            )
+            .collect();
+        report.design_models = design_models.len();
+        report.pre_identified = pre_identified.len();
+        let mut manifest = RunManifest::new();
+        let mut input_artifacts: Vec<(String, HashSet<String>)> = vec![];
+        // inputs of the same category are told apart by their index
+        for (i, m) in design_models.iter().enumerate() {
+            let files = m.write_files_to_dir(
+                inputs_path,
+                format!("input_{}", i).as_str(),
+                "Orchestratror",
+            );
+            if let Some(artifact) = manifest.record(
+                run_path,
+                &files,
+                ArtifactKind::DesignModel,
+                Stage::Input,
+                &m.category(),
+            ) {
+                input_artifacts.push((artifact.id.to_owned(), m.elements()));
+            }
+        }
+        for (i, m) in pre_identified.iter().enumerate() {
+            let files = m.write_files_to_dir(
+                inputs_path,
+                format!("input_{}", design_models.len() + i).as_str(),
+                "Orchestratror",
+            );
+            if let Some(artifact) = manifest.record(
+                run_path,
+                &files,
+                ArtifactKind::DecisionModel,
+                Stage::Input,
+                &m.category(),
+            ) {
+                input_artifacts.push((artifact.id.to_owned(), m.part()));
+            }
+        }
+        save_manifest(&manifest, run_path);
+        // the decision models identified in the last watch cycle only from elements of unchanged inputs are still valid
+        let changed_elements: HashSet<String> = changed_inputs
+            .iter()
+            .flat_map(|p| {
+                last.input_elements
+                    .get(p)
+                    .into_iter()
+                    .chain(current.input_elements.get(p))
+            })
+            .flatten()
+            .cloned()
+            .collect();
+        let input_elements: HashSet<String> = current
+            .input_elements
+            .values()
+            .flatten()
+            .cloned()
+            .chain(self.design_models.iter().flat_map(|m| m.elements()))
+            .chain(self.decision_models.iter().flat_map(|m| m.part()))
+            .collect();
+        let reused: Vec<Arc<dyn DecisionModel>> = last
+            .identified
+            .iter()
+            .filter(|m| {
+                let part = m.part();
+                !part.is_empty()
+                    && part.is_subset(&input_elements)
+                    && part.is_disjoint(&changed_elements)
+                    && !pre_identified.iter().any(|p| {
+                        p.category() == m.category() && p.global_sha2_hash() == m.global_sha2_hash()
+                    })
+            })
+            .cloned()
+            .collect();
+        if !reused.is_empty() {
+            info!(
+                "Reusing {} decision model(s) identified from unchanged inputs",
+                reused.len()
+            );
+        }
+        report.reused_identified = reused.len();
+        info!(
+            "Starting identification with {} pre-identified decision models",
+            pre_identified.len()
+        );
+        let identification_time = std::time::Instant::now();
+        let (all_identified, _, mut origins) = identification_procedure_with_origins(
+            &modules.to_vec(),
+            &design_models,
+            &pre_identified
+                .iter()
+                .chain(reused.iter())
+                .cloned()
+                .collect(),
+            0,
+        );
+        // the reused decision models keep the module that identified them in the last cycle
+        for m in &reused {
+            let key = (m.category(), m.global_sha2_hash());
+            if let Some(module) = last.identified_origins.get(&key) {
+                origins.entry(key).or_insert(module.to_owned());
+            }
+        }
+        current.identified = all_identified.clone();
+        // the identification order depends on thread scheduling, so it is fixed here
+        // to make bidding and exploration reproducible
+        let mut identified: Vec<Arc<dyn DecisionModel>> = all_identified
+            .iter()
+            .filter(|m| self.filters.decision_models.accepts(&m.category()))
+            .cloned()
+            .collect();
+        identified.sort_by_key(|m| (m.category(), m.global_sha2_hash()));
+        if identified.len() < all_identified.len() {
+            info!(
+                "{} identified decision model(s) excluded by the decision model filters",
+                all_identified.len() - identified.len()
+            );
+        }
+        report.identification_ms = identification_time.elapsed().as_millis() as u64;
+        debug!(
+            elapsed_ms = report.identification_ms,
+            "Time spent identifying"
+        );
+        info!("Identified {} decision model(s)", identified.len());
+        report.identified = identified.len();
+        debug!(
+            "identified categories: {}",
+            identified
+                .iter()
+                .map(|x| x.category())
+                .reduce(|s1, s2| s1.clone() + ", " + &s2)
+                .unwrap_or("None".to_string())
+        );
+        events::emit(ProgressEvent::IdentificationFinished {
+            identified: identified.len(),
+            categories: identified.iter().map(|x| x.category()).collect(),
+        });
+        for m in &identified {
+            for hook in &self.identified_hooks {
+                hook(m);
+            }
+        }
+        let mut identified_artifacts: HashMap<(String, Vec<u8>), String> = HashMap::new();
+        for (i, m) in identified.iter().enumerate() {
+            let files = m.write_files_to_dir(
+                &identified_path,
+                format!("final_{}", i).as_str(),
+                "Orchestratror",
+            );
+            let part = m.part();
+            if let Some(artifact) = manifest.record(
+                run_path,
+                &files,
+                ArtifactKind::DecisionModel,
+                Stage::Identification,
+                &m.category(),
+            ) {
+                artifact.module = origins.get(&(m.category(), m.global_sha2_hash())).cloned();
+                artifact.parents = input_artifacts
+                    .iter()
+                    .filter(|(_, elements)| !elements.is_disjoint(&part))
+                    .map(|(id, _)| id.to_owned())
+                    .collect();
+                identified_artifacts
+                    .insert((m.category(), m.global_sha2_hash()), artifact.id.to_owned());
+            }
+        }
+        save_manifest(&manifest, run_path);
+        current.identified_origins = origins;
+        let bidding_time = std::time::Instant::now();
+        let dominant_partial_identification =
+            idesyde_core::compute_dominant_identification(&identified);
+        let all_biddings: Vec<Bidding> = explorers
+            .iter()
+            .flat_map(|explorer| {
+                dominant_partial_identification.iter().map(|x| {
+                    let bid = debug_span!(
+                        "bid",
+                        explorer = explorer.unique_identifier(),
+                        decision_model = x.category()
+                    )
+                    .in_scope(|| explorer.bid(x.clone()));
+                    (explorer.clone(), x.clone(), bid)
+                })
+            })
+            .collect();
+        let biddings: Vec<_> = all_biddings
+            .iter()
+            .filter(|(_, _, b)| b.can_explore)
+            .cloned()
+            .collect();
+        debug!(
+            elapsed_ms = bidding_time.elapsed().as_millis() as u64,
+            "Time spent bidding"
+        );
+        let dominant_biddings_idx: Vec<usize> = idesyde_core::compute_dominant_biddings(&biddings);
+        info!(
+            "Acquired {} dominant bidding(s) out of {} bidding(s)",
+            dominant_biddings_idx.len(),
+            biddings.len()
+        );
+        for (i, (explorer, m, bid)) in biddings.iter().enumerate() {
+            events::emit(ProgressEvent::Bid {
+                explorer: explorer.unique_identifier(),
+                decision_model: m.category(),
+                can_explore: bid.can_explore,
+                is_exact: bid.is_exact,
+                competitiveness: bid.competitiveness,
+                target_objectives: bid.target_objectives.iter().cloned().collect(),
+                dominant: dominant_biddings_idx.contains(&i),
+            });
+        }
+        let total_identifieable_elements: HashSet<String> =
+            design_models.iter().flat_map(|x| x.elements()).collect();
+        let covered_elements: HashSet<String> = dominant_biddings_idx
+            .iter()
+            .flat_map(|i| biddings[*i].1.part())
+            .collect();
+        report.biddings = all_biddings.len();
+        report.explorable_biddings = biddings.len();
+        report.dominant_biddings = dominant_biddings_idx.len();
+        report.uncovered_elements = total_identifieable_elements
+            .difference(&covered_elements)
+            .cloned()
+            .collect();
+        report.uncovered_elements.sort();
+        IdentificationAndBidding {
+            design_models,
+            all_identified,
+            dominant_partial_identification,
+            all_biddings,
+            biddings,
+            dominant_biddings_idx,
+            total_identifieable_elements,
+            identified_artifacts,
+            manifest,
+        }
+    }
+
+    fn explore_and_reverse(
+        &self,
+        modules: &[Arc<dyn Module>],
+        bidding: IdentificationAndBidding,
+        last: &WatchCycle,
+        current: &mut WatchCycle,
+        report: &mut RunReport,
+    ) {
+        let IdentificationAndBidding {
+            design_models,
+            biddings,
+            dominant_biddings_idx,
+            total_identifieable_elements,
+            identified_artifacts,
+            mut manifest,
+            ..
+        } = bidding;
+        let run_path = self.run_path.as_path();
+        let explored_path = &run_path.join("explored");
+        let reverse_path = &run_path.join("reversed");
+        let conf = &self.exploration_configuration;
+        if !dominant_biddings_idx.iter().any(|i| {
+            biddings[*i]
+                .1
+                .part()
+                .is_superset(&total_identifieable_elements)
+        }) {
+            warn!("No dominant bidding captures all partially identified elements. Double-check any final reversed models if any is produced. You can see the non-identified elements by setting using DEBUG verbosity.");
+            debug!(
+                "Elements that are not covered are: {:?}",
+                total_identifieable_elements
+                    .difference(
+                        &dominant_biddings_idx
+                            .iter()
+                            .flat_map(|i| biddings[*i].1.part())
+                            .collect()
+                    )
+                    .map(|s| s.to_owned())
+                    .reduce(|s1, s2| format!("{}, {}", s1, s2))
+                    .unwrap_or("{}".to_string())
+            );
+        }
+        match (conf.total_timeout, conf.max_sols) {
+            (t, n) if t > 0 && n > 0 => info!(
+                "Starting exploration up to {} total time-out seconds and {} solution(s)",
+                t, n
+            ),
+            (t, _) if t > 0 => info!("Starting exploration up to {} total time-out second(s)", t),
+            (_, n) if n > 0 => info!("Starting exploration up to {} solution(s)", n),
+            _ => info!("Starting exploration until completion"),
+        }
+        debug!(
+            "Proceeding to explore {}",
+            dominant_biddings_idx
+                .iter()
+                .map(|i| biddings[*i].1.category() + " with " + &biddings[*i].0.unique_identifier())
+                .reduce(|a, b| a + " and " + &b)
+                .unwrap_or("No explorer".to_string())
+        );
+        let mut num_sols = 0;
+        let exploration_time = std::time::Instant::now();
+        let explorers_and_models: Vec<(Arc<dyn Explorer>, Arc<dyn DecisionModel>)> =
+            dominant_biddings_idx
+                .iter()
+                .map(|i| (biddings[*i].0.to_owned(), biddings[*i].1.to_owned()))
+                .collect();
+        // the dominant solutions of the last watch cycle are still valid if the decision models
+        // of their category are explored again unchanged, but they are only hints for the explorers
+        current.explored = explorers_and_models
+            .iter()
+            .map(|(_, m)| explored_key(m.as_ref()))
+            .collect();
+        let is_unchanged = |s: &ExplorationSolution| {
+            let explored_before: Vec<&(String, Vec<u8>, Vec<u8>)> = last
+                .explored
+                .iter()
+                .filter(|(category, _, _)| *category == s.solved.category())
+                .collect();
+            !explored_before.is_empty()
+                && explored_before
+                    .iter()
+                    .all(|k| current.explored.contains(*k))
+        };
+        let warm_start: HashSet<ExplorationSolution> = last
+            .dominant
+            .iter()
+            .flat_map(|s| s.parts.iter())
+            .filter(|p| is_unchanged(p))
+            .cloned()
+            .collect();
+        // the still valid solutions are kept for the warm start of the next cycle
+        let still_valid: Vec<ComposedSolution> = last
+            .dominant
+            .iter()
+            .filter(|s| s.parts.iter().all(is_unchanged))
+            .cloned()
+            .collect();
+        let mut dominant_sols: Vec<ComposedSolution> = vec![];
+        if !warm_start.is_empty() {
+            info!(
+                "Warm starting the exploration with {} solution(s) of the last cycle",
+                warm_start.len()
+            );
+        }
+        report.warm_start_solutions = warm_start.len();
+        let dominant_biddings: Vec<ExplorationBid> = dominant_biddings_idx
+            .iter()
+            .map(|i| biddings[*i].2.to_owned())
+            .collect();
+        events::emit(ProgressEvent::ExplorationStarted {
+            explorers: explorers_and_models
+                .iter()
+                .map(|(e, _)| e.unique_identifier())
+                .collect(),
+            decision_models: explorers_and_models
+                .iter()
+                .map(|(_, m)| m.category())
+                .collect(),
+        });
+        let subproblems = independent_subproblems(explorers_and_models.as_slice());
+        if subproblems.len() > 1 {
+            info!(
+                "Detected {} independent sub-problems. Exploring them in parallel and combining their solutions",
+                subproblems.len()
+            );
+        }
+        let trace = ExplorationTrace::new();
+        let mut exploration = explore_composed(
+            explorers_and_models.as_slice(),
+            dominant_biddings.as_slice(),
+            &warm_start,
+            conf,
+            &self.strategy,
+            &trace,
+        );
+        for sol in exploration.by_ref() {
+            if !dominant_sols.contains(&sol) {
+                debug!(
+                    "New solution {} with objectives: {}.",
+                    sol.category(),
+                    &sol.objectives
+                        .iter()
+                        .map(|(k, v)| format!("{}: {}", k, v))
+                        .reduce(|s1, s2| format!("{}, {}", s1, s2))
+                        .unwrap_or("None".to_owned())
+                );
+                dominant_sols.retain(|cur_sol| sol.partial_cmp(cur_sol) != Some(Ordering::Less));
+                dominant_sols.push(sol.clone());
+                events::emit(ProgressEvent::NewSolution {
+                    index: num_sols as u64,
+                    decision_model: sol.category(),
+                    objectives: sol.objectives.clone(),
+                });
+                for hook in &self.solution_hooks {
+                    hook(&sol);
+                }
+                for (j, part) in sol.parts.iter().enumerate() {
+                    let files = part.solved.write_files_to_dir(
+                        explored_path,
+                        composed_prefix(num_sols as usize, j, sol.parts.len(), "_intermediate")
+                            .as_str(),
+                        "Orchestratror",
+                    );
+                    if let Some(artifact) = manifest.record(
+                        run_path,
+                        &files,
+                        ArtifactKind::DecisionModel,
+                        Stage::Exploration,
+                        &part.solved.category(),
+                    ) {
+                        artifact.module =
+                            trace.explorer_of(&part.solved.category(), &part.objectives);
+                        artifact.parents = identified_artifacts
+                            .get(&(part.solved.category(), part.solved.global_sha2_hash()))
+                            .into_iter()
+                            .cloned()
+                            .collect();
+                        artifact.intermediate = true;
+                    }
+                }
+                save_manifest(&manifest, run_path);
+                num_sols += 1;
+                if conf.max_sols > 0 && num_sols >= conf.max_sols {
+                    break;
+                }
+            }
+        }
+        if interrupt::is_interrupted() {
+            info!("Exploration interrupted. Writing and reverse identifying the solutions found so far");
+        }
+        report.exploration_ms = exploration_time.elapsed().as_millis() as u64;
+        debug!(elapsed_ms = report.exploration_ms, "Time spent exploring");
+        info!(
+            "Finished exploration with {} total and {} dominant solution(s)",
+            num_sols,
+            dominant_sols.len()
+        );
+        report.termination = exploration.termination();
+        report.solutions = num_sols as u64;
+        report.dominant_solutions = dominant_sols.len();
+        current.dominant = dominant_sols.clone();
+        current.dominant.extend(still_valid.into_iter().filter(|s| {
+            !dominant_sols.contains(s)
+                && dominant_sols
+                    .iter()
+                    .all(|d| d.partial_cmp(s) != Some(Ordering::Less))
+        }));
+        debug!(termination = ?report.termination, "Exploration stopped");
+        let trace_report = trace.report();
+        report.time_to_first_solution_ms = trace_report
+            .time_to_first_solution_ms
+            .values()
+            .min()
+            .copied();
+        for (explorer, ms) in &trace_report.time_to_first_solution_ms {
+            info!(
+                explorer = explorer.as_str(),
+                elapsed_ms = ms,
+                "Time to first solution of {}",
+                explorer
+            );
+        }
+        let trace_files = [
+            run_path.join("exploration_trace.csv"),
+            run_path.join("exploration_trace.json"),
+        ];
+        let hypervolume_file = run_path.join("exploration_hypervolume.csv");
+        match trace_report
+            .write_csv(&trace_files[0])
+            .and_then(|_| trace_report.write_json(&trace_files[1]))
+            .and_then(|_| trace_report.write_hypervolume_csv(&hypervolume_file))
+        {
+            Ok(_) => {
+                manifest.record(
+                    run_path,
+                    &trace_files,
+                    ArtifactKind::Trace,
+                    Stage::Exploration,
+                    "ExplorationTrace",
+                );
+                manifest.record(
+                    run_path,
+                    &[hypervolume_file],
+                    ArtifactKind::Trace,
+                    Stage::Exploration,
+                    "HypervolumeCurve",
+                );
+            }
+            Err(e) => warn!("Failed to write the exploration trace: {}", e),
+        }
+        events::emit(ProgressEvent::ExplorationFinished {
+            total_solutions: num_sols as u64,
+            dominant_solutions: dominant_sols.len(),
+        });
+        // the explored artifacts with the design elements they solve, to link the reversed models to them
+        let mut explored_artifacts: Vec<(String, HashSet<String>)> = vec![];
+        for (i, sol) in dominant_sols.iter().enumerate() {
+            for (j, part) in sol.parts.iter().enumerate() {
+                let files = part.solved.write_files_to_dir(
+                    explored_path,
+                    composed_prefix(i, j, sol.parts.len(), "").as_str(),
+                    "Orchestratror",
+                );
+                if let Some(artifact) = manifest.record(
+                    run_path,
+                    &files,
+                    ArtifactKind::DecisionModel,
+                    Stage::Exploration,
+                    &part.solved.category(),
+                ) {
+                    artifact.module = trace.explorer_of(&part.solved.category(), &part.objectives);
+                    artifact.parents = identified_artifacts
+                        .get(&(part.solved.category(), part.solved.global_sha2_hash()))
+                        .into_iter()
+                        .cloned()
+                        .collect();
+                    explored_artifacts.push((artifact.id.to_owned(), part.solved.part()));
+                }
+            }
+            debug!(
+                "Written dominant {} with objectives: {}",
+                sol.category(),
+                sol.objectives
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", k, v))
+                    .reduce(|s1, s2| format!("{}, {}", s1, s2))
+                    .unwrap_or("None".to_owned())
+            )
+        }
+        let solved_models: Vec<Arc<dyn DecisionModel>> = dominant_sols
+            .iter()
+            .flat_map(|cur_sol| cur_sol.parts.iter().map(|p| p.solved.clone()))
+            .collect();
+        save_manifest(&manifest, run_path);
+        if !solved_models.is_empty() {
+            info!("Starting reverse identification");
+            let reverse_time = std::time::Instant::now();
+            // the modules and their rules reverse in parallel, each rule once with all the solutions;
+            // the files of every reversed model are kept with its module and elements, and are
+            // numbered across all rules so that no two reversed models share a name
+            let reversed_count = AtomicUsize::new(0);
+            let all_reversed: Vec<(String, String, Vec<PathBuf>, HashSet<String>)> = modules
+                .par_iter()
+                .flat_map(|module| {
+                    module
+                        .reverse_identification_rules()
+                        .par_iter()
+                        .enumerate()
+                        .flat_map(|(rule, rrule)| {
+                            let _rule_span = debug_span!(
+                                "reverse_rule",
+                                module = module.unique_identifier(),
+                                rule
+                            )
+                            .entered();
+                            let mut reversed = vec![];
+                            let (models, msgs) =
+                                rrule.reverse_identify(&solved_models, &design_models);
+                            for msg in msgs {
+                                debug!("{}", msg);
+                            }
+                            for model in &models {
+                                let files = model.write_files_to_dir(
+                                    reverse_path,
+                                    format!(
+                                        "{}",
+                                        reversed_count.fetch_add(1, AtomicOrdering::SeqCst)
+                                    )
+                                    .as_str(),
+                                    module.unique_identifier().as_str(),
+                                );
+                                for p in &files {
+                                    events::emit(ProgressEvent::ReverseIdentified {
+                                        module: module.unique_identifier(),
+                                        design_model: model.category(),
+                                        path: p.display().to_string(),
+                                    });
+                                }
+                                for hook in &self.reversed_hooks {
+                                    hook(model);
+                                }
+                                debug!("Reverse identified a {} design model", model.category());
+                                reversed.push((
+                                    module.unique_identifier(),
+                                    model.category(),
+                                    files,
+                                    model.elements(),
+                                ));
+                            }
+                            reversed
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            // a reversed model comes from the explored models that solve some of its elements,
+            // or from all of them if it shares none, e.g. because its elements are not known
+            for (module, category, files, elements) in &all_reversed {
+                let sources: Vec<String> = explored_artifacts
+                    .iter()
+                    .filter(|(_, part)| !part.is_disjoint(elements))
+                    .map(|(id, _)| id.to_owned())
+                    .collect();
+                if let Some(artifact) = manifest.record(
+                    run_path,
+                    files,
+                    ArtifactKind::DesignModel,
+                    Stage::ReverseIdentification,
+                    category,
+                ) {
+                    artifact.module = Some(module.to_owned());
+                    artifact.parents = if sources.is_empty() {
+                        explored_artifacts
+                            .iter()
+                            .map(|(id, _)| id.to_owned())
+                            .collect()
+                    } else {
+                        sources
+                    };
+                }
+            }
+            save_manifest(&manifest, run_path);
+            debug!(
+                elapsed_ms = reverse_time.elapsed().as_millis() as u64,
+                "Time spent reversing"
+            );
+            info!(
+                "Finished reverse identification of {} design model(s)",
+                all_reversed.len()
+            );
+            report.reversed = all_reversed.len();
+        } else {
+            info!("No solution to reverse identify");
+        }
+    }
+}
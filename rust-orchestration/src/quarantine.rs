@@ -0,0 +1,540 @@
+//! Isolation of misbehaving modules.
+//!
+//! A module that panics in one of its identification rules, explorers, exploration iterators or
+//! reverse identification rules would otherwise take the whole orchestration down, e.g. by unwinding
+//! through the rayon thread pool. The orchestrator wraps every module with [guard_module], which
+//! catches these panics and quarantines the module: for the rest of the run, it behaves as if it had
+//! no rules and its explorers cannot explore anything, so that the healthy modules keep working.
+//! The Java modules turn the exceptions thrown by their rules and explorers into panics, so that
+//! they are quarantined in the same way.
+//!
+//! The quarantined modules are logged, emitted as `module_quarantined` progress events and listed in
+//! the run report.
+//!
+//! Like the progress events, the quarantine is process-wide. Every run of the orchestrator releases its
+//! modules with [release] first, so that a failure of a module in one run does not exclude it from the next.
+//! A module whose identifier cannot be read is quarantined under the name of where it was loaded from,
+//! see [unknown_module], until modules are loaded from there again.
+use std::{
+    any::Any,
+    collections::HashSet,
+    fmt::Display,
+    panic::AssertUnwindSafe,
+    sync::{Arc, Mutex},
+};
+
+use idesyde_core::{
+    DecisionModel, DesignModel, ExplorationBid, ExplorationConfiguration, ExplorationSolution,
+    Explorer, IdentificationResult, IdentificationRuleLike, Module, ReverseIdentificationResult,
+    ReverseIdentificationRuleLike,
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use url::Url;
+
+use crate::events::{self, ProgressEvent};
+
+/// Where a module failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultStage {
+    Loading,
+    Identification,
+    Bidding,
+    Exploration,
+    ReverseIdentification,
+}
+
+impl Display for FaultStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FaultStage::Loading => write!(f, "loading"),
+            FaultStage::Identification => write!(f, "identification"),
+            FaultStage::Bidding => write!(f, "bidding"),
+            FaultStage::Exploration => write!(f, "exploration"),
+            FaultStage::ReverseIdentification => write!(f, "reverse_identification"),
+        }
+    }
+}
+
+/// A module taken out of the run and its first failure.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantinedModule {
+    pub module: String,
+    pub stage: FaultStage,
+    pub reason: String,
+}
+
+static QUARANTINE: Mutex<Vec<QuarantinedModule>> = Mutex::new(Vec::new());
+
+/// Takes `module` out of the run. Only its first failure is kept.
+pub fn quarantine(module: &str, stage: FaultStage, reason: &str) {
+    if let Ok(mut quarantined) = QUARANTINE.lock() {
+        if quarantined.iter().any(|q| q.module == module) {
+            return;
+        }
+        error!(
+            "Module {} failed during {}: {}. Quarantining it for the rest of the run.",
+            module, stage, reason
+        );
+        quarantined.push(QuarantinedModule {
+            module: module.to_owned(),
+            stage,
+            reason: reason.to_owned(),
+        });
+    }
+    events::emit(ProgressEvent::ModuleQuarantined {
+        module: module.to_owned(),
+        stage: stage.to_string(),
+        reason: reason.to_owned(),
+    });
+}
+
+/// Takes `modules` out of quarantine.
+pub fn release(modules: &[String]) {
+    if let Ok(mut quarantined) = QUARANTINE.lock() {
+        quarantined.retain(|q| !modules.contains(&q.module));
+    }
+}
+
+pub fn is_quarantined(module: &str) -> bool {
+    QUARANTINE
+        .lock()
+        .map(|quarantined| quarantined.iter().any(|q| q.module == module))
+        .unwrap_or(false)
+}
+
+pub fn quarantined_modules() -> Vec<QuarantinedModule> {
+    QUARANTINE
+        .lock()
+        .map(|quarantined| quarantined.clone())
+        .unwrap_or_default()
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Runs `f` on behalf of `module`, quarantining the module if `f` panics.
+///
+/// Returns `None` if `f` panicked or if the module was already quarantined, in which case `f` is not run.
+pub fn guard<T>(module: &str, stage: FaultStage, f: impl FnOnce() -> T) -> Option<T> {
+    if is_quarantined(module) {
+        return None;
+    }
+    match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(t) => Some(t),
+        Err(payload) => {
+            quarantine(module, stage, &panic_message(payload.as_ref()));
+            None
+        }
+    }
+}
+
+/// The name under which the modules loaded from `origin`, e.g. a directory or a URL, are quarantined
+/// when their identifier cannot be read.
+pub fn unknown_module(origin: &str) -> String {
+    format!("<unknown from {}>", origin)
+}
+
+/// Wraps `module`, loaded from `origin`, so that its failures are isolated, or returns `None` if its
+/// identifier cannot even be read.
+pub fn guard_module(module: Arc<dyn Module>, origin: &str) -> Option<Arc<dyn Module>> {
+    match std::panic::catch_unwind(AssertUnwindSafe(|| module.unique_identifier())) {
+        Ok(id) => Some(Arc::new(GuardedModule { id, inner: module })),
+        Err(payload) => {
+            quarantine(
+                &unknown_module(origin),
+                FaultStage::Loading,
+                &panic_message(payload.as_ref()),
+            );
+            None
+        }
+    }
+}
+
+/// Wraps all the `modules` loaded from `origin` as in [guard_module], releasing first the modules
+/// whose identifier could not be read the last time modules were loaded from there.
+pub fn guard_modules(modules: Vec<Arc<dyn Module>>, origin: &str) -> Vec<Arc<dyn Module>> {
+    release(&[unknown_module(origin)]);
+    modules
+        .into_iter()
+        .filter_map(|m| guard_module(m, origin))
+        .collect()
+}
+
+struct GuardedModule {
+    id: String,
+    inner: Arc<dyn Module>,
+}
+
+impl Module for GuardedModule {
+    fn unique_identifier(&self) -> String {
+        self.id.to_owned()
+    }
+
+    fn location_url(&self) -> Option<Url> {
+        guard(&self.id, FaultStage::Loading, || self.inner.location_url()).flatten()
+    }
+
+    fn explorers(&self) -> Vec<Arc<dyn Explorer>> {
+        guard(&self.id, FaultStage::Loading, || self.inner.explorers())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|explorer| {
+                guard(&self.id, FaultStage::Loading, || {
+                    explorer.unique_identifier()
+                })
+                .map(|id| {
+                    Arc::new(GuardedExplorer {
+                        module: self.id.to_owned(),
+                        id,
+                        inner: explorer,
+                    }) as Arc<dyn Explorer>
+                })
+            })
+            .collect()
+    }
+
+    fn identification_rules(&self) -> Vec<Arc<dyn IdentificationRuleLike>> {
+        guard(&self.id, FaultStage::Loading, || {
+            self.inner.identification_rules()
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .map(|rule| {
+            Arc::new(GuardedIdentificationRule {
+                module: self.id.to_owned(),
+                inner: rule,
+            }) as Arc<dyn IdentificationRuleLike>
+        })
+        .collect()
+    }
+
+    fn reverse_identification_rules(&self) -> Vec<Arc<dyn ReverseIdentificationRuleLike>> {
+        guard(&self.id, FaultStage::Loading, || {
+            self.inner.reverse_identification_rules()
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .map(|rule| {
+            Arc::new(GuardedReverseIdentificationRule {
+                module: self.id.to_owned(),
+                inner: rule,
+            }) as Arc<dyn ReverseIdentificationRuleLike>
+        })
+        .collect()
+    }
+
+    fn identification_step(
+        &self,
+        decision_models: &Vec<Arc<dyn DecisionModel>>,
+        design_models: &Vec<Arc<dyn DesignModel>>,
+    ) -> IdentificationResult {
+        guard(&self.id, FaultStage::Identification, || {
+            self.inner
+                .identification_step(decision_models, design_models)
+        })
+        .unwrap_or_default()
+    }
+
+    fn reverse_identification(
+        &self,
+        solved_decision_model: &Vec<Arc<dyn DecisionModel>>,
+        design_model: &Vec<Arc<dyn DesignModel>>,
+    ) -> Vec<Arc<dyn DesignModel>> {
+        guard(&self.id, FaultStage::ReverseIdentification, || {
+            self.inner
+                .reverse_identification(solved_decision_model, design_model)
+        })
+        .unwrap_or_default()
+    }
+}
+
+struct GuardedIdentificationRule {
+    module: String,
+    inner: Arc<dyn IdentificationRuleLike>,
+}
+
+impl IdentificationRuleLike for GuardedIdentificationRule {
+    fn identify(
+        &self,
+        design_models: &[Arc<dyn DesignModel>],
+        decision_models: &[Arc<dyn DecisionModel>],
+    ) -> IdentificationResult {
+        guard(&self.module, FaultStage::Identification, || {
+            self.inner.identify(design_models, decision_models)
+        })
+        .unwrap_or_default()
+    }
+
+    fn uses_design_models(&self) -> bool {
+        guard(&self.module, FaultStage::Identification, || {
+            self.inner.uses_design_models()
+        })
+        .unwrap_or(true)
+    }
+
+    fn uses_decision_models(&self) -> bool {
+        guard(&self.module, FaultStage::Identification, || {
+            self.inner.uses_decision_models()
+        })
+        .unwrap_or(true)
+    }
+
+    fn uses_specific_decision_models(&self) -> Option<Vec<String>> {
+        guard(&self.module, FaultStage::Identification, || {
+            self.inner.uses_specific_decision_models()
+        })
+        .flatten()
+    }
+}
+
+struct GuardedReverseIdentificationRule {
+    module: String,
+    inner: Arc<dyn ReverseIdentificationRuleLike>,
+}
+
+impl ReverseIdentificationRuleLike for GuardedReverseIdentificationRule {
+    fn reverse_identify(
+        &self,
+        decision_models: &[Arc<dyn DecisionModel>],
+        design_models: &[Arc<dyn DesignModel>],
+    ) -> ReverseIdentificationResult {
+        guard(&self.module, FaultStage::ReverseIdentification, || {
+            self.inner.reverse_identify(decision_models, design_models)
+        })
+        .unwrap_or_default()
+    }
+}
+
+struct GuardedExplorer {
+    module: String,
+    id: String,
+    inner: Arc<dyn Explorer>,
+}
+
+impl Explorer for GuardedExplorer {
+    fn unique_identifier(&self) -> String {
+        self.id.to_owned()
+    }
+
+    fn location_url(&self) -> Option<Url> {
+        guard(&self.module, FaultStage::Loading, || {
+            self.inner.location_url()
+        })
+        .flatten()
+    }
+
+    fn bid(&self, m: Arc<dyn DecisionModel>) -> ExplorationBid {
+        guard(&self.module, FaultStage::Bidding, || self.inner.bid(m))
+            .unwrap_or_else(ExplorationBid::impossible)
+    }
+
+    fn explore(
+        &self,
+        m: Arc<dyn DecisionModel>,
+        currrent_solutions: &HashSet<ExplorationSolution>,
+        exploration_configuration: ExplorationConfiguration,
+    ) -> Arc<Mutex<dyn Iterator<Item = ExplorationSolution> + Send + Sync>> {
+        match guard(&self.module, FaultStage::Exploration, || {
+            self.inner
+                .explore(m, currrent_solutions, exploration_configuration)
+        }) {
+            Some(iter) => Arc::new(Mutex::new(GuardedIterator {
+                module: self.module.to_owned(),
+                inner: iter,
+            })),
+            None => Arc::new(Mutex::new(std::iter::empty())),
+        }
+    }
+}
+
+/// Stops at the first panic of the wrapped iterator, which may run in the rayon thread pool,
+/// or once the wrapped iterator is poisoned by a panic elsewhere.
+struct GuardedIterator {
+    module: String,
+    inner: Arc<Mutex<dyn Iterator<Item = ExplorationSolution> + Send + Sync>>,
+}
+
+impl Iterator for GuardedIterator {
+    type Item = ExplorationSolution;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        guard(&self.module, FaultStage::Exploration, || {
+            match self.inner.lock() {
+                Ok(mut iter) => iter.next(),
+                Err(_) => {
+                    quarantine(
+                        &self.module,
+                        FaultStage::Exploration,
+                        "its exploration iterator was poisoned by an earlier panic",
+                    );
+                    None
+                }
+            }
+        })
+        .flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use idesyde_core::ExplorationBidBuilder;
+
+    use super::*;
+
+    /// A module that panics at the given point, named after it.
+    struct Faulty(&'static str);
+
+    impl Module for Faulty {
+        fn unique_identifier(&self) -> String {
+            if self.0 == "identifier" {
+                panic!("no identifier");
+            }
+            format!("Faulty_{}", self.0)
+        }
+
+        fn explorers(&self) -> Vec<Arc<dyn Explorer>> {
+            vec![Arc::new(FaultyExplorer(self.0))]
+        }
+
+        fn identification_rules(&self) -> Vec<Arc<dyn IdentificationRuleLike>> {
+            vec![Arc::new(FaultyRule)]
+        }
+    }
+
+    struct FaultyRule;
+
+    impl IdentificationRuleLike for FaultyRule {
+        fn identify(
+            &self,
+            _design_models: &[Arc<dyn DesignModel>],
+            _decision_models: &[Arc<dyn DecisionModel>],
+        ) -> IdentificationResult {
+            panic!("rule failed")
+        }
+    }
+
+    struct FaultyExplorer(&'static str);
+
+    impl Explorer for FaultyExplorer {
+        fn unique_identifier(&self) -> String {
+            format!("FaultyExplorer_{}", self.0)
+        }
+
+        fn bid(&self, _m: Arc<dyn DecisionModel>) -> ExplorationBid {
+            if self.0 == "bid" {
+                panic!("bid failed");
+            }
+            ExplorationBidBuilder::default()
+                .can_explore(true)
+                .build()
+                .expect("Bid of the tests is complete. Should never fail.")
+        }
+
+        fn explore(
+            &self,
+            _m: Arc<dyn DecisionModel>,
+            _currrent_solutions: &HashSet<ExplorationSolution>,
+            _exploration_configuration: ExplorationConfiguration,
+        ) -> Arc<Mutex<dyn Iterator<Item = ExplorationSolution> + Send + Sync>> {
+            let iter: Arc<Mutex<dyn Iterator<Item = ExplorationSolution> + Send + Sync>> =
+                Arc::new(Mutex::new(std::iter::from_fn(|| panic!("next failed"))));
+            if self.0 == "poisoned" {
+                let poisoning = iter.clone();
+                let _ = std::thread::spawn(move || {
+                    let _lock = poisoning.lock();
+                    panic!("poisoning the iterator");
+                })
+                .join();
+            }
+            iter
+        }
+    }
+
+    fn guarded(kind: &'static str) -> Arc<dyn Module> {
+        guard_module(Arc::new(Faulty(kind)), "the tests")
+            .expect("The faulty module has an identifier. Should never fail.")
+    }
+
+    fn model() -> Arc<dyn DecisionModel> {
+        Arc::new(idesyde_core::OpaqueDecisionModel {
+            category: "Model".to_string(),
+            part: HashSet::from(["a".to_string()]),
+            body_json: None,
+            body_msgpack: None,
+            body_cbor: None,
+        })
+    }
+
+    fn configuration() -> ExplorationConfiguration {
+        idesyde_core::ExplorationConfigurationBuilder::default()
+            .total_timeout(0)
+            .improvement_timeout(0)
+            .time_resolution(0)
+            .memory_resolution(0)
+            .build()
+            .expect("Configuration of the tests is complete. Should never fail.")
+    }
+
+    fn stage_of(module: &str) -> Option<FaultStage> {
+        quarantined_modules()
+            .into_iter()
+            .find(|q| q.module == module)
+            .map(|q| q.stage)
+    }
+
+    #[test]
+    fn panicking_rule_quarantines_its_module() {
+        let module = guarded("rule");
+        let rules = module.identification_rules();
+        let (identified, _) = rules[0].identify(&[], &[]);
+        assert!(identified.is_empty());
+        assert_eq!(stage_of("Faulty_rule"), Some(FaultStage::Identification));
+        assert!(module.identification_rules().is_empty());
+        release(&["Faulty_rule".to_string()]);
+        assert!(!is_quarantined("Faulty_rule"));
+    }
+
+    #[test]
+    fn panicking_bid_is_impossible() {
+        let module = guarded("bid");
+        let bid = module.explorers()[0].bid(model());
+        assert!(!bid.can_explore);
+        assert_eq!(stage_of("Faulty_bid"), Some(FaultStage::Bidding));
+    }
+
+    #[test]
+    fn panicking_iterator_stops_the_exploration() {
+        let module = guarded("next");
+        let iter = module.explorers()[0].explore(model(), &HashSet::new(), configuration());
+        assert!(iter.lock().map(|mut i| i.next()).unwrap_or(None).is_none());
+        assert_eq!(stage_of("Faulty_next"), Some(FaultStage::Exploration));
+    }
+
+    #[test]
+    fn poisoned_iterator_stops_the_exploration() {
+        let module = guarded("poisoned");
+        let iter = module.explorers()[0].explore(model(), &HashSet::new(), configuration());
+        assert!(iter.lock().map(|mut i| i.next()).unwrap_or(None).is_none());
+        assert_eq!(stage_of("Faulty_poisoned"), Some(FaultStage::Exploration));
+        assert!(quarantined_modules()
+            .iter()
+            .any(|q| q.module == "Faulty_poisoned" && q.reason.contains("poisoned")));
+    }
+
+    #[test]
+    fn unreadable_identifier_is_quarantined_until_loaded_again() {
+        let unknown = unknown_module("the identifier test");
+        assert!(guard_module(Arc::new(Faulty("identifier")), "the identifier test").is_none());
+        assert_eq!(stage_of(&unknown), Some(FaultStage::Loading));
+        let loaded = guard_modules(vec![Arc::new(Faulty("reloaded"))], "the identifier test");
+        assert_eq!(loaded.len(), 1);
+        assert!(!is_quarantined(&unknown));
+    }
+}
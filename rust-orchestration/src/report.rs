@@ -0,0 +1,223 @@
+//! The outcome of a run, written to `report.json` in the run directory, and the exit code of the orchestrator.
+//!
+//! The exit codes are:
+//!
+//! | Code | Outcome | Meaning |
+//! |------|---------|---------|
+//! | 0    | `success` | Solutions were found for all the identifiable design elements. |
+//! | 1    | | The orchestrator could not run, e.g. because of an invalid argument. |
+//! | 2    | `partial_coverage` | Solutions were found, but some design elements are not covered by any explored decision model. |
+//! | 3    | `no_identification` | No decision model was identified from the inputs. |
+//! | 4    | `no_explorer` | Decision models were identified, but no explorer can explore them. |
+//! | 5    | `infeasible` | The exploration was `complete` without any solution, so there is none. |
+//! | 6    | `timed_out` | The exploration timed out before finding any solution. |
+//! | 7    | `invalid_solution` | An explorer returned an invalid solution and `--x-solution-validation` is `abort`. |
+//! | 8    | `no_solution` | The explorers stopped without any solution and without proving that there is none, e.g. because they failed or were quarantined. |
+//! | 130  | `interrupted` | The run was interrupted, see [crate::interrupt]. |
+//!
+//! A run that times out after finding solutions is a success, and its report says that the
+//! exploration `timed_out` instead of being `complete`.
+//!
+//! In watch mode, the report is rewritten at the end of every cycle, and the exit code is the one of
+//! the last cycle.
+use std::{fmt::Display, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{exploration::ExplorationTermination, interrupt, quarantine::QuarantinedModule};
+
+pub const REPORT_FILE_NAME: &str = "report.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunOutcome {
+    Success,
+    PartialCoverage,
+    NoIdentification,
+    NoExplorer,
+    Infeasible,
+    TimedOut,
+    InvalidSolution,
+    NoSolution,
+    Interrupted,
+}
+
+impl RunOutcome {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RunOutcome::Success => 0,
+            RunOutcome::PartialCoverage => 2,
+            RunOutcome::NoIdentification => 3,
+            RunOutcome::NoExplorer => 4,
+            RunOutcome::Infeasible => 5,
+            RunOutcome::TimedOut => 6,
+            RunOutcome::InvalidSolution => 7,
+            RunOutcome::NoSolution => 8,
+            RunOutcome::Interrupted => interrupt::INTERRUPTED_EXIT_CODE,
+        }
+    }
+}
+
+impl Display for RunOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunOutcome::Success => write!(f, "success"),
+            RunOutcome::PartialCoverage => write!(f, "partial_coverage"),
+            RunOutcome::NoIdentification => write!(f, "no_identification"),
+            RunOutcome::NoExplorer => write!(f, "no_explorer"),
+            RunOutcome::Infeasible => write!(f, "infeasible"),
+            RunOutcome::TimedOut => write!(f, "timed_out"),
+            RunOutcome::InvalidSolution => write!(f, "invalid_solution"),
+            RunOutcome::NoSolution => write!(f, "no_solution"),
+            RunOutcome::Interrupted => write!(f, "interrupted"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct RunReport {
+    pub design_models: usize,
+    pub pre_identified: usize,
+    /// The decision models identified in the previous watch cycle from inputs that did not change.
+    #[serde(default)]
+    pub reused_identified: usize,
+    pub identified: usize,
+    #[serde(default)]
+    pub identification_ms: u64,
+    /// All the biddings of the explorers for the dominant identified decision models.
+    #[serde(default)]
+    pub biddings: usize,
+    /// The biddings of the explorers that can explore the decision model.
+    #[serde(default)]
+    pub explorable_biddings: usize,
+    pub dominant_biddings: usize,
+    /// The identifiable design elements that are not part of any dominant bidding, sorted.
+    pub uncovered_elements: Vec<String>,
+    pub termination: Option<ExplorationTermination>,
+    #[serde(default)]
+    pub exploration_ms: u64,
+    /// The time to the first solution of the fastest explorer, see [crate::trace].
+    #[serde(default)]
+    pub time_to_first_solution_ms: Option<u64>,
+    pub solutions: u64,
+    /// The solutions of the previous watch cycle given to the explorers as warm start, which do not count as found.
+    #[serde(default)]
+    pub warm_start_solutions: usize,
+    pub dominant_solutions: usize,
+    pub reversed: usize,
+    pub interrupted: bool,
+    /// The modules that failed and were not used for the rest of the run, see [crate::quarantine].
+    #[serde(default)]
+    pub quarantined_modules: Vec<QuarantinedModule>,
+    pub elapsed_ms: u64,
+    pub outcome: Option<RunOutcome>,
+    pub exit_code: Option<i32>,
+}
+
+impl RunReport {
+    pub fn new() -> RunReport {
+        RunReport::default()
+    }
+
+    /// The outcome of the run from what was found in each stage.
+    pub fn outcome(&self) -> RunOutcome {
+        if self.interrupted {
+            RunOutcome::Interrupted
+        } else if self.termination == Some(ExplorationTermination::Aborted) {
+            RunOutcome::InvalidSolution
+        } else if self.identified == 0 {
+            RunOutcome::NoIdentification
+        } else if self.dominant_biddings == 0 {
+            RunOutcome::NoExplorer
+        } else if self.solutions == 0 {
+            match self.termination {
+                Some(ExplorationTermination::TimedOut) => RunOutcome::TimedOut,
+                Some(ExplorationTermination::Complete) => RunOutcome::Infeasible,
+                _ => RunOutcome::NoSolution,
+            }
+        } else if !self.uncovered_elements.is_empty() {
+            RunOutcome::PartialCoverage
+        } else {
+            RunOutcome::Success
+        }
+    }
+
+    /// Sets the outcome and the exit code of the report and returns the outcome.
+    pub fn conclude(&mut self) -> RunOutcome {
+        let outcome = self.outcome();
+        self.outcome = Some(outcome);
+        self.exit_code = Some(outcome.exit_code());
+        outcome
+    }
+
+    /// Writes the report to `report.json` in `run_path`, atomically replacing the previous one.
+    pub fn save(&self, run_path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .expect("Failed to serialize the run report. Should never fail.");
+        let tmp_path = run_path.join(format!("{}.tmp", REPORT_FILE_NAME));
+        std::fs::write(&tmp_path, json)?;
+        std::fs::rename(&tmp_path, run_path.join(REPORT_FILE_NAME))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn explored(termination: ExplorationTermination, solutions: u64) -> RunReport {
+        RunReport {
+            identified: 1,
+            dominant_biddings: 1,
+            termination: Some(termination),
+            solutions,
+            ..RunReport::default()
+        }
+    }
+
+    #[test]
+    fn outcome_without_solutions_depends_on_termination() {
+        assert_eq!(
+            explored(ExplorationTermination::Complete, 0).outcome(),
+            RunOutcome::Infeasible
+        );
+        assert_eq!(
+            explored(ExplorationTermination::TimedOut, 0).outcome(),
+            RunOutcome::TimedOut
+        );
+        assert_eq!(
+            explored(ExplorationTermination::Exhausted, 0).outcome(),
+            RunOutcome::NoSolution
+        );
+        assert_eq!(
+            explored(ExplorationTermination::Aborted, 0).outcome(),
+            RunOutcome::InvalidSolution
+        );
+    }
+
+    #[test]
+    fn outcome_with_solutions() {
+        assert_eq!(
+            explored(ExplorationTermination::TimedOut, 3).outcome(),
+            RunOutcome::Success
+        );
+        let mut report = explored(ExplorationTermination::Exhausted, 1);
+        report.uncovered_elements = vec!["a".to_string()];
+        assert_eq!(report.outcome(), RunOutcome::PartialCoverage);
+    }
+
+    #[test]
+    fn outcome_before_exploration() {
+        assert_eq!(RunReport::new().outcome(), RunOutcome::NoIdentification);
+        let report = RunReport {
+            identified: 2,
+            ..RunReport::default()
+        };
+        assert_eq!(report.outcome(), RunOutcome::NoExplorer);
+        let mut report = RunReport {
+            interrupted: true,
+            ..explored(ExplorationTermination::Interrupted, 0)
+        };
+        assert_eq!(report.conclude(), RunOutcome::Interrupted);
+        assert_eq!(report.exit_code, Some(interrupt::INTERRUPTED_EXIT_CODE));
+    }
+}
@@ -0,0 +1,361 @@
+//! Anytime performance traces of the exploration.
+//!
+//! Every solution that an explorer sends to [crate::exploration::MultiLevelCombinedExplorerIterator3]
+//! is recorded, whether it is kept or not, together with the explorer and the level that produced it,
+//! the wall-clock time since the trace started and whether it was dominated on arrival,
+//! i.e. dominated by or equal to a solution already in the Pareto front of the orchestrator.
+//!
+//! From the recorded entries, [ExplorationTrace::report] computes the time to the first solution of each
+//! explorer and the hypervolume of the Pareto front over time, which together show how good each
+//! explorer is as an anytime algorithm.
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceEntry {
+    /// The arrival order of the solution in the whole exploration.
+    pub index: usize,
+    pub elapsed_ms: u64,
+    /// The independent sub-problem being explored, see [crate::exploration::independent_subproblems].
+    pub subproblem: usize,
+    /// The level of the cooperative exploration, starting at 0 and increased at every restart.
+    pub level: u64,
+    pub explorer: String,
+    pub decision_model: String,
+    pub dominated_on_arrival: bool,
+    pub objectives: BTreeMap<String, f64>,
+}
+
+/// The hypervolume of the Pareto front of one sub-problem, taken after every solution that entered it.
+///
+/// All objectives are minimised and only the objectives shared by every solution recorded in the sub-problem
+/// are considered. The reference point is, for each objective, the worst value recorded in the sub-problem
+/// plus 10% of the recorded range, or plus one if all recorded values are the same.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HypervolumeCurve {
+    pub subproblem: usize,
+    pub objectives: Vec<String>,
+    pub reference: Vec<f64>,
+    /// Pairs of elapsed milliseconds and hypervolume.
+    pub points: Vec<(u64, f64)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct TraceReport {
+    pub entries: Vec<TraceEntry>,
+    /// The elapsed milliseconds until each explorer produced its first solution not dominated on arrival.
+    pub time_to_first_solution_ms: BTreeMap<String, u64>,
+    pub hypervolume: Vec<HypervolumeCurve>,
+}
+
+/// A trace shared between all the iterators of an exploration.
+///
+/// Clones record into the same trace, so one can be given to each sub-problem of a composed exploration.
+#[derive(Debug, Clone)]
+pub struct ExplorationTrace {
+    start: Instant,
+    entries: Arc<Mutex<Vec<TraceEntry>>>,
+}
+
+impl Default for ExplorationTrace {
+    fn default() -> Self {
+        ExplorationTrace::new()
+    }
+}
+
+impl ExplorationTrace {
+    pub fn new() -> ExplorationTrace {
+        ExplorationTrace {
+            start: Instant::now(),
+            entries: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    pub fn record(
+        &self,
+        subproblem: usize,
+        level: u64,
+        explorer: &str,
+        decision_model: &str,
+        dominated_on_arrival: bool,
+        objectives: &HashMap<String, f64>,
+    ) {
+        let elapsed_ms = self.start.elapsed().as_millis() as u64;
+        if let Ok(mut entries) = self.entries.lock() {
+            let index = entries.len();
+            entries.push(TraceEntry {
+                index,
+                elapsed_ms,
+                subproblem,
+                level,
+                explorer: explorer.to_owned(),
+                decision_model: decision_model.to_owned(),
+                dominated_on_arrival,
+                objectives: objectives.iter().map(|(k, v)| (k.to_owned(), *v)).collect(),
+            });
+        }
+    }
+
+    pub fn entries(&self) -> Vec<TraceEntry> {
+        self.entries.lock().map(|e| e.clone()).unwrap_or_default()
+    }
+
+    /// The explorer whose solution of `decision_model` with `objectives` entered the Pareto front,
+    /// i.e. the first one that sent it without it being dominated on arrival.
+    pub fn explorer_of(
+        &self,
+        decision_model: &str,
+        objectives: &HashMap<String, f64>,
+    ) -> Option<String> {
+        self.entries.lock().ok().and_then(|entries| {
+            entries
+                .iter()
+                .find(|e| {
+                    !e.dominated_on_arrival
+                        && e.decision_model == decision_model
+                        && e.objectives.len() == objectives.len()
+                        && objectives
+                            .iter()
+                            .all(|(k, v)| e.objectives.get(k) == Some(v))
+                })
+                .map(|e| e.explorer.to_owned())
+        })
+    }
+
+    pub fn report(&self) -> TraceReport {
+        let entries = self.entries();
+        let mut time_to_first_solution_ms = BTreeMap::new();
+        for entry in entries.iter().filter(|e| !e.dominated_on_arrival) {
+            time_to_first_solution_ms
+                .entry(entry.explorer.to_owned())
+                .or_insert(entry.elapsed_ms);
+        }
+        let subproblems: BTreeSet<usize> = entries.iter().map(|e| e.subproblem).collect();
+        let hypervolume = subproblems
+            .into_iter()
+            .map(|s| {
+                let subproblem_entries: Vec<&TraceEntry> =
+                    entries.iter().filter(|e| e.subproblem == s).collect();
+                hypervolume_curve(s, &subproblem_entries)
+            })
+            .collect();
+        TraceReport {
+            entries,
+            time_to_first_solution_ms,
+            hypervolume,
+        }
+    }
+}
+
+fn hypervolume_curve(subproblem: usize, entries: &[&TraceEntry]) -> HypervolumeCurve {
+    let objectives: Vec<String> = entries
+        .iter()
+        .map(|e| e.objectives.keys().cloned().collect::<BTreeSet<String>>())
+        .reduce(|a, b| a.intersection(&b).cloned().collect())
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+    let reference: Vec<f64> = objectives
+        .iter()
+        .map(|k| {
+            let (min, max) = entries
+                .iter()
+                .map(|e| e.objectives[k])
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                    (lo.min(v), hi.max(v))
+                });
+            if max > min {
+                max + (max - min) * 0.1
+            } else {
+                max + 1.0
+            }
+        })
+        .collect();
+    let mut front: Vec<Vec<f64>> = vec![];
+    let mut points = vec![];
+    for entry in entries.iter().filter(|e| !e.dominated_on_arrival) {
+        let point: Vec<f64> = objectives.iter().map(|k| entry.objectives[k]).collect();
+        if front
+            .iter()
+            .any(|p| matches!(dominance(p, &point), Some(Ordering::Less | Ordering::Equal)))
+        {
+            continue;
+        }
+        front.retain(|p| dominance(&point, p) != Some(Ordering::Less));
+        front.push(point);
+        points.push((entry.elapsed_ms, hypervolume(&front, &reference)));
+    }
+    HypervolumeCurve {
+        subproblem,
+        objectives,
+        reference,
+        points,
+    }
+}
+
+fn dominance(a: &[f64], b: &[f64]) -> Option<Ordering> {
+    let less = a.iter().zip(b).any(|(x, y)| x < y);
+    let greater = a.iter().zip(b).any(|(x, y)| x > y);
+    match (less, greater) {
+        (false, false) => Some(Ordering::Equal),
+        (true, false) => Some(Ordering::Less),
+        (false, true) => Some(Ordering::Greater),
+        (true, true) => None,
+    }
+}
+
+/// The hypervolume dominated by `points` and bounded by `reference`, for minimisation,
+/// computed by slicing along the last objective.
+pub fn hypervolume(points: &[Vec<f64>], reference: &[f64]) -> f64 {
+    let dims = reference.len();
+    let mut inside: Vec<&Vec<f64>> = points
+        .iter()
+        .filter(|p| p.len() == dims && p.iter().zip(reference).all(|(x, r)| x < r))
+        .collect();
+    if dims == 0 || inside.is_empty() {
+        return 0.0;
+    }
+    if dims == 1 {
+        return inside
+            .iter()
+            .map(|p| reference[0] - p[0])
+            .fold(0.0, f64::max);
+    }
+    inside.sort_by(|a, b| {
+        a[dims - 1]
+            .partial_cmp(&b[dims - 1])
+            .unwrap_or(Ordering::Equal)
+    });
+    let mut volume = 0.0;
+    for i in 0..inside.len() {
+        let upper = inside
+            .get(i + 1)
+            .map(|p| p[dims - 1])
+            .unwrap_or(reference[dims - 1]);
+        let height = upper - inside[i][dims - 1];
+        if height > 0.0 {
+            let projected: Vec<Vec<f64>> = inside[..=i]
+                .iter()
+                .map(|p| p[..dims - 1].to_vec())
+                .collect();
+            volume += hypervolume(&projected, &reference[..dims - 1]) * height;
+        }
+    }
+    volume
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+impl TraceReport {
+    /// Writes the entries, one per line, with the objectives as `name=value` pairs separated by `;`.
+    pub fn write_csv(&self, path: &Path) -> std::io::Result<()> {
+        let mut out =
+            String::from("index,elapsed_ms,subproblem,level,explorer,decision_model,dominated_on_arrival,objectives\n");
+        for e in &self.entries {
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\n",
+                e.index,
+                e.elapsed_ms,
+                e.subproblem,
+                e.level,
+                csv_field(&e.explorer),
+                csv_field(&e.decision_model),
+                e.dominated_on_arrival,
+                csv_field(
+                    &e.objectives
+                        .iter()
+                        .map(|(k, v)| format!("{}={}", k, v))
+                        .collect::<Vec<String>>()
+                        .join(";")
+                )
+            ));
+        }
+        std::fs::write(path, out)
+    }
+
+    /// Writes the hypervolume curves, one point per line.
+    pub fn write_hypervolume_csv(&self, path: &Path) -> std::io::Result<()> {
+        let mut out = String::from("subproblem,elapsed_ms,hypervolume\n");
+        for curve in &self.hypervolume {
+            for (elapsed_ms, hv) in &curve.points {
+                out.push_str(&format!("{},{},{}\n", curve.subproblem, elapsed_ms, hv));
+            }
+        }
+        std::fs::write(path, out)
+    }
+
+    pub fn write_json(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .expect("Failed to serialize the exploration trace. Should never fail.");
+        std::fs::write(path, json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {} but got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn hypervolume_of_one_objective() {
+        assert_close(hypervolume(&[vec![3.0], vec![1.0]], &[4.0]), 3.0);
+    }
+
+    #[test]
+    fn hypervolume_of_two_objectives() {
+        // the union of the boxes [1, 4] x [3, 4] and [2, 4] x [1, 4]
+        assert_close(
+            hypervolume(&[vec![1.0, 3.0], vec![2.0, 1.0]], &[4.0, 4.0]),
+            7.0,
+        );
+        // a dominated point adds nothing
+        assert_close(
+            hypervolume(
+                &[vec![1.0, 3.0], vec![2.0, 1.0], vec![3.0, 3.0]],
+                &[4.0, 4.0],
+            ),
+            7.0,
+        );
+    }
+
+    #[test]
+    fn hypervolume_of_three_objectives() {
+        assert_close(hypervolume(&[vec![0.0, 0.0, 0.0]], &[1.0, 2.0, 3.0]), 6.0);
+        // the union of two unit cubes overlapping in half of their volume
+        assert_close(
+            hypervolume(
+                &[vec![0.0, 0.0, 0.5], vec![0.5, 0.0, 0.0]],
+                &[1.0, 1.0, 1.0],
+            ),
+            0.75,
+        );
+    }
+
+    #[test]
+    fn hypervolume_ignores_points_outside_the_reference() {
+        assert_close(hypervolume(&[vec![5.0, 1.0]], &[4.0, 4.0]), 0.0);
+        assert_close(hypervolume(&[vec![1.0]], &[4.0, 4.0]), 0.0);
+        assert_close(hypervolume(&[], &[4.0, 4.0]), 0.0);
+    }
+}
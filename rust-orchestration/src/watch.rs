@@ -0,0 +1,126 @@
+//! Watching the inputs of the orchestration for changes, for the `--watch` mode of the orchestrator.
+//!
+//! In watch mode, the orchestrator runs in cycles: it identifies, explores and reverse identifies the
+//! inputs, then waits for them to change and starts over. A change during a cycle cancels its
+//! exploration with [interrupt::cancel_exploration], so that the next cycle starts right away.
+//!
+//! The inputs are polled and compared by content, so that saving a file without changing it does not
+//! start a new cycle. A change is only reported once the inputs stay the same for one poll, as editors
+//! often write a file in several steps.
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use sha3::Digest;
+use tracing::debug;
+
+use crate::interrupt;
+
+/// The contents of the inputs at one point in time, by their hash. Inputs that cannot be read have no hash.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct InputSnapshot {
+    hashes: BTreeMap<PathBuf, Option<Vec<u8>>>,
+}
+
+impl InputSnapshot {
+    pub fn take(paths: &[PathBuf]) -> InputSnapshot {
+        InputSnapshot {
+            hashes: paths
+                .iter()
+                .map(|p| {
+                    (
+                        p.to_owned(),
+                        std::fs::read(p)
+                            .ok()
+                            .map(|bytes| sha3::Sha3_256::digest(bytes).to_vec()),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.hashes.keys().cloned().collect()
+    }
+
+    /// The inputs whose contents differ between the snapshots, including those only in one of them.
+    pub fn changed(&self, other: &InputSnapshot) -> Vec<PathBuf> {
+        self.hashes
+            .keys()
+            .chain(other.hashes.keys())
+            .filter(|p| self.hashes.get(*p) != other.hashes.get(*p))
+            .cloned()
+            .collect::<std::collections::BTreeSet<PathBuf>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Blocks until the inputs differ from `snapshot` and then stay the same for one `poll`,
+/// returning their new snapshot, or `None` if the orchestration is interrupted first.
+pub fn wait_for_change(snapshot: &InputSnapshot, poll: Duration) -> Option<InputSnapshot> {
+    let paths = snapshot.paths();
+    let mut last = snapshot.to_owned();
+    loop {
+        std::thread::sleep(poll);
+        if interrupt::is_interrupted() {
+            return None;
+        }
+        let current = InputSnapshot::take(&paths);
+        if current != *snapshot && current == last {
+            return Some(current);
+        }
+        last = current;
+    }
+}
+
+/// Cancels the exploration as soon as the inputs differ from a snapshot, until it is dropped.
+pub struct CancelOnChange {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl CancelOnChange {
+    pub fn start(snapshot: InputSnapshot, poll: Duration) -> CancelOnChange {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let handle = std::thread::spawn(move || {
+            let paths = snapshot.paths();
+            while !thread_stop.load(Ordering::SeqCst) && !interrupt::should_stop() {
+                std::thread::sleep(poll);
+                let changed = InputSnapshot::take(&paths).changed(&snapshot);
+                if !changed.is_empty() {
+                    debug!(
+                        "Inputs {} changed, cancelling the exploration",
+                        changed
+                            .iter()
+                            .map(|p| p.display().to_string())
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    );
+                    interrupt::cancel_exploration();
+                }
+            }
+        });
+        CancelOnChange {
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for CancelOnChange {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}